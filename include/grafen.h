@@ -0,0 +1,259 @@
+/*
+ * C interface to grafen, built with the `capi` feature as a shared library:
+ *
+ *     cargo rustc --lib --release --features capi --crate-type cdylib
+ *
+ * and linked with the resulting `libgrafen` shared library.
+ *
+ * Databases and systems are opaque handles which are created and freed by the
+ * functions below. Functions which can fail return a `GrafenStatus`, or a null
+ * pointer if they create a handle. The message of the last error on the calling
+ * thread is available from `grafen_last_error`.
+ *
+ * Lengths are in nm. Coordinates are arrays of three values (x, y, z).
+ */
+
+#ifndef GRAFEN_H
+#define GRAFEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct GrafenDataBase GrafenDataBase;
+typedef struct GrafenSystem GrafenSystem;
+
+typedef enum GrafenStatus {
+    GRAFEN_OK = 0,
+    /* A required pointer was null. */
+    GRAFEN_ERROR_NULL_POINTER = 1,
+    /* An argument was invalid, eg. a string which is not UTF-8. */
+    GRAFEN_ERROR_INVALID_ARGUMENT = 2,
+    /* A file could not be read or written. */
+    GRAFEN_ERROR_IO = 3,
+    /* An internal error occurred. The handles which were passed to the call
+     * may be in an inconsistent state. */
+    GRAFEN_ERROR_RUN = 4,
+    /* The contents of a file could not be parsed. */
+    GRAFEN_ERROR_PARSE = 5,
+    /* A database or one of its definitions is invalid. */
+    GRAFEN_ERROR_DATABASE = 6,
+    /* The lattice of a surface definition is invalid. */
+    GRAFEN_ERROR_LATTICE = 7
+} GrafenStatus;
+
+typedef enum GrafenFillType {
+    /* Use the default fill type of the definition. */
+    GRAFEN_FILL_DEFAULT = 0,
+    /* A number of residues. */
+    GRAFEN_FILL_NUMBER = 1,
+    /* A number density (1/nm^3). */
+    GRAFEN_FILL_DENSITY = 2,
+    /* A mass density (g/cm^3). */
+    GRAFEN_FILL_MASS_DENSITY = 3,
+    /* A molar concentration (mol/L). */
+    GRAFEN_FILL_MOLARITY = 4
+} GrafenFillType;
+
+typedef enum GrafenInclusionPolicy {
+    /* Any atom of the residue is inside. */
+    GRAFEN_INCLUDE_ANY_ATOM = 0,
+    /* All atoms of the residue are inside. */
+    GRAFEN_INCLUDE_ALL_ATOMS = 1,
+    /* The center of geometry of the residue is inside. */
+    GRAFEN_INCLUDE_CENTER_OF_GEOMETRY = 2,
+    /* The center of mass of the residue is inside. */
+    GRAFEN_INCLUDE_CENTER_OF_MASS = 3,
+    /* The first atom of the residue is inside. */
+    GRAFEN_INCLUDE_FIRST_ATOM = 4
+} GrafenInclusionPolicy;
+
+/* Message of the last error on this thread, or null. Valid until the next error. */
+const char *grafen_last_error(void);
+
+/* Read a database from a file. Returns null if it could not be read. */
+GrafenDataBase *grafen_database_read(const char *path);
+
+/* Get the built-in library of definitions. */
+GrafenDataBase *grafen_database_library(void);
+
+/* Free a database. Null pointers are ignored. */
+void grafen_database_free(GrafenDataBase *database);
+
+/* Create an empty system which constructs components from a copy of the database. */
+GrafenSystem *grafen_system_new(const char *title, const GrafenDataBase *database);
+
+/* Free a system. Null pointers are ignored. */
+void grafen_system_free(GrafenSystem *system);
+
+/* Construct a sheet from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_sheet(GrafenSystem *system,
+                                     const char *name,
+                                     const double origin[3],
+                                     double length,
+                                     double width);
+
+/* Construct a sheet with a blue noise lattice of `number` residues from a named
+ * definition and add it to the system. */
+GrafenStatus grafen_system_add_blue_noise_sheet(GrafenSystem *system,
+                                                const char *name,
+                                                const double origin[3],
+                                                double length,
+                                                double width,
+                                                uint64_t number);
+
+/* Construct a cuboid surface from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_surface_cuboid(GrafenSystem *system,
+                                              const char *name,
+                                              const double origin[3],
+                                              const double size[3]);
+
+/* Construct a cylinder surface from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_cylinder(GrafenSystem *system,
+                                        const char *name,
+                                        const double origin[3],
+                                        double radius,
+                                        double height);
+
+/* Construct a spherical shell from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_sphere_shell(GrafenSystem *system,
+                                            const char *name,
+                                            const double origin[3],
+                                            double radius);
+
+/* Construct an ellipsoidal shell from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_ellipsoid_shell(GrafenSystem *system,
+                                               const char *name,
+                                               const double origin[3],
+                                               const double semi_axes[3]);
+
+/* Construct a lipid bilayer from a named definition and add it to the system. */
+GrafenStatus grafen_system_add_bilayer(GrafenSystem *system,
+                                       const char *name,
+                                       const double origin[3],
+                                       double length,
+                                       double width);
+
+/* Fill a cuboid from a named definition and add it to the system.
+ * The fill type is one of the GrafenFillType values, otherwise
+ * GRAFEN_ERROR_INVALID_ARGUMENT is returned. The value is ignored for
+ * GRAFEN_FILL_DEFAULT and must be a non-negative integer for GRAFEN_FILL_NUMBER,
+ * otherwise GRAFEN_ERROR_INVALID_ARGUMENT is returned. */
+GrafenStatus grafen_system_fill_cuboid(GrafenSystem *system,
+                                       const char *name,
+                                       const double origin[3],
+                                       const double size[3],
+                                       int fill_type,
+                                       double value);
+
+/* Fill a cylinder from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_cylinder(GrafenSystem *system,
+                                         const char *name,
+                                         const double origin[3],
+                                         double radius,
+                                         double height,
+                                         int fill_type,
+                                         double value);
+
+/* Fill a sphere from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_sphere(GrafenSystem *system,
+                                       const char *name,
+                                       const double origin[3],
+                                       double radius,
+                                       int fill_type,
+                                       double value);
+
+/* Fill an ellipsoid from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_ellipsoid(GrafenSystem *system,
+                                          const char *name,
+                                          const double origin[3],
+                                          const double semi_axes[3],
+                                          int fill_type,
+                                          double value);
+
+/* Fill a cone from a named definition and add it to the system. A cone with a
+ * top radius is truncated into a frustum.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_cone(GrafenSystem *system,
+                                     const char *name,
+                                     const double origin[3],
+                                     double bottom_radius,
+                                     double top_radius,
+                                     double height,
+                                     int fill_type,
+                                     double value);
+
+/* Fill a torus from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_torus(GrafenSystem *system,
+                                      const char *name,
+                                      const double origin[3],
+                                      double major_radius,
+                                      double minor_radius,
+                                      int fill_type,
+                                      double value);
+
+/* Fill a capsule from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_capsule(GrafenSystem *system,
+                                        const char *name,
+                                        const double origin[3],
+                                        double radius,
+                                        double height,
+                                        int fill_type,
+                                        double value);
+
+/* Fill a region of a slab from a named definition and add it to the system.
+ * The fill type and value are used as for grafen_system_fill_cuboid. */
+GrafenStatus grafen_system_fill_slab(GrafenSystem *system,
+                                     const char *name,
+                                     const double origin[3],
+                                     double thickness,
+                                     double length,
+                                     double width,
+                                     int fill_type,
+                                     double value);
+
+/* Read the configuration file of a named definition, reconstruct it into the
+ * volume of the definition and add it to the system at the origin. Residues are
+ * kept by the policy, one of the GrafenInclusionPolicy values. */
+GrafenStatus grafen_system_add_configuration(GrafenSystem *system,
+                                             const char *name,
+                                             const double origin[3],
+                                             int policy);
+
+/* Remove the residues of component `index` which are inside the volume component
+ * `volume_index` by the policy, one of the GrafenInclusionPolicy values, with a
+ * margin around the volume. */
+GrafenStatus grafen_system_prune_against(GrafenSystem *system,
+                                         size_t index,
+                                         size_t volume_index,
+                                         double margin,
+                                         int policy);
+
+/* Number of components in the system. */
+size_t grafen_system_num_components(const GrafenSystem *system);
+
+/* Number of atoms in the system. */
+uint64_t grafen_system_num_atoms(const GrafenSystem *system);
+
+/* Copy the atom positions as x, y, z triplets into an array of length `len`,
+ * which must be at least 3 * grafen_system_num_atoms(system). */
+GrafenStatus grafen_system_coordinates(const GrafenSystem *system,
+                                       double *coordinates,
+                                       size_t len);
+
+/* Write the system to a file, in the format of the path extension (.gro or .pdb). */
+GrafenStatus grafen_system_write(GrafenSystem *system, const char *path);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GRAFEN_H */
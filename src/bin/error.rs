@@ -109,7 +109,7 @@ impl From<io::Error> for GrafenCliError {
 
 impl From<GrafenError> for GrafenCliError {
     fn from(err: GrafenError) -> GrafenCliError {
-        GrafenCliError::RunError(err.description().to_string())
+        GrafenCliError::RunError(err.to_string())
     }
 }
 
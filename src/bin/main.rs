@@ -10,16 +10,32 @@ use crate::{
 };
 
 use grafen::{
-    database::{read_database, ComponentEntry, DataBase},
+    coord::Coord,
+    database::{
+        export_bundle, import_bundle, merge_database, read_database, ComponentEntry, Conflict,
+        DataBase, MergeAction, Scope, Severity,
+    },
+    describe::Describe,
     read_conf::ReadConf,
+    read_system::{read_index_groups, read_system, IndexGroup, SplitRule},
+    session::{read_session, Session},
+    system::BoxSetting,
 };
 
 use colored::*;
 use dirs;
-use std::{env::current_dir, fs::DirBuilder, path::PathBuf, process};
+use std::{
+    env::current_dir,
+    fs::DirBuilder,
+    path::{Path, PathBuf},
+    process, result,
+    str::FromStr,
+};
 use structopt::StructOpt;
 
 const DEFAULT_DBNAME: &str = "database.json";
+const PROJECT_DBNAME: &str = "grafen.json";
+const DEFAULT_OUTPUT: &str = "conf.gro";
 
 /// The program run configuration.
 pub struct Config {
@@ -31,34 +47,79 @@ pub struct Config {
     pub components: Vec<ComponentEntry>,
     /// Database of residue and substrate definitions.
     pub database: DataBase,
+    /// Path to the session file which the system is saved to.
+    pub session_path: Option<PathBuf>,
+    /// How the size of the system box is set.
+    pub box_setting: BoxSetting,
 }
 
 impl Config {
-    /// Parse the input command line arguments and read the `DataBase`.
+    /// Read the `DataBase` and input configurations from the command line arguments.
     ///
     /// # Errors
-    /// Returns an error if the `DataBase` (if given as an input) could not be read.
-    fn new() -> Result<Config> {
-        eprintln!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    /// Returns an error if the `DataBase` (if given as an input) or an existing session
+    /// could not be read. Read-only database layers which cannot be read are skipped
+    /// with a warning.
+    fn new(options: CliOptions) -> Result<Config> {
+        let session = match options.session {
+            Some(ref path) if path.exists() => Some(resume_session(path)?),
+            _ => None,
+        };
+
+        let (title, output_path, mut session_components, box_setting) = match session {
+            Some(session) => (
+                session.title,
+                session.output_path,
+                session.components,
+                session.box_setting,
+            ),
+            None => (
+                "System created by grafen".to_string(),
+                PathBuf::from(DEFAULT_OUTPUT),
+                Vec::new(),
+                BoxSetting::Auto,
+            ),
+        };
+        let title = options.title.unwrap_or(title);
+        let output_path = options.output.unwrap_or(output_path);
 
-        let options = CliOptions::from_args();
+        let box_setting = match (options.box_size, options.padding) {
+            (Some(size), _) => BoxSetting::Fixed(size),
+            (None, Some(padding)) => BoxSetting::Padded(padding),
+            (None, None) => box_setting,
+        };
 
-        let output_path = options.output;
-        let title = options.title.unwrap_or("System created by grafen".into());
+        let mut database = read_database_layers(options.database, options.shared_database)?;
+        print_diagnostics(&database);
+
+        for path in options.import {
+            import_database(&mut database, &path, options.on_conflict)?;
+        }
 
-        let mut database = match options.database {
-            Some(path) => read_database(&path).map_err(|err| GrafenCliError::from(err)),
-            None => read_or_create_default_database(),
-        }?;
+        if let Some(ref path) = options.system {
+            let rule = get_split_rule(options.index.as_ref(), &options.groups)?;
+            let mut system = read_system(&path, &rule)?;
+
+            eprintln!(
+                "Split system '{}' into {} components.\n",
+                path.display(),
+                system.components.len()
+            );
 
-        let (components, mut entries) = read_input_configurations(options.input_confs);
+            session_components.append(&mut system.components);
+        }
+
+        let (mut components, mut entries) = read_input_configurations(options.input_confs);
         database.component_defs.append(&mut entries);
+        session_components.append(&mut components);
 
         Ok(Config {
             title,
             output_path,
-            components,
+            components: session_components,
             database,
+            session_path: options.session,
+            box_setting,
         })
     }
 }
@@ -70,29 +131,279 @@ struct CliOptions {
     #[structopt(short = "t", long = "title")]
     /// Title of output system
     title: Option<String>,
-    #[structopt(
-        short = "o",
-        long = "output",
-        default_value = "conf.gro",
-        parse(from_os_str)
-    )]
-    /// Output configuration file
-    output: PathBuf,
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    /// Output configuration file [default: conf.gro, or that of a resumed session]
+    output: Option<PathBuf>,
+    #[structopt(short = "s", long = "session", parse(from_os_str))]
+    /// Session file to resume the system from, if it exists, and to save it to
+    session: Option<PathBuf>,
+    #[structopt(long = "box", conflicts_with = "padding")]
+    /// Fixed size of the system box, as "x y z" in nm
+    box_size: Option<Coord>,
+    #[structopt(long = "padding", conflicts_with = "box_size")]
+    /// Vacuum padding added to the system box along each axis, as "x y z" in nm
+    padding: Option<Coord>,
     #[structopt(short = "d", long = "database", parse(from_os_str))]
     /// Path to residue and component database
     database: Option<PathBuf>,
+    #[structopt(
+        long = "shared-database",
+        env = "GRAFEN_SHARED_DATABASE",
+        parse(from_os_str)
+    )]
+    /// Path to a read-only database shared with others
+    shared_database: Option<PathBuf>,
     #[structopt(short = "c", long = "conf", parse(from_os_str))]
     /// Path to input configuration files to add as components
     input_confs: Vec<PathBuf>,
+    #[structopt(long = "system", parse(from_os_str))]
+    /// Path to a full system to split into components by residue name blocks or index groups
+    system: Option<PathBuf>,
+    #[structopt(short = "n", long = "index", parse(from_os_str))]
+    /// Index file with groups to split the system by
+    index: Option<PathBuf>,
+    #[structopt(long = "group")]
+    /// Names of the index groups to split the system by
+    groups: Vec<String>,
+    #[structopt(short = "i", long = "import", parse(from_os_str))]
+    /// Path to databases or bundle directories to import residue and component definitions from
+    import: Vec<PathBuf>,
+    #[structopt(
+        long = "on-conflict",
+        default_value = "skip",
+        raw(possible_values = "&[\"skip\", \"overwrite\", \"rename\"]")
+    )]
+    /// How to import definitions with the same name as existing ones
+    on_conflict: ConflictPolicy,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+/// Commands which are run instead of the interactive menu
+enum Command {
+    #[structopt(name = "db")]
+    /// Manage databases
+    DataBase(DataBaseCommand),
+}
+
+#[derive(StructOpt, Debug)]
+enum DataBaseCommand {
+    #[structopt(name = "check")]
+    /// Check the definitions of databases for problems
+    Check {
+        #[structopt(parse(from_os_str))]
+        /// Path to the database to check (default: the databases which would be read)
+        path: Option<PathBuf>,
+    },
+    #[structopt(name = "export")]
+    /// Export the database with its configuration files to a bundle directory
+    Export {
+        #[structopt(parse(from_os_str))]
+        /// Directory to write the bundle to
+        dir: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+/// How to resolve conflicting definitions when importing databases from the command line.
+enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> result::Result<ConflictPolicy, String> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "rename" => Ok(ConflictPolicy::Rename),
+            _ => Err(format!("unknown conflict policy '{}'", s)),
+        }
+    }
+}
+
+impl ConflictPolicy {
+    fn resolve(&self, conflict: &Conflict) -> MergeAction {
+        match self {
+            &ConflictPolicy::Skip => MergeAction::Skip,
+            &ConflictPolicy::Overwrite => MergeAction::Overwrite,
+            &ConflictPolicy::Rename => MergeAction::Rename(conflict.suggested_name.clone()),
+        }
+    }
 }
 
 fn main() {
-    if let Err(err) = Config::new().and_then(|conf| ui::user_menu(conf)) {
+    eprintln!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+    let mut options = CliOptions::from_args();
+
+    let result = match options.command.take() {
+        Some(Command::DataBase(DataBaseCommand::Check { path })) => check_databases(path, options)
+            .and_then(|num_errors| {
+                if num_errors > 0 {
+                    Err(GrafenCliError::RunError(format!(
+                        "found {} errors in the database definitions",
+                        num_errors
+                    )))
+                } else {
+                    Ok(())
+                }
+            }),
+        Some(Command::DataBase(DataBaseCommand::Export { dir })) => export_database(&dir, options),
+        None => Config::new(options).and_then(|conf| ui::user_menu(conf)),
+    };
+
+    if let Err(err) = result {
         eprintln!("{}", err);
         process::exit(1);
     }
 }
 
+/// Check a database, or all database layers which would be read, and print any problems.
+///
+/// Returns the number of errors which were found.
+fn check_databases(path: Option<PathBuf>, options: CliOptions) -> Result<usize> {
+    let database = match path {
+        Some(path) => DataBase {
+            scope: Scope::File,
+            ..read_database(&path)?
+        },
+        None => read_database_layers(options.database, options.shared_database)?,
+    };
+
+    let num_errors = print_diagnostics(&database);
+
+    if num_errors == 0 {
+        eprintln!("No errors were found.");
+    }
+
+    Ok(num_errors)
+}
+
+/// Print the problems with the definitions in all database layers.
+///
+/// Returns the number of errors.
+fn print_diagnostics(database: &DataBase) -> usize {
+    let mut num_errors = 0;
+
+    for layer in Some(database).into_iter().chain(database.layers.iter()) {
+        let diagnostics = layer.validate();
+
+        if diagnostics.is_empty() {
+            continue;
+        }
+
+        eprintln!(
+            "Problems with definitions in database {} [{}]:",
+            layer.get_path_pretty(),
+            layer.scope
+        );
+
+        for diagnostic in diagnostics {
+            let color = match diagnostic.severity {
+                Severity::Error => {
+                    num_errors += 1;
+                    "red"
+                }
+                Severity::Warning => "yellow",
+            };
+
+            eprintln!("  {}", diagnostic.describe().color(color));
+        }
+
+        eprint!("\n");
+    }
+
+    num_errors
+}
+
+/// Get the rule to split a system by: blocks of residue names if no index file is given,
+/// otherwise the selected groups of the index file.
+fn get_split_rule(index: Option<&PathBuf>, names: &[String]) -> Result<SplitRule> {
+    let path = match index {
+        Some(path) => path,
+        None => return Ok(SplitRule::ResidueBlocks),
+    };
+
+    if names.is_empty() {
+        return Err(GrafenCliError::RunError(
+            "select the index groups to split the system by with --group".to_string(),
+        ));
+    }
+
+    let groups = read_index_groups(&path)?;
+
+    let selected = names
+        .iter()
+        .map(|name| {
+            groups
+                .iter()
+                .find(|group| &group.name == name)
+                .cloned()
+                .ok_or_else(|| {
+                    GrafenCliError::RunError(format!(
+                        "no group '{}' in index file '{}'",
+                        name,
+                        path.display()
+                    ))
+                })
+        })
+        .collect::<Result<Vec<IndexGroup>>>()?;
+
+    Ok(SplitRule::IndexGroups(selected))
+}
+
+/// Read a saved session to continue constructing its system.
+fn resume_session(path: &Path) -> Result<Session> {
+    let session = read_session(path)?;
+
+    eprintln!(
+        "Resumed session from '{}' with {} components.\n",
+        path.display(),
+        session.components.len()
+    );
+
+    Ok(session)
+}
+
+/// Export the writable database and its configuration files to a bundle directory.
+fn export_database(dir: &PathBuf, options: CliOptions) -> Result<()> {
+    let database = read_database_layers(options.database, options.shared_database)?;
+    let bundle = export_bundle(&database, &dir)?;
+
+    eprintln!(
+        "Exported {} residue and {} component definitions to '{}'.",
+        bundle.residue_defs.len(),
+        bundle.component_defs.len(),
+        dir.display()
+    );
+
+    Ok(())
+}
+
+/// Import the definitions of a database file, or of a bundle if the path is a directory.
+fn import_database(database: &mut DataBase, path: &PathBuf, policy: ConflictPolicy) -> Result<()> {
+    let resolve = |conflict: &Conflict| policy.resolve(conflict);
+
+    let summary = if path.is_dir() {
+        import_bundle(database, &path, resolve)?
+    } else {
+        merge_database(database, &read_database(&path)?, resolve)
+    };
+
+    eprintln!(
+        "Imported definitions from '{}': {}",
+        path.display(),
+        summary.describe()
+    );
+
+    Ok(())
+}
+
 fn read_input_configurations(confs: Vec<PathBuf>) -> (Vec<ComponentEntry>, Vec<ComponentEntry>) {
     let mut configurations = Vec::new();
 
@@ -126,6 +437,67 @@ fn read_input_configurations(confs: Vec<PathBuf>) -> (Vec<ComponentEntry>, Vec<C
     (components, entries)
 }
 
+/// Read the writable database and stack the read-only databases below it.
+///
+/// The writable database is the one given by the user, or the project database
+/// in the current directory if it exists, or else the default user database.
+/// Below it are the project, user and shared databases which are not writable.
+fn read_database_layers(
+    database_path: Option<PathBuf>,
+    shared_path: Option<PathBuf>,
+) -> Result<DataBase> {
+    let project_path = current_dir()
+        .map(|dir| dir.join(PROJECT_DBNAME))
+        .ok()
+        .filter(|path| path.is_file());
+
+    let mut database = match (database_path, project_path.as_ref()) {
+        (Some(path), _) => read_database(&path).map(|database| DataBase {
+            scope: Scope::File,
+            ..database
+        })?,
+        (None, Some(path)) => read_database(&path).map(|database| DataBase {
+            scope: Scope::Project,
+            ..database
+        })?,
+        (None, None) => read_or_create_default_database()?,
+    };
+
+    if database.scope == Scope::File {
+        if let Some(path) = project_path {
+            add_database_layer(&mut database, &path, Scope::Project);
+        }
+    }
+
+    if database.scope != Scope::User {
+        if let Some(path) = get_default_database_path().filter(|path| path.is_file()) {
+            add_database_layer(&mut database, &path, Scope::User);
+        }
+    }
+
+    if let Some(path) = shared_path {
+        add_database_layer(&mut database, &path, Scope::Shared);
+    }
+
+    Ok(database)
+}
+
+fn add_database_layer(database: &mut DataBase, path: &PathBuf, scope: Scope) {
+    match read_database(&path) {
+        Ok(layer) => database.add_layer(DataBase { scope, ..layer }),
+        Err(err) => eprintln!(
+            "{}",
+            format!(
+                "Warning: Could not read the {} database at '{}' ({}). Skipping it.",
+                scope,
+                path.display(),
+                err
+            )
+            .color("yellow")
+        ),
+    }
+}
+
 fn read_or_create_default_database() -> Result<DataBase> {
     let path = match get_default_database_path() {
         Some(path) => path,
@@ -4,16 +4,18 @@ use crate::{
     error::{GrafenCliError, Result},
     ui::utils::{
         get_position_from_user, get_value_from_user, print_description, select_command,
-        select_item, select_item_index, MenuResult,
+        select_inclusion_policy, select_item, select_item_index, warn_about_unknown_masses,
+        MenuResult,
     },
 };
 
 use grafen::{
-    coord::Direction,
-    coord::{Coord, Translate},
+    coord::Translate,
     database::*,
+    describe::Describe,
+    read_conf::WrapCenter,
     system::*,
-    volume::{prune_residues_from_volume, Contains, Cuboid, Cylinder},
+    volume::{keep_residues_within_volume, prune_residues_from_volume, Combination, Contains},
 };
 use std::borrow::Borrow;
 
@@ -48,11 +50,13 @@ pub fn user_menu(components: &mut Vec<ComponentEntry>) -> MenuResult {
         },
 
         PruneByVolume, "Remove residues which overlap another component" => {
-            let volume: Box<Contains> = get_volume_from_user(components)?;
+            let volume: Box<Contains> = get_combined_volume_from_user(components)?;
+            let policy = select_inclusion_policy("Remove residues if")?;
+            warn_about_unknown_masses(&component, policy);
             let num_before = component.num_atoms();
 
             let remaining_residues = prune_residues_from_volume::<_, Contains>(
-                &component, volume.borrow());
+                &component, volume.borrow(), policy);
 
             component.assign_residues(&remaining_residues);
 
@@ -61,6 +65,26 @@ pub fn user_menu(components: &mut Vec<ComponentEntry>) -> MenuResult {
             Ok(Some(format!("Removed {} atoms from the component", num_before - num_after)))
         },
 
+        KeepWithinVolume, "Remove residues which are outside of another component" => {
+            let volume: Box<Contains> = get_combined_volume_from_user(components)?;
+            let policy = select_inclusion_policy("Keep residues if")?;
+            warn_about_unknown_masses(&component, policy);
+            let num_before = component.num_atoms();
+
+            let remaining_residues = keep_residues_within_volume::<_, Contains>(
+                &component, volume.borrow(), policy);
+
+            component.assign_residues(&remaining_residues);
+
+            let num_after = component.num_atoms();
+
+            Ok(Some(format!("Removed {} atoms from the component", num_before - num_after)))
+        },
+
+        WrapIntoBox, "Wrap residues of a read configuration into its periodic box" => {
+            wrap_component(&mut component)
+        },
+
         QuitAndSave, "Finish editing component" => {
             components[index] = component;
             return Ok(Some("Finished editing component".to_string()));
@@ -72,90 +96,132 @@ pub fn user_menu(components: &mut Vec<ComponentEntry>) -> MenuResult {
     ];
 }
 
-/// Ask the user to select a volume object that has been constructed.
-fn get_volume_from_user(components: &[ComponentEntry]) -> Result<Box<Contains>> {
-    let volume_components = get_volume_objects(components);
-    let component = select_item(&volume_components, Some("Select component to cut with"))?.clone();
-
-    let margin: f64 = get_value_from_user("Margin around volume to also exclude (nm)")?;
+#[derive(Clone, Copy, Debug)]
+enum WrapCenterMenu {
+    Geometry,
+    FirstAtom,
+}
 
+/// Wrap the residues of a read configuration into its box. Residues are kept whole
+/// and wrapped by a center which the user selects.
+///
+/// Other components are not wrapped, since many of them have their origin at their
+/// center and would be broken apart by wrapping their coordinates into the box.
+fn wrap_component(component: &mut ComponentEntry) -> MenuResult {
     match component {
-        ComponentEntry::VolumeCuboid(mut obj) => {
-            let coord_margins = Coord::new(margin, margin, margin);
-            obj.origin -= coord_margins;
-            obj.size += coord_margins * 2.0;
+        ComponentEntry::ConfigurationFile(conf) => {
+            let (commands, item_texts) = create_menu_items![
+                (
+                    WrapCenterMenu::Geometry,
+                    "Wrap residues by their center of geometry"
+                ),
+                (
+                    WrapCenterMenu::FirstAtom,
+                    "Wrap residues by their first atom"
+                )
+            ];
+
+            let center = match select_command(item_texts, commands)? {
+                WrapCenterMenu::Geometry => WrapCenter::Geometry,
+                WrapCenterMenu::FirstAtom => WrapCenter::FirstAtom,
+            };
+
+            conf.wrap_residues(center);
 
-            Ok(Box::new(obj))
+            Ok(None)
         }
-        ComponentEntry::VolumeCylinder(mut obj) => {
-            obj.radius += margin;
-            obj.height += 2.0 * margin;
+        _ => Err(GrafenCliError::RunError(
+            "Only components read from configuration files can be wrapped into their box"
+                .to_string(),
+        )),
+    }
+}
+
+/// Ask the user to construct a volume from constructed volume objects, which can be
+/// combined with others using boolean operations.
+fn get_combined_volume_from_user(components: &[ComponentEntry]) -> Result<Box<Contains>> {
+    let (mut volume, mut description) = get_volume_from_user(components)?;
+
+    create_menu![
+        @pre: {
+            eprintln!("Volume: {}", description);
+            eprint!("\n");
+        };
+
+        Finish, "Use the current volume" => {
+            return Ok(volume);
+        },
 
-            match obj.alignment {
-                Direction::X => obj.translate_in_place(Coord::new(-margin, 0.0, 0.0)),
-                Direction::Y => obj.translate_in_place(Coord::new(0.0, -margin, 0.0)),
-                Direction::Z => obj.translate_in_place(Coord::new(0.0, 0.0, -margin)),
-            }
+        Union, "Add the space of another component (union)" => {
+            let (other, other_description) = get_volume_from_user(components)?;
+            volume = Box::new(Combination::Union(volume, other));
+            description = format!("({} OR {})", description, other_description);
 
-            Ok(Box::new(obj))
+            Ok(None)
+        },
+
+        Intersection, "Only use the space shared with another component (intersection)" => {
+            let (other, other_description) = get_volume_from_user(components)?;
+            volume = Box::new(Combination::Intersection(volume, other));
+            description = format!("({} AND {})", description, other_description);
+
+            Ok(None)
+        },
+
+        Difference, "Remove the space of another component (difference)" => {
+            let (other, other_description) = get_volume_from_user(components)?;
+            volume = Box::new(Combination::Difference(volume, other));
+            description = format!("({} NOT {})", description, other_description);
+
+            Ok(None)
+        },
+
+        Complement, "Invert the volume to all space outside of it (complement)" => {
+            volume = Box::new(Combination::Complement(volume));
+            description = format!("(NOT {})", description);
+
+            Ok(None)
         }
-        _ => Err(GrafenCliError::RunError(String::from(
+    ];
+}
+
+/// Ask the user to select a volume object that has been constructed.
+///
+/// Returns the volume along with a short description of it.
+fn get_volume_from_user(components: &[ComponentEntry]) -> Result<(Box<Contains>, String)> {
+    let volume_components = get_volume_objects(components);
+    let component = select_item(&volume_components, Some("Select component volume"))?.0;
+    let description = component.describe_short();
+
+    let margin: f64 = get_value_from_user("Margin to add around the volume (nm)")?;
+
+    let volume = component.to_volume(margin).ok_or_else(|| {
+        GrafenCliError::RunError(String::from(
             "Tried to get a volume type that has not been implemented: this should be impossible",
-        ))),
+        ))
+    })?;
+
+    Ok((volume, description))
+}
+
+/// A component which has a volume, referenced since we don't want to copy its coordinates.
+struct VolumeComponent<'a>(&'a ComponentEntry);
+
+impl<'a> Describe for VolumeComponent<'a> {
+    fn describe(&self) -> String {
+        self.0.describe()
+    }
+
+    fn describe_short(&self) -> String {
+        self.0.describe_short()
     }
 }
 
-/// Prune the list of components to only return those that are volumes, without their
-/// coordinates since we don't want to copy those.
-// fn get_volume_objects(components: &[ComponentEntry]) -> Vec<VolumeComponent> {
-fn get_volume_objects(components: &[ComponentEntry]) -> Vec<ComponentEntry> {
+/// Prune the list of components to only return those that have a volume.
+fn get_volume_objects(components: &[ComponentEntry]) -> Vec<VolumeComponent<'_>> {
     components
         .iter()
-        .filter_map(|comp| match comp {
-            &ComponentEntry::VolumeCuboid(ref obj) => {
-                let volume = Cuboid {
-                    name: obj.name.clone(),
-                    residue: obj.residue.clone(),
-                    origin: obj.origin,
-                    size: obj.size,
-                    density: obj.density,
-                    coords: vec![],
-                };
-
-                Some(ComponentEntry::from(volume))
-            }
-
-            &ComponentEntry::VolumeCylinder(ref obj) => {
-                let volume = Cylinder {
-                    name: obj.name.clone(),
-                    residue: obj.residue.clone(),
-                    origin: obj.origin,
-                    radius: obj.radius,
-                    height: obj.height,
-                    density: obj.density,
-                    alignment: obj.alignment,
-                    coords: vec![],
-                };
-
-                Some(ComponentEntry::from(volume))
-            }
-
-            &ComponentEntry::SurfaceCylinder(ref obj) => {
-                let volume = Cylinder {
-                    name: obj.name.clone(),
-                    residue: obj.residue.clone(),
-                    origin: obj.origin,
-                    radius: obj.radius,
-                    height: obj.height,
-                    density: None,
-                    alignment: obj.alignment,
-                    coords: vec![],
-                };
-
-                Some(ComponentEntry::from(volume))
-            }
-
-            _ => None,
-        })
-        .collect::<Vec<_>>()
+        .filter(|component| component.has_volume())
+        .map(VolumeComponent)
+        .collect()
 }
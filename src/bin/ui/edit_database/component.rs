@@ -5,20 +5,24 @@
 use crate::{
     error::{GrafenCliError, UIErrorKind, UIResult},
     ui::utils::{
-        get_value_from_user, print_description, print_list_description_short,
+        get_coord_from_user, get_value_from_user, print_description, print_list_description_short,
         print_message_to_user_and_hold, remove_items, reorder_list, select_command,
-        select_direction, select_item, MenuResult,
+        select_direction, select_item, select_item_index, MenuResult,
     },
 };
 
 use grafen::{
     coord::{Coord, Direction},
-    database::ComponentEntry::{self, *},
+    database::{
+        unique_name,
+        ComponentEntry::{self, *},
+    },
     describe::Describe,
     surface,
-    surface::{CylinderCap, LatticeType, Sides},
+    surface::{CylinderCap, LatticeType, NormalDirection, Orientation, Sides, SphereLattice},
     system::Residue,
     volume,
+    volume::FillType,
 };
 
 use dialoguer::Checkboxes;
@@ -43,6 +47,12 @@ pub fn user_menu(
                     "Could not create component definition".to_string()
                 ))
         },
+        EditComponent, "Edit a component definition" => {
+            edit_component(&mut component_list, &residue_list)
+        },
+        DuplicateComponent, "Duplicate a component definition" => {
+            duplicate_component(&mut component_list, &residue_list)
+        },
         RemoveComponent, "Remove a component definition" => {
             remove_items(&mut component_list)
                 .map(|_| None)
@@ -74,6 +84,14 @@ enum ComponentSelect {
     Cylinder,
     Cuboid,
     Spheroid,
+    Ellipsoid,
+    Cone,
+    Torus,
+    Capsule,
+    Slab,
+    SphereShell,
+    EllipsoidShell,
+    Bilayer,
     Abort,
 }
 use self::ComponentSelect::*;
@@ -88,6 +106,14 @@ fn new_component(residue_list: &[Residue]) -> UIResult<ComponentEntry> {
             Cylinder => create_cylinder(&residue_list),
             Cuboid => create_cuboid(&residue_list),
             Spheroid => create_spheroid(&residue_list),
+            Ellipsoid => create_shaped_volume(VolumeShape::Ellipsoid, &residue_list),
+            Cone => create_shaped_volume(VolumeShape::Cone, &residue_list),
+            Torus => create_shaped_volume(VolumeShape::Torus, &residue_list),
+            Capsule => create_shaped_volume(VolumeShape::Capsule, &residue_list),
+            Slab => create_shaped_volume(VolumeShape::Slab, &residue_list),
+            SphereShell => create_shell(ShellShape::Sphere, &residue_list),
+            EllipsoidShell => create_shell(ShellShape::Ellipsoid, &residue_list),
+            Bilayer => create_bilayer(&residue_list),
             Abort => return Err(UIErrorKind::Abort),
         };
 
@@ -107,12 +133,279 @@ fn new_component(residue_list: &[Residue]) -> UIResult<ComponentEntry> {
     }
 }
 
+/// Select a component from the list and replace it with an edited version.
+fn edit_component(
+    component_list: &mut [ComponentEntry],
+    residue_list: &[Residue],
+) -> result::Result<Option<String>, GrafenCliError> {
+    eprintln!("Component to edit:");
+    let index = select_item_index(&component_list, 0)?;
+
+    match edit_definition(&component_list[index], &residue_list) {
+        Ok(component) => {
+            component_list[index] = component;
+            Ok(Some("Successfully edited component definition".to_string()))
+        }
+        Err(UIErrorKind::Abort) => Ok(Some("Discarding changes to component".to_string())),
+        Err(err) => Err(GrafenCliError::from(err)),
+    }
+}
+
+/// Select a component from the list and add an edited copy of it to the list.
+///
+/// The copy is named uniquely before it is edited. Configurations read from files
+/// cannot be edited and are copied as they are.
+fn duplicate_component(
+    component_list: &mut Vec<ComponentEntry>,
+    residue_list: &[Residue],
+) -> result::Result<Option<String>, GrafenCliError> {
+    eprintln!("Component to duplicate:");
+    let index = select_item_index(&component_list, 0)?;
+
+    let names = component_list
+        .iter()
+        .filter_map(|component| component.name())
+        .collect::<Vec<_>>();
+
+    let mut copy = component_list[index].clone();
+    let name = unique_name(&copy.name().unwrap_or_default(), &names);
+    copy.set_name(&name);
+
+    let result = match copy {
+        ConfigurationFile(_) => Ok(copy),
+        _ => {
+            eprintln!("Editing the copy of the component.\n");
+            edit_definition(&copy, &residue_list)
+        }
+    };
+
+    match result {
+        Ok(component) => {
+            component_list.push(component);
+            Ok(Some(format!(
+                "Successfully duplicated component definition as '{}'",
+                name
+            )))
+        }
+        Err(UIErrorKind::Abort) => Ok(Some("Discarding the duplicated component".to_string())),
+        Err(err) => Err(GrafenCliError::from(err)),
+    }
+}
+
+/// Open the construction menu of a component with its current definition.
+///
+/// If the user asks to change the component type a new component is created from scratch.
+fn edit_definition(
+    component: &ComponentEntry,
+    residue_list: &[Residue],
+) -> UIResult<ComponentEntry> {
+    let result = match component {
+        &SurfaceSheet(ref conf) => edit_sheet(
+            SheetBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                lattice: conf.lattice,
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                normal: conf.normal,
+                std_z: conf.std_z,
+                orientation: conf.orientation,
+            },
+            &residue_list,
+        ),
+
+        &SurfaceCylinder(ref conf) => edit_cylinder(
+            CylinderBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                cylinder_type: Surface,
+                lattice: Some(conf.lattice),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: None,
+                cap: conf.cap,
+                alignment: conf.alignment,
+                orientation: conf.orientation,
+            },
+            &residue_list,
+        ),
+
+        &VolumeCylinder(ref conf) => edit_cylinder(
+            CylinderBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                cylinder_type: Volume,
+                lattice: None,
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                cap: None,
+                alignment: conf.alignment,
+                orientation: None,
+            },
+            &residue_list,
+        ),
+
+        &SurfaceCuboid(ref conf) => edit_cuboid(
+            CuboidBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                cuboid_type: Surface,
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: None,
+                lattice: Some(conf.lattice),
+                std_z: conf.std_z,
+                sides: Some(conf.sides),
+                orientation: conf.orientation,
+            },
+            &residue_list,
+        ),
+
+        &VolumeCuboid(ref conf) => edit_cuboid(
+            CuboidBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                cuboid_type: Volume,
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                lattice: None,
+                std_z: None,
+                sides: Some(Sides::all()),
+                orientation: None,
+            },
+            &residue_list,
+        ),
+
+        &VolumeSpheroid(ref conf) => edit_spheroid(
+            SpheroidBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+            },
+            &residue_list,
+        ),
+
+        &VolumeEllipsoid(ref conf) => edit_shaped_volume(
+            ShapedVolumeBuilder {
+                shape: VolumeShape::Ellipsoid,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                alignment: Direction::Z,
+            },
+            &residue_list,
+        ),
+
+        &VolumeCone(ref conf) => edit_shaped_volume(
+            ShapedVolumeBuilder {
+                shape: VolumeShape::Cone,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                alignment: conf.alignment,
+            },
+            &residue_list,
+        ),
+
+        &VolumeTorus(ref conf) => edit_shaped_volume(
+            ShapedVolumeBuilder {
+                shape: VolumeShape::Torus,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                alignment: conf.alignment,
+            },
+            &residue_list,
+        ),
+
+        &VolumeCapsule(ref conf) => edit_shaped_volume(
+            ShapedVolumeBuilder {
+                shape: VolumeShape::Capsule,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                alignment: conf.alignment,
+            },
+            &residue_list,
+        ),
+
+        &VolumeSlab(ref conf) => edit_shaped_volume(
+            ShapedVolumeBuilder {
+                shape: VolumeShape::Slab,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                fill_type: conf.fill_type,
+                alignment: conf.normal,
+            },
+            &residue_list,
+        ),
+
+        &SurfaceSphere(ref conf) => edit_shell(
+            ShellBuilder {
+                shape: ShellShape::Sphere,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                lattice: conf.lattice,
+                orientation: conf.orientation,
+            },
+            &residue_list,
+        ),
+
+        &SurfaceEllipsoid(ref conf) => edit_shell(
+            ShellBuilder {
+                shape: ShellShape::Ellipsoid,
+                name: conf.name.clone().unwrap_or_default(),
+                residue: residue_or_select(&conf.residue, &residue_list)?,
+                lattice: conf.lattice,
+                orientation: conf.orientation,
+            },
+            &residue_list,
+        ),
+
+        &SurfaceBilayer(ref conf) => edit_bilayer(
+            BilayerBuilder {
+                name: conf.name.clone().unwrap_or_default(),
+                upper_leaflet: conf.upper_leaflet.clone(),
+                lower_leaflet: conf.lower_leaflet.clone(),
+                area_per_lipid: conf.area_per_lipid,
+                thickness: conf.thickness,
+                lipid_axis: conf.lipid_axis,
+                random_rotation: conf.random_rotation,
+            },
+            &residue_list,
+        ),
+
+        &ConfigurationFile(_) => {
+            return Err(UIErrorKind::BadValue(
+                "configurations read from files cannot be edited".to_string(),
+            ));
+        }
+    };
+
+    match result {
+        Ok(component) => Ok(component),
+        Err(ChangeOrError::ChangeComponent) => new_component(&residue_list),
+        Err(ChangeOrError::Error(err)) => Err(err),
+    }
+}
+
+/// Use the residue of a component definition or let the user select one if it is not set.
+fn residue_or_select(residue: &Option<Residue>, residue_list: &[Residue]) -> UIResult<Residue> {
+    match residue {
+        &Some(ref residue) => Ok(residue.clone()),
+        &None => {
+            eprintln!("The component has no residue. Residue:");
+            select_residue(&residue_list)
+        }
+    }
+}
+
 fn select_component_type() -> UIResult<ComponentSelect> {
     let (choices, item_texts) = create_menu_items![
         (Sheet, "Sheet"),
         (Cylinder, "Cylinder"),
         (Cuboid, "Cuboid box"),
         (Spheroid, "Spheroid"),
+        (Ellipsoid, "Ellipsoid"),
+        (Cone, "Cone or frustum"),
+        (Torus, "Torus"),
+        (Capsule, "Capsule (cylinder with hemispherical caps)"),
+        (Slab, "Slab (volume between two planes)"),
+        (SphereShell, "Spherical surface shell"),
+        (EllipsoidShell, "Ellipsoidal surface shell"),
+        (Bilayer, "Lipid bilayer"),
         (Abort, "(Abort)")
     ];
 
@@ -152,6 +445,7 @@ struct SheetBuilder {
     residue: Residue,
     normal: Direction,
     std_z: Option<f64>,
+    orientation: Option<Orientation>,
 }
 
 impl SheetBuilder {
@@ -169,6 +463,7 @@ impl SheetBuilder {
             residue,
             normal,
             std_z: None,
+            orientation: None,
         })
     }
 
@@ -183,6 +478,7 @@ impl SheetBuilder {
                 std_z: self.std_z,
                 origin: Coord::default(),
                 normal: self.normal,
+                orientation: self.orientation,
                 length: 0.0,
                 width: 0.0,
                 coords: vec![],
@@ -201,6 +497,12 @@ impl Describe for SheetBuilder {
         writeln!(description, "Normal: {}", &self.normal).expect(ERR);
         writeln!(description, "Residue: {}", &self.residue.code).expect(ERR);
         writeln!(description, "Z-variance: {}", &self.std_z.unwrap_or(0.0)).expect(ERR);
+        writeln!(
+            description,
+            "Orientation: {}",
+            describe_orientation(self.orientation)
+        )
+        .expect(ERR);
 
         description
     }
@@ -218,11 +520,21 @@ enum SheetMenu {
     SetNormal,
     SetResidue,
     SetVarianceZ,
+    SetOrientation,
     QuitAndSave,
     QuitWithoutSaving,
 }
 
 fn create_sheet(residue_list: &[Residue]) -> result::Result<ComponentEntry, ChangeOrError> {
+    let builder = SheetBuilder::initialize(&residue_list)?;
+    edit_sheet(builder, residue_list)
+}
+
+/// Edit the component definition of a builder in its menu.
+fn edit_sheet(
+    mut builder: SheetBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
     use self::SheetMenu::*;
 
     let (commands, item_texts) = create_menu_items![
@@ -232,12 +544,14 @@ fn create_sheet(residue_list: &[Residue]) -> result::Result<ComponentEntry, Chan
         (SetLattice, "Set lattice"),
         (SetNormal, "Set normal vector direction"),
         (SetVarianceZ, "Set variance of residue positions along z"),
+        (
+            SetOrientation,
+            "Set residue orientation along the sheet normal"
+        ),
         (QuitAndSave, "Finalize component definition and return"),
         (QuitWithoutSaving, "Abort")
     ];
 
-    let mut builder = SheetBuilder::initialize(&residue_list)?;
-
     loop {
         eprintln!("{}", builder.describe());
 
@@ -277,6 +591,12 @@ fn create_sheet(residue_list: &[Residue]) -> result::Result<ComponentEntry, Chan
                 }
                 Err(_) => eprintln!("error: Could not read new variance"),
             },
+            SetOrientation => match select_orientation() {
+                Ok(new_orientation) => {
+                    builder.orientation = new_orientation;
+                }
+                Err(_) => eprintln!("error: Could not select new orientation"),
+            },
             QuitAndSave => match builder.finalize() {
                 Ok(component) => return Ok(component),
                 Err(msg) => eprintln!("{}", msg),
@@ -305,9 +625,10 @@ struct CylinderBuilder {
     cylinder_type: ComponentType,
     lattice: Option<LatticeType>,
     residue: Residue,
-    density: Option<f64>,
+    fill_type: Option<FillType>,
     cap: Option<CylinderCap>,
     alignment: Direction,
+    orientation: Option<Orientation>,
 }
 
 impl CylinderBuilder {
@@ -326,9 +647,10 @@ impl CylinderBuilder {
             cylinder_type,
             lattice,
             residue,
-            density: None,
+            fill_type: None,
             cap: None,
             alignment: Direction::Z,
+            orientation: None,
         })
     }
 
@@ -343,6 +665,7 @@ impl CylinderBuilder {
                     lattice: self.lattice.unwrap(),
                     alignment: self.alignment,
                     cap: self.cap,
+                    orientation: self.orientation,
                     origin: Coord::default(),
                     radius: 0.0,
                     height: 0.0,
@@ -356,7 +679,7 @@ impl CylinderBuilder {
                     origin: Coord::default(),
                     radius: 0.0,
                     height: 0.0,
-                    density: self.density,
+                    fill_type: self.fill_type,
                     coords: vec![],
                 })),
             }
@@ -389,16 +712,22 @@ impl Describe for CylinderBuilder {
                     .unwrap_or("None".to_string());
 
                 writeln!(description, "Cap: {}", cap_string).expect(ERR);
+                writeln!(
+                    description,
+                    "Orientation: {}",
+                    describe_orientation(self.orientation)
+                )
+                .expect(ERR);
             }
             Volume => {
                 writeln!(description, "Type: Cylinder Volume").expect(ERR);
                 writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
 
-                let density_string = self
-                    .density
-                    .map(|dens| format!("{}", dens))
+                let fill_string = self
+                    .fill_type
+                    .map(|fill_type| format!("{}", fill_type))
                     .unwrap_or("None".into());
-                writeln!(description, "Density: {}", density_string).expect(ERR);
+                writeln!(description, "Default fill: {}", fill_string).expect(ERR);
             }
         }
 
@@ -420,6 +749,7 @@ enum CylinderSurfaceMenu {
     SetResidue,
     SetCap,
     SetAlignment,
+    SetOrientation,
     QuitAndSave,
     QuitWithoutSaving,
 }
@@ -430,15 +760,22 @@ enum CylinderVolumeMenu {
     ChangeCylinderType,
     SetName,
     SetResidue,
-    SetDensity,
+    SetFillType,
     SetAlignment,
     QuitAndSave,
     QuitWithoutSaving,
 }
 
 fn create_cylinder(residue_list: &[Residue]) -> result::Result<ComponentEntry, ChangeOrError> {
-    let mut builder = CylinderBuilder::initialize(&residue_list)?;
+    let builder = CylinderBuilder::initialize(&residue_list)?;
+    edit_cylinder(builder, residue_list)
+}
 
+/// Edit the component definition of a builder in its menu.
+fn edit_cylinder(
+    mut builder: CylinderBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
     loop {
         print_description(&builder);
 
@@ -455,6 +792,10 @@ fn create_cylinder(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
                     (SetResidue, "Set residue"),
                     (SetCap, "Cap either cylinder edge"),
                     (SetAlignment, "Set cylinder normal axis"),
+                    (
+                        SetOrientation,
+                        "Set residue orientation along the surface normal"
+                    ),
                     (QuitAndSave, "Finalize component definition and return"),
                     (QuitWithoutSaving, "Abort")
                 ];
@@ -503,6 +844,12 @@ fn create_cylinder(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
                         }
                         Err(_) => eprintln!("error: Could not select new direction"),
                     },
+                    SetOrientation => match select_orientation() {
+                        Ok(new_orientation) => {
+                            builder.orientation = new_orientation;
+                        }
+                        Err(_) => eprintln!("error: Could not select new orientation"),
+                    },
                     QuitAndSave => match builder.finalize() {
                         Ok(component) => return Ok(component),
                         Err(msg) => eprintln!("{}", msg),
@@ -520,7 +867,7 @@ fn create_cylinder(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
                     (ChangeCylinderType, "Change cylinder type"),
                     (SetName, "Set name"),
                     (SetResidue, "Set residue"),
-                    (SetDensity, "Set default density"),
+                    (SetFillType, "Set default density or concentration"),
                     (SetAlignment, "Set cylinder normal axis"),
                     (QuitAndSave, "Finalize component definition and return"),
                     (QuitWithoutSaving, "Abort")
@@ -558,9 +905,9 @@ fn create_cylinder(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
                         }
                         Err(_) => eprintln!("error: Could not select new residue"),
                     },
-                    SetDensity => match get_density() {
-                        Ok(density) => {
-                            builder.density = density;
+                    SetFillType => match select_default_fill_type() {
+                        Ok(fill_type) => {
+                            builder.fill_type = fill_type;
                         }
                         Err(_) => eprintln!("error: Could not set density"),
                     },
@@ -611,9 +958,11 @@ struct CuboidBuilder {
     name: String,
     cuboid_type: ComponentType,
     residue: Residue,
-    density: Option<f64>,
+    fill_type: Option<FillType>,
     lattice: Option<LatticeType>,
+    std_z: Option<f64>,
     sides: Option<Sides>,
+    orientation: Option<Orientation>,
 }
 
 impl CuboidBuilder {
@@ -631,9 +980,11 @@ impl CuboidBuilder {
             name: String::new(),
             cuboid_type,
             residue,
-            density: None,
+            fill_type: None,
             lattice,
+            std_z: None,
             sides: Some(Sides::all()),
+            orientation: None,
         })
     }
 
@@ -646,7 +997,7 @@ impl CuboidBuilder {
                     Ok(VolumeCuboid(volume::Cuboid {
                         name: Some(self.name.clone()),
                         residue: Some(self.residue.clone()),
-                        density: self.density.clone(),
+                        fill_type: self.fill_type,
                         ..volume::Cuboid::default()
                     }))
                 }
@@ -661,10 +1012,11 @@ impl CuboidBuilder {
                         name: Some(self.name.clone()),
                         residue: Some(self.residue.clone()),
                         lattice: self.lattice.unwrap().clone(),
-                        std_z: None,
+                        std_z: self.std_z,
                         origin: Coord::ORIGO,
                         size: Coord::ORIGO,
                         sides: self.sides.unwrap_or(Sides::all()),
+                        orientation: self.orientation,
                         coords: Vec::new(),
                     }))
                 }
@@ -693,16 +1045,22 @@ impl Describe for CuboidBuilder {
                 writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
 
                 writeln!(description, "Sides: {}", self.sides.unwrap_or(Sides::all())).expect(ERR);
+                writeln!(
+                    description,
+                    "Orientation: {}",
+                    describe_orientation(self.orientation)
+                )
+                .expect(ERR);
             }
             Volume => {
                 writeln!(description, "Type: Cuboid Volume").expect(ERR);
                 writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
 
-                let density_string = self
-                    .density
-                    .map(|dens| format!("{}", dens))
+                let fill_string = self
+                    .fill_type
+                    .map(|fill_type| format!("{}", fill_type))
                     .unwrap_or("None".into());
-                writeln!(description, "Density: {}", density_string).expect(ERR);
+                writeln!(description, "Default fill: {}", fill_string).expect(ERR);
             }
         }
 
@@ -720,7 +1078,7 @@ enum CuboidMenu {
     ChangeCuboidType,
     SetName,
     SetResidue,
-    SetDensity,
+    SetFillType,
     QuitAndSave,
     QuitWithoutSaving,
 }
@@ -732,13 +1090,21 @@ enum CuboidSurfaceMenu {
     SetName,
     SetResidue,
     SetSides,
+    SetOrientation,
     QuitAndSave,
     QuitWithoutSaving,
 }
 
 fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, ChangeOrError> {
-    let mut builder = CuboidBuilder::initialize(&residue_list)?;
+    let builder = CuboidBuilder::initialize(&residue_list)?;
+    edit_cuboid(builder, residue_list)
+}
 
+/// Edit the component definition of a builder in its menu.
+fn edit_cuboid(
+    mut builder: CuboidBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
     loop {
         print_description(&builder);
 
@@ -752,6 +1118,10 @@ fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, Cha
                     (SetName, "Set name"),
                     (SetResidue, "Set residue"),
                     (SetSides, "Set which sides of the cuboid to construct"),
+                    (
+                        SetOrientation,
+                        "Set residue orientation along the surface normal"
+                    ),
                     (QuitAndSave, "Finalize component definition and return"),
                     (QuitWithoutSaving, "Abort")
                 ];
@@ -792,6 +1162,12 @@ fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, Cha
                         Ok(sides) => builder.sides = Some(sides),
                         Err(_) => eprintln!("error: Could not select sides"),
                     },
+                    SetOrientation => match select_orientation() {
+                        Ok(new_orientation) => {
+                            builder.orientation = new_orientation;
+                        }
+                        Err(_) => eprintln!("error: Could not select new orientation"),
+                    },
                     QuitAndSave => match builder.finalize() {
                         Ok(component) => return Ok(component),
                         Err(msg) => eprintln!("{}", msg),
@@ -808,7 +1184,7 @@ fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, Cha
                     (ChangeCuboidType, "Change cuboid type"),
                     (SetName, "Set name"),
                     (SetResidue, "Set residue"),
-                    (SetDensity, "Set default density"),
+                    (SetFillType, "Set default density or concentration"),
                     (QuitAndSave, "Finalize component definition and return"),
                     (QuitWithoutSaving, "Abort")
                 ];
@@ -845,9 +1221,9 @@ fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, Cha
                         }
                         Err(_) => eprintln!("error: Could not select new residue"),
                     },
-                    SetDensity => match get_density() {
-                        Ok(density) => {
-                            builder.density = density;
+                    SetFillType => match select_default_fill_type() {
+                        Ok(fill_type) => {
+                            builder.fill_type = fill_type;
                         }
                         Err(_) => eprintln!("error: Could not set density"),
                     },
@@ -871,7 +1247,7 @@ fn create_cuboid(residue_list: &[Residue]) -> result::Result<ComponentEntry, Cha
 struct SpheroidBuilder {
     name: String,
     residue: Residue,
-    density: Option<f64>,
+    fill_type: Option<FillType>,
 }
 
 impl SpheroidBuilder {
@@ -881,7 +1257,7 @@ impl SpheroidBuilder {
         Ok(SpheroidBuilder {
             name: String::new(),
             residue,
-            density: None,
+            fill_type: None,
         })
     }
 
@@ -892,7 +1268,7 @@ impl SpheroidBuilder {
             Ok(VolumeSpheroid(volume::Spheroid {
                 name: Some(self.name.clone()),
                 residue: Some(self.residue.clone()),
-                density: self.density.clone(),
+                fill_type: self.fill_type,
 
                 origin: Coord::default(),
                 coords: Vec::new(),
@@ -911,11 +1287,11 @@ impl Describe for SpheroidBuilder {
 
         writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
 
-        let density_string = self
-            .density
-            .map(|dens| format!("{}", dens))
+        let fill_string = self
+            .fill_type
+            .map(|fill_type| format!("{}", fill_type))
             .unwrap_or("None".into());
-        writeln!(description, "Density: {}", density_string).expect(ERR);
+        writeln!(description, "Default fill: {}", fill_string).expect(ERR);
 
         description
     }
@@ -930,14 +1306,21 @@ enum SpheroidMenu {
     ChangeComponent,
     SetName,
     SetResidue,
-    SetDensity,
+    SetFillType,
     QuitAndSave,
     QuitWithoutSaving,
 }
 
 fn create_spheroid(residue_list: &[Residue]) -> result::Result<ComponentEntry, ChangeOrError> {
-    let mut builder = SpheroidBuilder::initialize(&residue_list)?;
+    let builder = SpheroidBuilder::initialize(&residue_list)?;
+    edit_spheroid(builder, residue_list)
+}
 
+/// Edit the component definition of a builder in its menu.
+fn edit_spheroid(
+    mut builder: SpheroidBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
     loop {
         print_description(&builder);
 
@@ -947,7 +1330,7 @@ fn create_spheroid(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
             (ChangeComponent, "Change component type"),
             (SetName, "Set name"),
             (SetResidue, "Set residue"),
-            (SetDensity, "Set default density"),
+            (SetFillType, "Set default density or concentration"),
             (QuitAndSave, "Finalize component definition and return"),
             (QuitWithoutSaving, "Abort")
         ];
@@ -970,12 +1353,250 @@ fn create_spheroid(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
                 }
                 Err(_) => eprintln!("error: Could not select new residue"),
             },
-            SetDensity => match get_density() {
-                Ok(density) => {
-                    builder.density = density;
+            SetFillType => match select_default_fill_type() {
+                Ok(fill_type) => {
+                    builder.fill_type = fill_type;
+                }
+                Err(_) => eprintln!("error: Could not set density"),
+            },
+            QuitAndSave => match builder.finalize() {
+                Ok(component) => return Ok(component),
+                Err(msg) => eprintln!("{}", msg),
+            },
+            QuitWithoutSaving => return Err(ChangeOrError::Error(UIErrorKind::Abort)),
+        }
+
+        eprintln!("");
+    }
+}
+
+/*******************************
+ * Shaped volume construction *
+ *******************************/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Volume shapes which are constructed using the same builder.
+enum VolumeShape {
+    Ellipsoid,
+    Cone,
+    Torus,
+    Capsule,
+    Slab,
+}
+
+impl VolumeShape {
+    /// Whether the shape is aligned along an axis.
+    fn has_alignment(&self) -> bool {
+        *self != VolumeShape::Ellipsoid
+    }
+}
+
+struct ShapedVolumeBuilder {
+    shape: VolumeShape,
+    name: String,
+    residue: Residue,
+    fill_type: Option<FillType>,
+    alignment: Direction,
+}
+
+impl ShapedVolumeBuilder {
+    fn initialize(shape: VolumeShape, residue_list: &[Residue]) -> UIResult<ShapedVolumeBuilder> {
+        let residue = select_residue(&residue_list)?;
+
+        let alignment = if shape.has_alignment() {
+            select_direction(Some("Alignment axis"), None)?
+        } else {
+            Direction::Z
+        };
+
+        Ok(ShapedVolumeBuilder {
+            shape,
+            name: String::new(),
+            residue,
+            fill_type: None,
+            alignment,
+        })
+    }
+
+    fn finalize(&self) -> result::Result<ComponentEntry, &str> {
+        if self.name.is_empty() {
+            return Err("Cannot add component: No name is set");
+        }
+
+        let name = Some(self.name.clone());
+        let residue = Some(self.residue.clone());
+
+        let component = match self.shape {
+            VolumeShape::Ellipsoid => VolumeEllipsoid(volume::Ellipsoid {
+                name,
+                residue,
+                fill_type: self.fill_type,
+
+                origin: Coord::default(),
+                semi_axes: Coord::default(),
+                coords: Vec::new(),
+            }),
+            VolumeShape::Cone => VolumeCone(volume::Cone {
+                name,
+                residue,
+                alignment: self.alignment,
+                fill_type: self.fill_type,
+
+                origin: Coord::default(),
+                bottom_radius: 0.0,
+                top_radius: 0.0,
+                height: 0.0,
+                coords: Vec::new(),
+            }),
+            VolumeShape::Torus => VolumeTorus(volume::Torus {
+                name,
+                residue,
+                alignment: self.alignment,
+                fill_type: self.fill_type,
+
+                origin: Coord::default(),
+                major_radius: 0.0,
+                minor_radius: 0.0,
+                coords: Vec::new(),
+            }),
+            VolumeShape::Capsule => VolumeCapsule(volume::Capsule {
+                name,
+                residue,
+                alignment: self.alignment,
+                fill_type: self.fill_type,
+
+                origin: Coord::default(),
+                radius: 0.0,
+                height: 0.0,
+                coords: Vec::new(),
+            }),
+            VolumeShape::Slab => VolumeSlab(volume::Slab {
+                name,
+                residue,
+                normal: self.alignment,
+                fill_type: self.fill_type,
+
+                origin: Coord::default(),
+                thickness: 0.0,
+                length: 0.0,
+                width: 0.0,
+                coords: Vec::new(),
+            }),
+        };
+
+        Ok(component)
+    }
+}
+
+impl Describe for ShapedVolumeBuilder {
+    fn describe(&self) -> String {
+        let mut description = String::new();
+        const ERR: &'static str = "could not construct a string";
+
+        writeln!(description, "Name: {}", &self.name).expect(ERR);
+        writeln!(description, "Shape: {:?}", &self.shape).expect(ERR);
+
+        if self.shape.has_alignment() {
+            writeln!(description, "Alignment: {}", &self.alignment).expect(ERR);
+        }
+
+        writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
+
+        let fill_string = self
+            .fill_type
+            .map(|fill_type| format!("{}", fill_type))
+            .unwrap_or("None".into());
+        writeln!(description, "Default fill: {}", fill_string).expect(ERR);
+
+        description
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ShapedVolumeMenu {
+    ChangeComponent,
+    SetName,
+    SetResidue,
+    SetFillType,
+    SetAlignment,
+    QuitAndSave,
+    QuitWithoutSaving,
+}
+
+fn create_shaped_volume(
+    shape: VolumeShape,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
+    let builder = ShapedVolumeBuilder::initialize(shape, &residue_list)?;
+    edit_shaped_volume(builder, residue_list)
+}
+
+/// Edit the component definition of a builder in its menu.
+fn edit_shaped_volume(
+    mut builder: ShapedVolumeBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
+    loop {
+        print_description(&builder);
+
+        use self::ShapedVolumeMenu::*;
+
+        let (commands, item_texts): (&[ShapedVolumeMenu], &[&str]) =
+            if builder.shape.has_alignment() {
+                create_menu_items![
+                    (ChangeComponent, "Change component type"),
+                    (SetName, "Set name"),
+                    (SetResidue, "Set residue"),
+                    (SetFillType, "Set default density or concentration"),
+                    (SetAlignment, "Set alignment axis"),
+                    (QuitAndSave, "Finalize component definition and return"),
+                    (QuitWithoutSaving, "Abort")
+                ]
+            } else {
+                create_menu_items![
+                    (ChangeComponent, "Change component type"),
+                    (SetName, "Set name"),
+                    (SetResidue, "Set residue"),
+                    (SetFillType, "Set default density or concentration"),
+                    (QuitAndSave, "Finalize component definition and return"),
+                    (QuitWithoutSaving, "Abort")
+                ]
+            };
+
+        let command = select_command(item_texts, commands).map_err(|err| UIErrorKind::from(err))?;
+
+        match command {
+            ChangeComponent => return Err(ChangeOrError::ChangeComponent),
+            SetName => match get_value_from_user::<String>("Component name") {
+                Ok(new_name) => {
+                    builder.name = new_name;
+                }
+                Err(_) => {
+                    eprintln!("error: Could not read name");
+                }
+            },
+            SetResidue => match select_residue(&residue_list) {
+                Ok(new_residue) => {
+                    builder.residue = new_residue;
+                }
+                Err(_) => eprintln!("error: Could not select new residue"),
+            },
+            SetFillType => match select_default_fill_type() {
+                Ok(fill_type) => {
+                    builder.fill_type = fill_type;
                 }
                 Err(_) => eprintln!("error: Could not set density"),
             },
+            SetAlignment => match select_direction(Some("Alignment axis"), None) {
+                Ok(new_direction) => {
+                    builder.alignment = new_direction;
+                }
+                Err(_) => eprintln!("error: Could not select new direction"),
+            },
             QuitAndSave => match builder.finalize() {
                 Ok(component) => return Ok(component),
                 Err(msg) => eprintln!("{}", msg),
@@ -987,6 +1608,389 @@ fn create_spheroid(residue_list: &[Residue]) -> result::Result<ComponentEntry, C
     }
 }
 
+/**********************
+ * Shell construction *
+ **********************/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Closed surface shapes which are constructed using the same builder.
+enum ShellShape {
+    Sphere,
+    Ellipsoid,
+}
+
+struct ShellBuilder {
+    shape: ShellShape,
+    name: String,
+    residue: Residue,
+    lattice: SphereLattice,
+    orientation: Option<Orientation>,
+}
+
+impl ShellBuilder {
+    fn initialize(shape: ShellShape, residue_list: &[Residue]) -> UIResult<ShellBuilder> {
+        let lattice = select_sphere_lattice()?;
+
+        eprintln!("Residue:");
+        let residue = select_residue(&residue_list)?;
+
+        Ok(ShellBuilder {
+            shape,
+            name: String::new(),
+            residue,
+            lattice,
+            orientation: None,
+        })
+    }
+
+    fn finalize(&self) -> result::Result<ComponentEntry, &str> {
+        if self.name.is_empty() {
+            return Err("Cannot add component: No name is set");
+        }
+
+        let name = Some(self.name.clone());
+        let residue = Some(self.residue.clone());
+
+        let component = match self.shape {
+            ShellShape::Sphere => SurfaceSphere(surface::Sphere {
+                name,
+                residue,
+                lattice: self.lattice,
+                orientation: self.orientation,
+
+                origin: Coord::default(),
+                radius: 0.0,
+                coords: Vec::new(),
+            }),
+            ShellShape::Ellipsoid => SurfaceEllipsoid(surface::Ellipsoid {
+                name,
+                residue,
+                lattice: self.lattice,
+                orientation: self.orientation,
+
+                origin: Coord::default(),
+                semi_axes: Coord::default(),
+                coords: Vec::new(),
+            }),
+        };
+
+        Ok(component)
+    }
+}
+
+impl Describe for ShellBuilder {
+    fn describe(&self) -> String {
+        let mut description = String::new();
+        const ERR: &'static str = "could not construct a string";
+
+        writeln!(description, "Name: {}", &self.name).expect(ERR);
+        writeln!(description, "Shape: {:?} surface", &self.shape).expect(ERR);
+        writeln!(description, "Lattice: {:?}", &self.lattice).expect(ERR);
+        writeln!(description, "Residue: {}", self.residue.code).expect(ERR);
+        writeln!(
+            description,
+            "Orientation: {}",
+            describe_orientation(self.orientation)
+        )
+        .expect(ERR);
+
+        description
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ShellMenu {
+    ChangeComponent,
+    SetName,
+    SetResidue,
+    SetLattice,
+    SetOrientation,
+    QuitAndSave,
+    QuitWithoutSaving,
+}
+
+fn create_shell(
+    shape: ShellShape,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
+    let builder = ShellBuilder::initialize(shape, &residue_list)?;
+    edit_shell(builder, residue_list)
+}
+
+/// Edit the component definition of a builder in its menu.
+fn edit_shell(
+    mut builder: ShellBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
+    use self::ShellMenu::*;
+
+    let (commands, item_texts) = create_menu_items![
+        (ChangeComponent, "Change component type"),
+        (SetName, "Set name"),
+        (SetResidue, "Set residue"),
+        (SetLattice, "Set lattice"),
+        (
+            SetOrientation,
+            "Set residue orientation along the surface normal"
+        ),
+        (QuitAndSave, "Finalize component definition and return"),
+        (QuitWithoutSaving, "Abort")
+    ];
+
+    loop {
+        print_description(&builder);
+
+        let command = select_command(item_texts, commands).map_err(|err| UIErrorKind::from(err))?;
+
+        match command {
+            ChangeComponent => return Err(ChangeOrError::ChangeComponent),
+            SetName => match get_value_from_user::<String>("Component name") {
+                Ok(new_name) => {
+                    builder.name = new_name;
+                }
+                Err(_) => {
+                    eprintln!("error: Could not read name");
+                }
+            },
+            SetResidue => match select_residue(&residue_list) {
+                Ok(new_residue) => {
+                    builder.residue = new_residue;
+                }
+                Err(_) => eprintln!("error: Could not select new residue"),
+            },
+            SetLattice => match select_sphere_lattice() {
+                Ok(new_lattice) => {
+                    builder.lattice = new_lattice;
+                }
+                Err(_) => eprintln!("error: Could not select new lattice"),
+            },
+            SetOrientation => match select_orientation() {
+                Ok(new_orientation) => {
+                    builder.orientation = new_orientation;
+                }
+                Err(_) => eprintln!("error: Could not select new orientation"),
+            },
+            QuitAndSave => match builder.finalize() {
+                Ok(component) => return Ok(component),
+                Err(msg) => eprintln!("{}", msg),
+            },
+            QuitWithoutSaving => return Err(ChangeOrError::Error(UIErrorKind::Abort)),
+        }
+
+        eprintln!("");
+    }
+}
+
+/*************************
+ * Bilayer construction *
+ *************************/
+
+struct BilayerBuilder {
+    name: String,
+    upper_leaflet: Vec<surface::Lipid>,
+    lower_leaflet: Vec<surface::Lipid>,
+    area_per_lipid: f64,
+    thickness: f64,
+    lipid_axis: Coord,
+    random_rotation: bool,
+}
+
+impl BilayerBuilder {
+    fn initialize(residue_list: &[Residue]) -> UIResult<BilayerBuilder> {
+        eprintln!("Lipid of the upper leaflet:");
+        let lipid = select_lipid(&residue_list)?;
+
+        let area_per_lipid = get_value_from_user::<f64>("Area per lipid (nm^2)")?;
+        let thickness = get_value_from_user::<f64>("Distance between head groups (nm)")?;
+
+        Ok(BilayerBuilder {
+            name: String::new(),
+            upper_leaflet: vec![lipid],
+            lower_leaflet: Vec::new(),
+            area_per_lipid,
+            thickness,
+            lipid_axis: Coord::new(0.0, 0.0, -1.0),
+            random_rotation: true,
+        })
+    }
+
+    fn finalize(&self) -> result::Result<ComponentEntry, &str> {
+        if self.name.is_empty() {
+            return Err("Cannot add component: No name is set");
+        }
+
+        if self.upper_leaflet.is_empty() {
+            return Err("Cannot add component: No lipids are set");
+        }
+
+        Ok(SurfaceBilayer(surface::Bilayer {
+            name: Some(self.name.clone()),
+            upper_leaflet: self.upper_leaflet.clone(),
+            lower_leaflet: self.lower_leaflet.clone(),
+            area_per_lipid: self.area_per_lipid,
+            thickness: self.thickness,
+            lipid_axis: self.lipid_axis,
+            random_rotation: self.random_rotation,
+
+            origin: Coord::default(),
+            length: 0.0,
+            width: 0.0,
+            lipids: Vec::new(),
+        }))
+    }
+}
+
+fn describe_leaflet(leaflet: &[surface::Lipid]) -> String {
+    leaflet
+        .iter()
+        .map(|lipid| format!("{} ({})", lipid.residue.code, lipid.fraction))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Describe for BilayerBuilder {
+    fn describe(&self) -> String {
+        let mut description = String::new();
+        const ERR: &'static str = "could not construct a string";
+
+        writeln!(description, "Name: {}", &self.name).expect(ERR);
+        writeln!(
+            description,
+            "Upper leaflet: {}",
+            describe_leaflet(&self.upper_leaflet)
+        )
+        .expect(ERR);
+
+        if self.lower_leaflet.is_empty() {
+            writeln!(description, "Lower leaflet: (Same as upper)").expect(ERR);
+        } else {
+            writeln!(
+                description,
+                "Lower leaflet: {}",
+                describe_leaflet(&self.lower_leaflet)
+            )
+            .expect(ERR);
+        }
+
+        writeln!(description, "Area per lipid: {} nm^2", self.area_per_lipid).expect(ERR);
+        writeln!(description, "Thickness: {} nm", self.thickness).expect(ERR);
+        writeln!(description, "Lipid axis: {}", self.lipid_axis).expect(ERR);
+        writeln!(description, "Random rotation: {}", self.random_rotation).expect(ERR);
+
+        description
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BilayerMenu {
+    ChangeComponent,
+    SetName,
+    AddUpperLipid,
+    AddLowerLipid,
+    ClearUpperLeaflet,
+    ClearLowerLeaflet,
+    SetAreaPerLipid,
+    SetThickness,
+    SetLipidAxis,
+    ToggleRandomRotation,
+    QuitAndSave,
+    QuitWithoutSaving,
+}
+
+fn create_bilayer(residue_list: &[Residue]) -> result::Result<ComponentEntry, ChangeOrError> {
+    let builder = BilayerBuilder::initialize(&residue_list)?;
+    edit_bilayer(builder, residue_list)
+}
+
+/// Edit the component definition of a builder in its menu.
+fn edit_bilayer(
+    mut builder: BilayerBuilder,
+    residue_list: &[Residue],
+) -> result::Result<ComponentEntry, ChangeOrError> {
+    use self::BilayerMenu::*;
+
+    let (commands, item_texts) = create_menu_items![
+        (ChangeComponent, "Change component type"),
+        (SetName, "Set name"),
+        (AddUpperLipid, "Add lipid to upper leaflet"),
+        (AddLowerLipid, "Add lipid to lower leaflet"),
+        (ClearUpperLeaflet, "Clear lipids of upper leaflet"),
+        (
+            ClearLowerLeaflet,
+            "Clear lipids of lower leaflet (use upper leaflet composition)"
+        ),
+        (SetAreaPerLipid, "Set area per lipid"),
+        (SetThickness, "Set distance between head groups"),
+        (SetLipidAxis, "Set lipid axis (head group to tails)"),
+        (ToggleRandomRotation, "Toggle random rotation of lipids"),
+        (QuitAndSave, "Finalize component definition and return"),
+        (QuitWithoutSaving, "Abort")
+    ];
+
+    loop {
+        print_description(&builder);
+
+        let command = select_command(item_texts, commands).map_err(|err| UIErrorKind::from(err))?;
+
+        match command {
+            ChangeComponent => return Err(ChangeOrError::ChangeComponent),
+            SetName => match get_value_from_user::<String>("Component name") {
+                Ok(new_name) => {
+                    builder.name = new_name;
+                }
+                Err(_) => {
+                    eprintln!("error: Could not read name");
+                }
+            },
+            AddUpperLipid => match select_lipid(&residue_list) {
+                Ok(lipid) => builder.upper_leaflet.push(lipid),
+                Err(_) => eprintln!("error: Could not select lipid"),
+            },
+            AddLowerLipid => match select_lipid(&residue_list) {
+                Ok(lipid) => builder.lower_leaflet.push(lipid),
+                Err(_) => eprintln!("error: Could not select lipid"),
+            },
+            ClearUpperLeaflet => builder.upper_leaflet.clear(),
+            ClearLowerLeaflet => builder.lower_leaflet.clear(),
+            SetAreaPerLipid => match get_value_from_user::<f64>("Area per lipid (nm^2)") {
+                Ok(area_per_lipid) => builder.area_per_lipid = area_per_lipid,
+                Err(_) => eprintln!("error: Could not read value"),
+            },
+            SetThickness => match get_value_from_user::<f64>("Distance between head groups (nm)") {
+                Ok(thickness) => builder.thickness = thickness,
+                Err(_) => eprintln!("error: Could not read value"),
+            },
+            SetLipidAxis => match get_coord_from_user("Lipid axis (x y z)", Some("0 0 -1")) {
+                Ok(axis) => builder.lipid_axis = axis,
+                Err(_) => eprintln!("error: Could not read axis"),
+            },
+            ToggleRandomRotation => builder.random_rotation = !builder.random_rotation,
+            QuitAndSave => match builder.finalize() {
+                Ok(component) => return Ok(component),
+                Err(msg) => eprintln!("{}", msg),
+            },
+            QuitWithoutSaving => return Err(ChangeOrError::Error(UIErrorKind::Abort)),
+        }
+
+        eprintln!("");
+    }
+}
+
+fn select_lipid(residue_list: &[Residue]) -> UIResult<surface::Lipid> {
+    let residue = select_residue(&residue_list)?;
+    let fraction = get_value_from_user::<f64>("Mole fraction in leaflet")?;
+
+    Ok(surface::Lipid { residue, fraction })
+}
+
 fn select_sides() -> UIResult<Sides> {
     let choices = &["X0", "X1", "Y0", "Y1", "Z0", "Z1"];
 
@@ -1040,14 +2044,35 @@ enum LatticeSelection {
     BlueNoise,
 }
 
-fn get_density() -> UIResult<Option<f64>> {
-    let density = get_value_from_user::<f64>("Density (1/nm^3, negative: unset)")?;
+#[derive(Clone, Copy, Debug)]
+enum FillTypeSelection {
+    Unset,
+    Density,
+    MassDensity,
+    Molarity,
+}
 
-    if density > 0.0 {
-        Ok(Some(density))
-    } else {
-        Ok(None)
-    }
+fn select_default_fill_type() -> UIResult<Option<FillType>> {
+    use self::FillTypeSelection::*;
+
+    let (choices, item_texts) = create_menu_items![
+        (Unset, "None: Select when constructing the component"),
+        (Density, "Number density (1/nm^3)"),
+        (
+            MassDensity,
+            "Mass density (g/cm^3), requires residue masses"
+        ),
+        (Molarity, "Molar concentration (mol/L)")
+    ];
+
+    let fill_type = match select_command(item_texts, choices)? {
+        Unset => return Ok(None),
+        Density => FillType::Density(get_value_from_user::<f64>("Density (1/nm^3)")?),
+        MassDensity => FillType::MassDensity(get_value_from_user::<f64>("Mass density (g/cm^3)")?),
+        Molarity => FillType::Molarity(get_value_from_user::<f64>("Concentration (mol/L)")?),
+    };
+
+    Ok(Some(fill_type))
 }
 
 fn select_residue(residue_list: &[Residue]) -> UIResult<Residue> {
@@ -1119,3 +2144,79 @@ fn select_lattice() -> UIResult<LatticeType> {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug)]
+enum SphereLatticeSelection {
+    Fibonacci,
+    PoissonDisc,
+}
+
+fn select_sphere_lattice() -> UIResult<SphereLattice> {
+    use self::SphereLatticeSelection::*;
+
+    let (choices, item_texts) = create_menu_items![
+        (
+            Fibonacci,
+            "Fibonacci lattice: Evenly spaced points along a golden spiral"
+        ),
+        (
+            PoissonDisc,
+            "Poisson disc: Randomly generated points with a density"
+        )
+    ];
+
+    let lattice = select_command(item_texts, choices)?;
+
+    eprintln!("Points are generated with an input density 'ρ' points per area.");
+    eprintln!("");
+
+    let density = get_value_from_user::<f64>("Density 'ρ' (1/nm^2)")?;
+
+    match lattice {
+        Fibonacci => Ok(SphereLattice::Fibonacci { density }),
+        PoissonDisc => Ok(SphereLattice::PoissonDisc { density }),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum OrientationSelection {
+    Unchanged,
+    Outward,
+    Inward,
+}
+
+fn select_orientation() -> UIResult<Option<Orientation>> {
+    use self::OrientationSelection::*;
+
+    let (choices, item_texts) = create_menu_items![
+        (Unchanged, "None: Keep the residue as it is defined"),
+        (
+            Outward,
+            "Outward: Align a residue axis along the surface normal"
+        ),
+        (
+            Inward,
+            "Inward: Align a residue axis against the surface normal"
+        )
+    ];
+
+    let direction = match select_command(item_texts, choices)? {
+        Unchanged => return Ok(None),
+        Outward => NormalDirection::Outward,
+        Inward => NormalDirection::Inward,
+    };
+
+    eprintln!("The residue is rotated around its first atom to align the axis.");
+    let axis = get_coord_from_user("Residue axis (x y z)", Some("0 0 1"))?;
+
+    Ok(Some(Orientation { axis, direction }))
+}
+
+fn describe_orientation(orientation: Option<Orientation>) -> String {
+    match orientation {
+        Some(Orientation { axis, direction }) => {
+            format!("{:?} along residue axis {}", direction, axis)
+        }
+        None => "None".to_string(),
+    }
+}
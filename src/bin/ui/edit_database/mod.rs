@@ -5,17 +5,32 @@ mod residue;
 
 use crate::{
     error::GrafenCliError,
-    ui::utils::{print_description, select_command, MenuResult},
+    ui::utils::{
+        get_value_from_user, get_value_or_default_from_user, print_description, select_command,
+        MenuResult,
+    },
 };
 
-use grafen::database::{write_database, DataBase};
-use std::error::Error;
+use grafen::{
+    database::{
+        export_bundle, import_bundle, library, merge_database, read_database, write_database,
+        Conflict, DataBase, Definition, MergeAction,
+    },
+    describe::Describe,
+};
+
+use dialoguer::Checkboxes;
+use std::{fs, path::PathBuf};
 
 pub fn user_menu(database: &mut DataBase) -> MenuResult {
     let path_backup = database.path.clone();
     let residues_backup = database.residue_defs.clone();
     let components_backup = database.component_defs.clone();
 
+    // Configuration files are copied when bundles are imported and must be removed
+    // if the changes are discarded, unless the database has been written since
+    let mut copied_files = Vec::new();
+
     create_menu![
         @pre: { print_description(database); };
 
@@ -23,14 +38,33 @@ pub fn user_menu(database: &mut DataBase) -> MenuResult {
             residue::user_menu(&mut database.residue_defs).map(|msg| msg.into())
         },
         EditComponents, "Edit list of components" => {
-            component::user_menu(&mut database.component_defs, &database.residue_defs)
+            // Residues can be selected from all database layers
+            let residue_list = database
+                .residues()
+                .iter()
+                .map(|residue| residue.definition.clone())
+                .collect::<Vec<_>>();
+
+            component::user_menu(&mut database.component_defs, &residue_list)
                 .map(|msg| msg.into())
         },
+        CopyFromLibrary, "Copy definitions from the built-in library" => {
+            copy_from_library(database)
+        },
+        ImportDatabase, "Import definitions from another database (copies the files of bundles)" => {
+            import_database(database, &mut copied_files)
+        },
+        ExportBundle, "Export database with its configuration files as a bundle" => {
+            export_database(database)
+        },
         WriteToDisk, "Write changes of database to disk" => {
             write_database(&database)
-                .map(|_| String::from("Successfully wrote changes of database to disk.").into())
+                .map(|_| {
+                    copied_files.clear();
+                    String::from("Successfully wrote changes of database to disk.").into()
+                })
                 .map_err(|err| GrafenCliError::RunError(
-                    format!("Could not write database: {}", err.description())
+                    format!("Could not write database: {}", err)
                 ))
         },
         QuitAndSave, "Finish editing database" => {
@@ -40,8 +74,146 @@ pub fn user_menu(database: &mut DataBase) -> MenuResult {
             database.path = path_backup;
             database.residue_defs = residues_backup;
             database.component_defs = components_backup;
+            remove_copied_files(&copied_files);
 
             return Ok("Discarding changes to database".to_string().into());
         }
     ];
 }
+
+/// Select definitions from the built-in library and copy them into the database.
+fn copy_from_library(database: &mut DataBase) -> MenuResult {
+    let library = library();
+
+    let item_texts = library
+        .component_defs
+        .iter()
+        .map(|component| format!("Component: {}", component.describe_short()))
+        .chain(
+            library
+                .residue_defs
+                .iter()
+                .map(|residue| format!("Residue: {}", residue.describe())),
+        )
+        .collect::<Vec<_>>();
+    let item_refs = item_texts.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    eprintln!("Select definitions to copy ([space] select, [enter] confirm):");
+    let selections = Checkboxes::new().items(&item_refs).interact()?;
+
+    if selections.is_empty() {
+        return Ok(Some("No definitions were selected".to_string()));
+    }
+
+    let num_components = library.component_defs.len();
+
+    let selected = DataBase {
+        component_defs: library
+            .component_defs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| selections.contains(i))
+            .map(|(_, component)| component.clone())
+            .collect(),
+        residue_defs: library
+            .residue_defs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| selections.contains(&(i + num_components)))
+            .map(|(_, residue)| residue.clone())
+            .collect(),
+        ..DataBase::new()
+    };
+
+    let summary = merge_database(database, &selected, resolve_conflict);
+
+    Ok(Some(format!(
+        "Copied definitions from the library: {}",
+        summary.describe()
+    )))
+}
+
+/// Merge the definitions of a database which is read from disk into the current.
+///
+/// The configuration files of bundles are copied next to the database right away.
+/// The paths to the files which were created are added to the input list.
+fn import_database(database: &mut DataBase, copied_files: &mut Vec<PathBuf>) -> MenuResult {
+    let path = get_value_from_user::<String>("Path to database or bundle directory to import")?;
+    let input_path = PathBuf::from(&path);
+
+    let summary = if input_path.is_dir() {
+        let mut summary = import_bundle(database, &input_path, resolve_conflict)?;
+        copied_files.append(&mut summary.copied_files);
+
+        summary
+    } else {
+        merge_database(database, &read_database(&input_path)?, resolve_conflict)
+    };
+
+    Ok(Some(format!(
+        "Imported definitions from '{}': {}",
+        path,
+        summary.describe()
+    )))
+}
+
+/// Remove configuration files which were copied from bundles by discarded imports.
+fn remove_copied_files(paths: &[PathBuf]) {
+    for path in paths {
+        if let Err(err) = fs::remove_file(path) {
+            eprintln!(
+                "warning: could not remove the copied configuration '{}': {}",
+                path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Write the database along with copies of its configuration files to a directory.
+fn export_database(database: &DataBase) -> MenuResult {
+    let path = get_value_from_user::<String>("Directory to export the bundle to")?;
+    export_bundle(database, &PathBuf::from(&path))?;
+
+    Ok(Some(format!("Exported the database to '{}'", path)))
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ConflictMenu {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+/// Ask the user how to import a definition with the same name as an existing definition.
+///
+/// If the user aborts the definition is skipped.
+fn resolve_conflict(conflict: &Conflict) -> MergeAction {
+    use self::ConflictMenu::*;
+
+    let kind = match conflict.definition {
+        Definition::Residue => "residue",
+        Definition::Component => "component",
+    };
+
+    eprintln!(
+        "A {} named '{}' already exists with a different definition.",
+        kind, conflict.name
+    );
+
+    let (commands, item_texts) = create_menu_items![
+        (Skip, "Keep the existing definition"),
+        (Overwrite, "Overwrite it with the imported definition"),
+        (Rename, "Import the definition with a new name")
+    ];
+
+    match select_command(item_texts, commands) {
+        Ok(Overwrite) => MergeAction::Overwrite,
+        Ok(Rename) => {
+            get_value_or_default_from_user::<String>("New name", &conflict.suggested_name)
+                .map(|name| MergeAction::Rename(name))
+                .unwrap_or(MergeAction::Skip)
+        }
+        _ => MergeAction::Skip,
+    }
+}
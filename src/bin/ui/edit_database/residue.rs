@@ -3,16 +3,19 @@
 use crate::{
     error::{GrafenCliError, Result, UIErrorKind, UIResult},
     ui::utils::{
-        get_position_from_user, get_value_from_user, print_list_description, remove_items,
-        reorder_list, select_command, MenuResult,
+        get_optional_value_from_user, get_position_from_user, get_value_from_user,
+        print_list_description, remove_items, reorder_list, select_command, select_item_index,
+        MenuResult,
     },
 };
 
 use grafen::{
-    describe::describe_list,
-    system::{Atom, Residue},
+    database::unique_name,
+    describe::{describe_list, Describe},
+    read_residue::{read_residue, Centering},
+    system::{Atom, AtomMetadata, Residue},
 };
-use std::{error::Error, result};
+use std::{error::Error, path::PathBuf, result};
 
 pub fn user_menu(mut residue_list: &mut Vec<Residue>) -> MenuResult {
     let residues_backup = residue_list.clone();
@@ -30,6 +33,28 @@ pub fn user_menu(mut residue_list: &mut Vec<Residue>) -> MenuResult {
                     format!("Could not create residue: {}", err.description())
                 ))
         },
+        ImportResidue, "Import residue from file" => {
+            import_residue()
+                .map(|residue| {
+                    let msg = format!("Successfully imported residue {}", residue.describe());
+                    residue_list.push(residue);
+                    Some(msg)
+                })
+        },
+        EditResidue, "Edit a residue definition" => {
+            edit_residue_in_list(&mut residue_list)
+                .map(|_| Some("Successfully edited residue".to_string()))
+                .map_err(|err| GrafenCliError::RunError(
+                    format!("Could not edit residue: {}", err.description())
+                ))
+        },
+        DuplicateResidue, "Duplicate a residue definition" => {
+            duplicate_residue(&mut residue_list)
+                .map(|_| Some("Successfully duplicated residue".to_string()))
+                .map_err(|err| GrafenCliError::RunError(
+                    format!("Could not duplicate residue: {}", err.description())
+                ))
+        },
         RemoveResidue, "Remove residue definitions" => {
             remove_items(&mut residue_list)
                 .map(|_| None)
@@ -59,6 +84,15 @@ struct ResidueBuilder {
     atoms: Vec<Atom>,
 }
 
+impl From<Residue> for ResidueBuilder {
+    fn from(residue: Residue) -> ResidueBuilder {
+        ResidueBuilder {
+            name: residue.code,
+            atoms: residue.atoms,
+        }
+    }
+}
+
 impl ResidueBuilder {
     fn new() -> ResidueBuilder {
         ResidueBuilder {
@@ -88,8 +122,11 @@ impl ResidueBuilder {
 
 fn new_residue() -> Result<Residue> {
     println!("Creating a new residue.\n");
-    let mut builder = ResidueBuilder::new();
+    edit_residue(ResidueBuilder::new())
+}
 
+/// Edit the residue definition of a builder in its menu.
+fn edit_residue(mut builder: ResidueBuilder) -> Result<Residue> {
     create_menu![
         @pre: { builder.print_state(); };
 
@@ -129,7 +166,7 @@ fn new_residue() -> Result<Residue> {
                 ))
             }
         },
-        QuitAndAddResidue, "Finish and add residue to list" => {
+        QuitAndAddResidue, "Finish and save residue to list" => {
             match builder.finalize() {
                 Ok(residue) => return Ok(residue),
                 Err(msg) => Err(GrafenCliError::RunError(format!("{}", msg))),
@@ -141,13 +178,73 @@ fn new_residue() -> Result<Residue> {
     ];
 }
 
+/// Select a residue from the list and replace it with an edited version.
+fn edit_residue_in_list(residue_list: &mut [Residue]) -> Result<()> {
+    eprintln!("Residue to edit:");
+    let index = select_item_index(&residue_list, 0)?;
+
+    residue_list[index] = edit_residue(ResidueBuilder::from(residue_list[index].clone()))?;
+
+    Ok(())
+}
+
+/// Select a residue from the list and add an edited copy of it to the list.
+fn duplicate_residue(residue_list: &mut Vec<Residue>) -> Result<()> {
+    eprintln!("Residue to duplicate:");
+    let index = select_item_index(&residue_list, 0)?;
+
+    let names = residue_list
+        .iter()
+        .map(|residue| residue.code.clone())
+        .collect::<Vec<_>>();
+
+    // Name the copy uniquely before it is edited
+    let mut copy = residue_list[index].clone();
+    copy.code = unique_name(&copy.code, &names);
+
+    let residue = edit_residue(ResidueBuilder::from(copy))?;
+    residue_list.push(residue);
+
+    Ok(())
+}
+
+fn import_residue() -> Result<Residue> {
+    let path = get_value_from_user::<String>("Path to residue file (.gro, .pdb or .xyz)")?;
+
+    let (commands, item_texts) = create_menu_items![
+        (Centering::None, "Keep atom positions from the file"),
+        (Centering::FirstAtom, "Place the first atom at the origin"),
+        (
+            Centering::CenterOfGeometry,
+            "Place the center of geometry at the origin"
+        )
+    ];
+
+    eprintln!("Centering of residue:");
+    let centering = select_command(item_texts, commands)?;
+
+    read_residue(&PathBuf::from(path), centering).map_err(|err| GrafenCliError::from(err))
+}
+
 fn create_atom() -> UIResult<Atom> {
     let name = get_value_from_user::<String>("Atom name")?;
     let position = get_position_from_user(None)?;
 
+    eprintln!("Optional atom information ([enter] to leave unset):");
+    let element = get_optional_value_from_user::<String>("Element")?;
+    let mass = get_optional_value_from_user::<f64>("Mass (g/mol)")?;
+    let charge = get_optional_value_from_user::<f64>("Partial charge (e)")?;
+    let atom_type = get_optional_value_from_user::<String>("Force field atom type")?;
+
     Ok(Atom {
         code: name.to_uppercase().to_string(),
         position: position,
+        metadata: AtomMetadata {
+            element,
+            mass,
+            charge,
+            atom_type,
+        },
     })
 }
 
@@ -171,7 +268,26 @@ mod tests {
         builder.atoms.push(Atom {
             code: "A".to_string(),
             position: Coord::ORIGO,
+            metadata: AtomMetadata::default(),
         });
         assert!(builder.finalize().is_ok());
     }
+
+    #[test]
+    fn residue_builder_from_residue_finalizes_to_the_same_residue() {
+        let residue = Residue {
+            code: "RES".to_string(),
+            atoms: vec![Atom {
+                code: "A".to_string(),
+                position: Coord::new(1.0, 2.0, 3.0),
+                metadata: AtomMetadata {
+                    element: Some("C".to_string()),
+                    ..AtomMetadata::default()
+                },
+            }],
+        };
+
+        let builder = ResidueBuilder::from(residue.clone());
+        assert_eq!(builder.finalize().unwrap(), residue);
+    }
 }
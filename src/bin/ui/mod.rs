@@ -10,22 +10,26 @@ mod edit_database;
 use super::Config;
 
 use crate::{
-    error::{GrafenCliError, Result, UIErrorKind, UIResult},
+    error::{GrafenCliError, Result, UIResult},
     output,
     ui::utils::{
-        get_coord_from_user, get_position_from_user, get_value_from_user,
-        get_value_or_default_from_user, remove_items, reorder_list, select_command,
-        select_direction, select_item, MenuResult, YesOrNo,
+        get_coord_from_user, get_optional_value_from_user, get_position_from_user,
+        get_value_from_user, get_value_or_default_from_user, remove_items, reorder_list,
+        select_command, select_direction, select_inclusion_policy, select_item,
+        warn_about_unknown_masses, MenuResult, YesOrNo,
     },
 };
 
+use dialoguer::Checkboxes;
 use grafen::{
-    coord::{Coord, Translate},
+    coord::Coord,
     database::*,
     read_conf::{ConfType, ReadConf},
+    read_system::{read_index_groups, read_system, ResidueGroup, SplitRule},
+    session::{read_session, write_session, Session},
     surface::LatticeType,
     system::*,
-    volume::{FillType, Volume},
+    volume::FillType,
 };
 use std::path::{Path, PathBuf};
 
@@ -44,7 +48,9 @@ pub fn user_menu(config: Config) -> Result<()> {
         output_path: config.output_path,
         database: config.database,
         components: config.components,
+        box_setting: config.box_setting,
     };
+    let mut session_path = config.session_path;
 
     create_menu![
         @pre: { system.print_state() };
@@ -52,6 +58,9 @@ pub fn user_menu(config: Config) -> Result<()> {
         AddComponent, "Construct a component" => {
             create_component(&mut system)
         },
+        LoadSystem, "Load a full system and split it into components" => {
+            load_system(&mut system)
+        },
         EditComponent, "Edit or clone a component" => {
             edit_component::user_menu(&mut system.components)
         },
@@ -64,8 +73,17 @@ pub fn user_menu(config: Config) -> Result<()> {
         EditDatabase, "Edit the database of residue and object definitions" => {
             edit_database::user_menu(&mut system.database)
         },
+        SetBox, "Set the size of the system box" => {
+            set_box_size(&mut system)
+        },
         SaveSystem, "Save the constructed components to disk as a system" => {
-            output::write_gromos(&system).map(|_| "Saved system to disk".to_string().into())
+            output::write_system(&system).map(|_| "Saved system to disk".to_string().into())
+        },
+        SaveSession, "Save the session to continue with the system later" => {
+            save_session(&system, &mut session_path)
+        },
+        LoadSession, "Load a saved session, replacing the current components" => {
+            load_session(&mut system, &mut session_path)
         },
         Quit, "Quit the program" => {
             return Ok(());
@@ -73,15 +91,158 @@ pub fn user_menu(config: Config) -> Result<()> {
     ];
 }
 
+#[derive(Clone, Copy, Debug)]
+enum BoxMenu {
+    Auto,
+    Fixed,
+    Padded,
+}
+
+/// Ask the user how to set the size of the system box.
+fn set_box_size(system: &mut System) -> MenuResult {
+    let (commands, item_texts) = create_menu_items![
+        (BoxMenu::Auto, "Use the maximum size of all components"),
+        (BoxMenu::Fixed, "Use a fixed size"),
+        (
+            BoxMenu::Padded,
+            "Add vacuum padding to the size of all components"
+        )
+    ];
+
+    system.box_setting = match select_command(item_texts, commands)? {
+        BoxMenu::Auto => BoxSetting::Auto,
+        BoxMenu::Fixed => {
+            let (x, y, z) = system.box_size().to_tuple();
+            let default = format!("{} {} {}", x, y, z);
+
+            BoxSetting::Fixed(get_coord_from_user("Box size (x y z nm)", Some(&default))?)
+        }
+        BoxMenu::Padded => {
+            BoxSetting::Padded(get_coord_from_user("Padding (x y z nm)", Some("0 0 0"))?)
+        }
+    };
+
+    Ok(Some(format!("Set the box size {}", system.box_setting)))
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SplitMenu {
+    ResidueBlocks,
+    IndexGroups,
+    ResidueGroups,
+}
+
+/// Ask the user for a configuration and how to split it, then add its parts as components.
+fn load_system(system: &mut System) -> MenuResult {
+    let path = get_value_from_user::<PathBuf>("Configuration file")?;
+
+    let (commands, item_texts) = create_menu_items![
+        (
+            SplitMenu::ResidueBlocks,
+            "Split by blocks of residues with the same name"
+        ),
+        (SplitMenu::IndexGroups, "Split by groups of an index file"),
+        (SplitMenu::ResidueGroups, "Split by groups of residue names")
+    ];
+    let rule = match select_command(item_texts, commands)? {
+        SplitMenu::ResidueBlocks => SplitRule::ResidueBlocks,
+        SplitMenu::IndexGroups => select_index_groups()?,
+        SplitMenu::ResidueGroups => get_residue_groups()?,
+    };
+
+    let mut loaded = read_system(&path, &rule)?;
+    let message = format!(
+        "Added {} components from '{}'",
+        loaded.components.len(),
+        path.display()
+    );
+
+    system.components.append(&mut loaded.components);
+
+    Ok(Some(message))
+}
+
+fn select_index_groups() -> Result<SplitRule> {
+    let path = get_value_from_user::<PathBuf>("Index file")?;
+    let groups = read_index_groups(&path)?;
+
+    let item_texts = groups
+        .iter()
+        .map(|group| format!("{} ({} atoms)", group.name, group.atoms.len()))
+        .collect::<Vec<_>>();
+    let item_refs = item_texts.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    eprintln!("Select groups to split the system by ([space] select, [enter] confirm):");
+    let selections = Checkboxes::new().items(&item_refs).interact()?;
+
+    let selected = groups
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selections.contains(i))
+        .map(|(_, group)| group)
+        .collect();
+
+    Ok(SplitRule::IndexGroups(selected))
+}
+
+fn get_residue_groups() -> Result<SplitRule> {
+    let mut groups = Vec::new();
+
+    while let Some(name) = get_optional_value_from_user::<String>("Group name (empty to finish)")? {
+        let residues = get_value_from_user::<String>("Residue names (separated by spaces)")?
+            .split_whitespace()
+            .map(|residue| residue.to_string())
+            .collect();
+
+        groups.push(ResidueGroup { name, residues });
+    }
+
+    Ok(SplitRule::ResidueGroups(groups))
+}
+
+/// Ask the user for a path and write the system as a session to it.
+fn save_session(system: &System, session_path: &mut Option<PathBuf>) -> MenuResult {
+    let default = session_path
+        .as_ref()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or("session.json".to_string());
+
+    let path = get_value_or_default_from_user::<PathBuf>("Session file", &default)?;
+    write_session(&Session::from_system(system), &path)?;
+
+    let message = format!("Saved session to '{}'", path.display());
+    *session_path = Some(path);
+
+    Ok(Some(message))
+}
+
+/// Ask the user for a session file and replace the system components with it.
+fn load_session(system: &mut System, session_path: &mut Option<PathBuf>) -> MenuResult {
+    let path = get_value_from_user::<PathBuf>("Session file")?;
+    let session = read_session(&path)?;
+
+    let message = format!(
+        "Loaded {} components from session '{}'",
+        session.components.len(),
+        path.display()
+    );
+
+    session.restore(system);
+    *session_path = Some(path);
+
+    Ok(Some(message))
+}
+
 /// Prompt the user to select a defined component from the `DataBase`, then create it.
 fn create_component(system: &mut System) -> MenuResult {
-    let component = select_item(
-        &system.database.component_defs,
-        Some("Available components"),
-    )?
-    .clone();
+    let definitions = system.database.components();
+    let selected = select_item(&definitions, Some("Available components"))?;
 
-    match fill_component(component, system.database.path.as_ref()) {
+    // Relative paths are relative to the database that the component is defined in
+    let component = selected.definition.clone();
+    let database_path = selected.path.cloned();
+
+    match fill_component(component, database_path.as_ref()) {
         Ok(filled) => {
             system.components.push(filled);
             Ok(Some("Added component to system".to_string()))
@@ -102,12 +263,15 @@ fn fill_component(
             let width = get_value_from_user::<f64>("Width ΔY (nm)")?;
             let height = get_value_from_user::<f64>("Height ΔZ (nm)")?;
 
-            let fill_type = select_num_coords_or_density_with_default(conf.density)?;
-
             conf.origin = position;
             conf.size = Coord::new(length, width, height);
 
-            Ok(ComponentEntry::from(conf.fill(fill_type)))
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
         }
 
         ComponentEntry::VolumeCylinder(mut conf) => {
@@ -115,18 +279,90 @@ fn fill_component(
             conf.radius = get_value_from_user::<f64>("Radius (nm)")?;
             conf.height = get_value_from_user::<f64>("Height (nm)")?;
 
-            let fill_type = select_num_coords_or_density_with_default(conf.density)?;
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
 
-            Ok(ComponentEntry::from(conf.fill(fill_type)))
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
         }
 
         ComponentEntry::VolumeSpheroid(mut conf) => {
             conf.origin = get_position_from_user(Some("0 0 0"))?;
             conf.radius = get_value_from_user::<f64>("Radius (nm)")?;
 
-            let fill_type = select_num_coords_or_density_with_default(conf.density)?;
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
 
-            Ok(ComponentEntry::from(conf.fill(fill_type)))
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
+        }
+
+        ComponentEntry::VolumeEllipsoid(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.semi_axes = get_coord_from_user("Semi-axes (a b c nm)", None)?;
+
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
+        }
+
+        ComponentEntry::VolumeCone(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.bottom_radius = get_value_from_user::<f64>("Bottom radius (nm)")?;
+            conf.top_radius = get_value_or_default_from_user::<f64>("Top radius (nm)", "0")?;
+            conf.height = get_value_from_user::<f64>("Height (nm)")?;
+
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
+        }
+
+        ComponentEntry::VolumeTorus(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.major_radius = get_value_from_user::<f64>("Major radius of ring (nm)")?;
+            conf.minor_radius = get_value_from_user::<f64>("Minor radius of tube (nm)")?;
+
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
+        }
+
+        ComponentEntry::VolumeCapsule(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.radius = get_value_from_user::<f64>("Radius (nm)")?;
+            conf.height = get_value_from_user::<f64>("Height of cylinder between caps (nm)")?;
+
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
+        }
+
+        ComponentEntry::VolumeSlab(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.thickness = get_value_from_user::<f64>("Thickness (nm)")?;
+            conf.length = get_value_from_user::<f64>("Length of filled region (nm)")?;
+            conf.width = get_value_from_user::<f64>("Width of filled region (nm)")?;
+
+            let fill_type = select_fill_type_with_default(conf.fill_type, conf.residue.as_ref())?;
+
+            Ok(construct_component(
+                ComponentEntry::from(conf),
+                Some(fill_type),
+            )?)
         }
 
         ComponentEntry::SurfaceSheet(mut conf) => {
@@ -141,11 +377,7 @@ fn fill_component(
                 _ => (),
             }
 
-            Ok(ComponentEntry::from(
-                conf.construct()
-                    .map_err(|_| UIErrorKind::from("Could not construct sheet"))?,
-            )
-            .with_pbc())
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
         }
 
         ComponentEntry::SurfaceCuboid(mut conf) => {
@@ -156,9 +388,7 @@ fn fill_component(
             let height = get_value_from_user::<f64>("Height ΔZ (nm)")?;
             conf.size = Coord::new(length, width, height);
 
-            Ok(ComponentEntry::from(conf.construct().map_err(|_| {
-                UIErrorKind::from("Could not construct cuboid surface")
-            })?))
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
         }
 
         ComponentEntry::SurfaceCylinder(mut conf) => {
@@ -166,9 +396,29 @@ fn fill_component(
             conf.radius = get_value_from_user::<f64>("Radius (nm)")?;
             conf.height = get_value_from_user::<f64>("Height (nm)")?;
 
-            Ok(ComponentEntry::from(conf.construct().map_err(|_| {
-                UIErrorKind::from("Could not construct cylinder")
-            })?))
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
+        }
+
+        ComponentEntry::SurfaceSphere(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.radius = get_value_from_user::<f64>("Radius (nm)")?;
+
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
+        }
+
+        ComponentEntry::SurfaceEllipsoid(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.semi_axes = get_coord_from_user("Semi-axes (a b c nm)", None)?;
+
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
+        }
+
+        ComponentEntry::SurfaceBilayer(mut conf) => {
+            conf.origin = get_position_from_user(Some("0 0 0"))?;
+            conf.length = get_value_from_user::<f64>("Length ΔX (nm)")?;
+            conf.width = get_value_from_user::<f64>("Width ΔY (nm)")?;
+
+            Ok(construct_component(ComponentEntry::from(conf), None)?)
         }
 
         ComponentEntry::ConfigurationFile(conf) => {
@@ -222,30 +472,18 @@ fn fill_component(
                 }
             };
 
-            // If the path is relative, it is relative to the database location.
-            // Construct the full path.
-            let path = if conf.path.is_absolute() {
-                conf.path
-            } else {
-                database_path
-                    .and_then(|db_path| db_path.parent())
-                    .map(|db_dir| PathBuf::from(db_dir))
-                    // If the database has no path, it has to be relative to
-                    // the current directory. Join with an empty path.
-                    .unwrap_or(PathBuf::new())
-                    .join(conf.path)
-            };
-
-            let mut new_conf = read_configuration(&path)?;
-
-            new_conf.description = conf.description;
-            new_conf.reconstruct(to_volume);
+            let policy = select_inclusion_policy("Keep residues if")?;
 
-            // Make sure that the origin is adjusted to that desired by the user.
-            let displayed_origin = new_conf.get_displayed_origin();
-            new_conf.translate_in_place(origin - displayed_origin);
+            let component = construct_configuration(
+                conf,
+                database_path.map(|path| path.as_path()),
+                origin,
+                Some(to_volume),
+                policy,
+            )?;
+            warn_about_unknown_masses(&component, policy);
 
-            Ok(ComponentEntry::from(new_conf))
+            Ok(component)
         }
     }
 }
@@ -264,35 +502,67 @@ pub fn read_configuration(path: &Path) -> Result<ReadConf> {
     Ok(conf)
 }
 
-fn select_num_coords_or_density_with_default(default_density: Option<f64>) -> UIResult<FillType> {
-    match default_density {
-        Some(density) => {
+fn select_fill_type_with_default(
+    default_fill_type: Option<FillType>,
+    residue: Option<&Residue>,
+) -> UIResult<FillType> {
+    match default_fill_type {
+        Some(fill_type) => {
             let (commands, item_texts) =
                 create_menu_items![(YesOrNo::Yes, "Yes"), (YesOrNo::No, "No")];
 
-            eprintln!("Use default density for component ({})?", density);
+            eprintln!("Use default fill type ({})?", fill_type);
             let command = select_command(item_texts, commands)?;
 
             match command {
-                YesOrNo::Yes => Ok(FillType::Density(density)),
-                YesOrNo::No => select_num_coords_or_density(),
+                YesOrNo::Yes => match check_fill_type(fill_type, residue) {
+                    Ok(fill_type) => Ok(fill_type),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        select_fill_type(residue)
+                    }
+                },
+                YesOrNo::No => select_fill_type(residue),
             }
         }
-        None => select_num_coords_or_density(),
+        None => select_fill_type(residue),
     }
 }
 
-fn select_num_coords_or_density() -> UIResult<FillType> {
+/// Assert that a fill type can be converted to a number of residues, ie. that
+/// the residue mass is known if filling by mass density.
+fn check_fill_type(fill_type: FillType, residue: Option<&Residue>) -> Result<FillType> {
+    fill_type
+        .check(residue)
+        .map_err(|err| GrafenCliError::ConstructError(err.to_string()))
+}
+
+fn select_fill_type(residue: Option<&Residue>) -> UIResult<FillType> {
     create_menu![
         @pre: { };
 
         Density, "Use density" => {
             let density = get_value_from_user::<f64>("Density (1/nm^3)")?;
 
-            if density > 0.0 {
-                return Ok(FillType::Density(density));
-            } else {
-                Err(GrafenCliError::ConstructError("Invalid density: it must be positive".into()))
+            match check_fill_type(FillType::Density(density), residue) {
+                Ok(fill_type) => return Ok(fill_type),
+                Err(err) => Err(err),
+            }
+        },
+        MassDensity, "Use mass density" => {
+            let density = get_value_from_user::<f64>("Mass density (g/cm^3)")?;
+
+            match check_fill_type(FillType::MassDensity(density), residue) {
+                Ok(fill_type) => return Ok(fill_type),
+                Err(err) => Err(err),
+            }
+        },
+        Molarity, "Use molar concentration" => {
+            let concentration = get_value_from_user::<f64>("Concentration (mol/L)")?;
+
+            match check_fill_type(FillType::Molarity(concentration), residue) {
+                Ok(fill_type) => return Ok(fill_type),
+                Err(err) => Err(err),
             }
         },
         NumCoords, "Use a specific number of residues" => {
@@ -5,6 +5,8 @@ use crate::error::{GrafenCliError, Result, UIErrorKind, UIResult};
 use grafen::{
     coord::{Coord, Direction},
     describe::{describe_list, describe_list_short, Describe},
+    system::Component,
+    volume::{num_residues_without_mass, InclusionPolicy},
 };
 
 use dialoguer::{Input, Select};
@@ -40,6 +42,22 @@ pub fn get_value_or_default_from_user<T: FromStr>(description: &str, default: &s
         .map_err(|_| UIErrorKind::from("could not parse a value"))
 }
 
+/// Parse an optional value from the user. An empty input leaves the value unset.
+pub fn get_optional_value_from_user<T: FromStr>(description: &str) -> UIResult<Option<T>> {
+    let input = Input::new(description)
+        .default("")
+        .show_default(false)
+        .interact()?;
+
+    match input.trim() {
+        "" => Ok(None),
+        value => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| UIErrorKind::from("could not parse a value")),
+    }
+}
+
 /// Get a `Coord` either from the user or by a default.
 pub fn get_position_from_user(default: Option<&str>) -> UIResult<Coord> {
     get_coord_from_user("Position (x y z nm)", default)
@@ -173,6 +191,43 @@ pub fn select_direction(
     select_command(item_texts, choices).map_err(|err| UIErrorKind::from(err))
 }
 
+/// Use a prompt to select how to decide whether residues are inside of a volume.
+pub fn select_inclusion_policy(description: &str) -> UIResult<InclusionPolicy> {
+    eprintln!("{}:", description);
+
+    let (choices, item_texts) = create_menu_items![
+        (InclusionPolicy::AnyAtom, "Any atom is inside"),
+        (InclusionPolicy::AllAtoms, "All atoms are inside"),
+        (
+            InclusionPolicy::CenterOfGeometry,
+            "The center of geometry is inside"
+        ),
+        (
+            InclusionPolicy::CenterOfMass,
+            "The center of mass is inside"
+        ),
+        (InclusionPolicy::FirstAtom, "The first atom is inside")
+    ];
+
+    select_command(item_texts, choices)
+}
+
+/// Warn if residues of a component are tested by their center of mass while the
+/// masses of their atoms are not known, since their center of geometry is used.
+pub fn warn_about_unknown_masses<'a, T: Component<'a>>(component: &'a T, policy: InclusionPolicy) {
+    if policy == InclusionPolicy::CenterOfMass {
+        let num_residues = num_residues_without_mass(component);
+
+        if num_residues > 0 {
+            eprintln!(
+                "warning: the mass of {} residues is not known, their center of geometry \
+                 is used instead of their center of mass",
+                num_residues
+            );
+        }
+    }
+}
+
 /// Promp the user to select an item from an input list. Return as a reference
 /// to the object.
 ///
@@ -0,0 +1,1209 @@
+//! C interface to the library, built with the `capi` feature.
+//!
+//! Databases and systems are passed to C as opaque handles which are created
+//! and freed by the functions below. Functions which can fail return a
+//! `GrafenStatus` code, or a null pointer for those which create handles.
+//! The message of the last error on the calling thread is available through
+//! `grafen_last_error`. The interface is declared in `include/grafen.h`.
+//!
+//! Panics do not unwind into C. They are caught and returned as `GrafenStatus::Run`.
+
+use crate::{
+    coord::Coord,
+    database::{library, read_database, DataBase},
+    error::GrafenError,
+    system::{Component, System},
+    volume::{FillType, InclusionPolicy},
+};
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    ptr, slice,
+};
+
+/// Opaque handle to a `DataBase`.
+pub struct GrafenDataBase(DataBase);
+
+/// Opaque handle to a `System`.
+pub struct GrafenSystem(System);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Status codes returned by the interface.
+pub enum GrafenStatus {
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An argument was invalid, eg. a string which is not UTF-8.
+    InvalidArgument = 2,
+    /// A file could not be read or written.
+    Io = 3,
+    /// An internal error occurred. The handles which were passed to the call
+    /// may be in an inconsistent state.
+    Run = 4,
+    /// The contents of a file could not be parsed.
+    Parse = 5,
+    /// A database or one of its definitions is invalid.
+    DataBase = 6,
+    /// The lattice of a surface definition is invalid.
+    Lattice = 7,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// How to fill a volume.
+pub enum GrafenFillType {
+    /// Use the default fill type of the definition.
+    Default = 0,
+    /// A number of residues.
+    Number = 1,
+    /// A number density (1/nm^3).
+    Density = 2,
+    /// A mass density (g/cm^3).
+    MassDensity = 3,
+    /// A molar concentration (mol/L).
+    Molarity = 4,
+}
+
+impl GrafenFillType {
+    /// Get the fill type of a value passed from C, which may be out of range.
+    fn from_raw(value: c_int) -> Option<GrafenFillType> {
+        match value {
+            0 => Some(GrafenFillType::Default),
+            1 => Some(GrafenFillType::Number),
+            2 => Some(GrafenFillType::Density),
+            3 => Some(GrafenFillType::MassDensity),
+            4 => Some(GrafenFillType::Molarity),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// When a residue is inside of a volume.
+pub enum GrafenInclusionPolicy {
+    /// Any atom of the residue is inside.
+    AnyAtom = 0,
+    /// All atoms of the residue are inside.
+    AllAtoms = 1,
+    /// The center of geometry of the residue is inside.
+    CenterOfGeometry = 2,
+    /// The center of mass of the residue is inside.
+    CenterOfMass = 3,
+    /// The first atom of the residue is inside.
+    FirstAtom = 4,
+}
+
+impl GrafenInclusionPolicy {
+    /// Get the inclusion policy of a value passed from C, which may be out of range.
+    fn from_raw(value: c_int) -> Option<InclusionPolicy> {
+        match value {
+            0 => Some(InclusionPolicy::AnyAtom),
+            1 => Some(InclusionPolicy::AllAtoms),
+            2 => Some(InclusionPolicy::CenterOfGeometry),
+            3 => Some(InclusionPolicy::CenterOfMass),
+            4 => Some(InclusionPolicy::FirstAtom),
+            _ => None,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', ""))
+        .unwrap_or_else(|_| CString::new("unknown error").unwrap());
+
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Error of an interface call: a status code along with a message.
+struct Error(GrafenStatus, String);
+
+impl From<GrafenError> for Error {
+    fn from(err: GrafenError) -> Error {
+        let status = match err {
+            GrafenError::InvalidParameter { .. } => GrafenStatus::InvalidArgument,
+            GrafenError::LatticeError(_) => GrafenStatus::Lattice,
+            GrafenError::IoError { .. } => GrafenStatus::Io,
+            GrafenError::ParseError { .. } => GrafenStatus::Parse,
+            GrafenError::DataBaseError { .. } => GrafenStatus::DataBase,
+        };
+
+        Error(status, err.to_string())
+    }
+}
+
+/// Run a call and catch any panic, which is returned as an error.
+fn catch_panic<T, F: FnOnce() -> Result<T, Error>>(call: F) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|payload| {
+        Err(Error(
+            GrafenStatus::Run,
+            format!("internal error: {}", panic_message(payload.as_ref())),
+        ))
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()))
+        .unwrap_or("the call panicked")
+}
+
+/// Run a call and convert its result to a status code, storing the error message.
+fn run<F: FnOnce() -> Result<(), Error>>(call: F) -> GrafenStatus {
+    match catch_panic(call) {
+        Ok(()) => GrafenStatus::Ok,
+        Err(Error(status, message)) => {
+            set_last_error(message);
+            status
+        }
+    }
+}
+
+/// Run a call which creates an object and return it as a handle, or null on errors.
+fn create<T, F: FnOnce() -> Result<T, Error>>(call: F) -> *mut T {
+    match catch_panic(call) {
+        Ok(object) => Box::into_raw(Box::new(object)),
+        Err(Error(_, message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn null_pointer(name: &str) -> Error {
+    Error(
+        GrafenStatus::NullPointer,
+        format!("the pointer '{}' is null", name),
+    )
+}
+
+unsafe fn get_str<'a>(string: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if string.is_null() {
+        return Err(null_pointer(name));
+    }
+
+    CStr::from_ptr(string).to_str().map_err(|_| {
+        Error(
+            GrafenStatus::InvalidArgument,
+            format!("the string '{}' is not valid UTF-8", name),
+        )
+    })
+}
+
+unsafe fn get_coord(coord: *const f64, name: &str) -> Result<Coord, Error> {
+    if coord.is_null() {
+        return Err(null_pointer(name));
+    }
+
+    let values = slice::from_raw_parts(coord, 3);
+
+    Ok(Coord::new(values[0], values[1], values[2]))
+}
+
+/// Get the fill type of a `GrafenFillType` value and the value to fill with.
+fn get_fill_type(fill_type: c_int, value: f64) -> Result<Option<FillType>, Error> {
+    let fill_type = GrafenFillType::from_raw(fill_type).ok_or_else(|| {
+        Error(
+            GrafenStatus::InvalidArgument,
+            format!("unknown fill type {}", fill_type),
+        )
+    })?;
+
+    let fill_type = match fill_type {
+        GrafenFillType::Default => None,
+        GrafenFillType::Number => {
+            if !value.is_finite() || value < 0.0 || value.fract() != 0.0 {
+                return Err(Error(
+                    GrafenStatus::InvalidArgument,
+                    format!(
+                        "the number of residues must be a non-negative integer, not {}",
+                        value
+                    ),
+                ));
+            }
+
+            Some(FillType::NumCoords(value as u64))
+        }
+        GrafenFillType::Density => Some(FillType::Density(value)),
+        GrafenFillType::MassDensity => Some(FillType::MassDensity(value)),
+        GrafenFillType::Molarity => Some(FillType::Molarity(value)),
+    };
+
+    Ok(fill_type)
+}
+
+/// Get the inclusion policy of a `GrafenInclusionPolicy` value.
+fn get_policy(policy: c_int) -> Result<InclusionPolicy, Error> {
+    GrafenInclusionPolicy::from_raw(policy).ok_or_else(|| {
+        Error(
+            GrafenStatus::InvalidArgument,
+            format!("unknown inclusion policy {}", policy),
+        )
+    })
+}
+
+unsafe fn get_ref<'a, T>(object: *const T, name: &str) -> Result<&'a T, Error> {
+    object.as_ref().ok_or_else(|| null_pointer(name))
+}
+
+unsafe fn get_mut<'a, T>(object: *mut T, name: &str) -> Result<&'a mut T, Error> {
+    object.as_mut().ok_or_else(|| null_pointer(name))
+}
+
+#[no_mangle]
+/// Get the message of the last error on this thread, or null if no error has occurred.
+///
+/// The message is valid until the next call which fails on this thread.
+pub extern "C" fn grafen_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+#[no_mangle]
+/// Read a database from a file. Returns null if it could not be read.
+///
+/// # Safety
+/// `path` must be null or a NUL-terminated string. The returned database is owned by
+/// the caller and freed with `grafen_database_free`.
+pub unsafe extern "C" fn grafen_database_read(path: *const c_char) -> *mut GrafenDataBase {
+    create(|| {
+        let path = PathBuf::from(get_str(path, "path")?);
+        Ok(GrafenDataBase(read_database(&path)?))
+    })
+}
+
+#[no_mangle]
+/// Get the built-in library of definitions.
+pub extern "C" fn grafen_database_library() -> *mut GrafenDataBase {
+    create(|| Ok(GrafenDataBase(library())))
+}
+
+#[no_mangle]
+/// Free a database. Null pointers are ignored.
+///
+/// # Safety
+/// `database` must be null or a database from `grafen_database_read` or
+/// `grafen_database_library` which has not been freed. The database must not be used
+/// after it is freed.
+pub unsafe extern "C" fn grafen_database_free(database: *mut GrafenDataBase) {
+    if !database.is_null() {
+        drop(Box::from_raw(database));
+    }
+}
+
+#[no_mangle]
+/// Create an empty system which constructs components from a copy of a database.
+/// Returns null if an argument is invalid.
+///
+/// # Safety
+/// `title` must be null or a NUL-terminated string and `database` must be null or a
+/// database from `grafen_database_read` or `grafen_database_library` which has not been
+/// freed. The database is copied and still owned by the caller. The returned system is
+/// owned by the caller and freed with `grafen_system_free`.
+pub unsafe extern "C" fn grafen_system_new(
+    title: *const c_char,
+    database: *const GrafenDataBase,
+) -> *mut GrafenSystem {
+    create(|| {
+        let title = get_str(title, "title")?;
+        let database = get_ref(database, "database")?;
+
+        Ok(GrafenSystem(
+            System::new(title).with_database(database.0.clone()),
+        ))
+    })
+}
+
+#[no_mangle]
+/// Free a system. Null pointers are ignored.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed.
+/// The system must not be used after it is freed.
+pub unsafe extern "C" fn grafen_system_free(system: *mut GrafenSystem) {
+    if !system.is_null() {
+        drop(Box::from_raw(system));
+    }
+}
+
+#[no_mangle]
+/// Construct a sheet from a named definition and add it to the system.
+///
+/// The origin is an array of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_sheet(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    length: f64,
+    width: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+
+        system.0.add_sheet(name, origin, length, width)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct a sheet with a blue noise lattice of a number of residues from a named
+/// definition and add it to the system.
+///
+/// The origin is an array of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_blue_noise_sheet(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    length: f64,
+    width: f64,
+    number: u64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+
+        system
+            .0
+            .add_blue_noise_sheet(name, origin, length, width, number)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct a cuboid surface from a named definition and add it to the system.
+///
+/// The origin and size are arrays of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string, `origin` must be null or point to
+/// three values and `size` must be null or point to three values. The arguments are
+/// only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_surface_cuboid(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    size: *const f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let size = get_coord(size, "size")?;
+
+        system.0.add_surface_cuboid(name, origin, size)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct a cylinder surface from a named definition and add it to the system.
+///
+/// The origin is an array of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_cylinder(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    radius: f64,
+    height: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+
+        system.0.add_cylinder(name, origin, radius, height)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct a spherical shell from a named definition and add it to the system.
+///
+/// The origin is an array of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_sphere_shell(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    radius: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+
+        system.0.add_sphere_shell(name, origin, radius)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct an ellipsoidal shell from a named definition and add it to the system.
+///
+/// The origin and semi axes are arrays of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string, `origin` must be null or point to
+/// three values and `semi_axes` must be null or point to three values. The arguments
+/// are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_ellipsoid_shell(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    semi_axes: *const f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let semi_axes = get_coord(semi_axes, "semi_axes")?;
+
+        system.0.add_ellipsoid_shell(name, origin, semi_axes)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Construct a lipid bilayer from a named definition and add it to the system.
+///
+/// The origin is an array of three values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_bilayer(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    length: f64,
+    width: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+
+        system.0.add_bilayer(name, origin, length, width)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a cuboid from a named definition and add it to the system.
+///
+/// The origin and size are arrays of three values. The fill type is one of the
+/// `GrafenFillType` values and its value is ignored for `GRAFEN_FILL_DEFAULT`.
+/// A number of residues must be a non-negative integer.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string, `origin` must be null or point to
+/// three values and `size` must be null or point to three values. The arguments are
+/// only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_cuboid(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    size: *const f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let size = get_coord(size, "size")?;
+
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system.0.fill_cuboid(name, origin, size, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a cylinder from a named definition and add it to the system.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_cylinder(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    radius: f64,
+    height: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_cylinder(name, origin, radius, height, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a sphere from a named definition and add it to the system.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_sphere(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    radius: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system.0.fill_sphere(name, origin, radius, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill an ellipsoid from a named definition and add it to the system.
+///
+/// The origin and semi axes are arrays of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string, `origin` must be null or point to
+/// three values and `semi_axes` must be null or point to three values. The arguments
+/// are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_ellipsoid(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    semi_axes: *const f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let semi_axes = get_coord(semi_axes, "semi_axes")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_ellipsoid(name, origin, semi_axes, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a cone from a named definition and add it to the system. A cone with a
+/// top radius is truncated into a frustum.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_cone(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    bottom_radius: f64,
+    top_radius: f64,
+    height: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_cone(name, origin, bottom_radius, top_radius, height, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a torus from a named definition and add it to the system.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_torus(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    major_radius: f64,
+    minor_radius: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_torus(name, origin, major_radius, minor_radius, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a capsule from a named definition and add it to the system.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_capsule(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    radius: f64,
+    height: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_capsule(name, origin, radius, height, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Fill a region of a slab from a named definition and add it to the system.
+///
+/// The origin is an array of three values. The fill type and value are used as for
+/// `grafen_system_fill_cuboid`.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_fill_slab(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    thickness: f64,
+    length: f64,
+    width: f64,
+    fill_type: c_int,
+    value: f64,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let fill_type = get_fill_type(fill_type, value)?;
+
+        system
+            .0
+            .fill_slab(name, origin, thickness, length, width, fill_type)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Read the configuration file of a named definition, reconstruct it into the volume
+/// of the definition and add it to the system with its origin at the input origin.
+///
+/// The origin is an array of three values. Residues are kept in the volume by the
+/// inclusion policy, one of the `GrafenInclusionPolicy` values.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed,
+/// `name` must be null or a NUL-terminated string and `origin` must be null or point to
+/// three values. The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_add_configuration(
+    system: *mut GrafenSystem,
+    name: *const c_char,
+    origin: *const f64,
+    policy: c_int,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        let name = get_str(name, "name")?;
+        let origin = get_coord(origin, "origin")?;
+        let policy = get_policy(policy)?;
+
+        system.0.add_configuration(name, origin, None, policy)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Remove the residues of a component which are inside a volume component by the
+/// inclusion policy, one of the `GrafenInclusionPolicy` values, with a margin around
+/// the volume. The components are given by their index in the system.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed.
+/// The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_prune_against(
+    system: *mut GrafenSystem,
+    index: usize,
+    volume_index: usize,
+    margin: f64,
+    policy: c_int,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+
+        let policy = get_policy(policy)?;
+
+        system
+            .0
+            .prune_against(index, volume_index, margin, policy)?;
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Get the number of components in a system, or 0 if the pointer is null.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed.
+/// The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_num_components(system: *const GrafenSystem) -> usize {
+    system
+        .as_ref()
+        .map(|system| system.0.components.len())
+        .unwrap_or(0)
+}
+
+#[no_mangle]
+/// Get the number of atoms in a system, or 0 if the pointer is null.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed.
+/// The arguments are only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_num_atoms(system: *const GrafenSystem) -> u64 {
+    system
+        .as_ref()
+        .map(|system| system.0.num_atoms())
+        .unwrap_or(0)
+}
+
+#[no_mangle]
+/// Copy the positions (nm) of all atoms in a system into an array as x, y, z triplets.
+///
+/// The array must have room for `3 * grafen_system_num_atoms(system)` values,
+/// which is given as its length.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed
+/// and `coordinates` must be null or point to `len` writable values. The arguments are
+/// only borrowed for the call.
+pub unsafe extern "C" fn grafen_system_coordinates(
+    system: *const GrafenSystem,
+    coordinates: *mut f64,
+    len: usize,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_ref(system, "system")?;
+
+        if coordinates.is_null() {
+            return Err(null_pointer("coordinates"));
+        }
+
+        let num_values = 3 * system.0.num_atoms() as usize;
+        if len < num_values {
+            return Err(Error(
+                GrafenStatus::InvalidArgument,
+                format!(
+                    "the array has length {} but {} values are needed",
+                    len, num_values
+                ),
+            ));
+        }
+
+        let output = slice::from_raw_parts_mut(coordinates, num_values);
+        let mut i = 0;
+
+        for component in &system.0.components {
+            let origin = component.get_origin();
+
+            for residue in component.iter_residues() {
+                for (_, position) in residue.get_atoms() {
+                    let (x, y, z) = (origin + position).to_tuple();
+                    output[i..i + 3].copy_from_slice(&[x, y, z]);
+                    i += 3;
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Write a system to a file, in the format of the path extension.
+///
+/// # Safety
+/// `system` must be null or a system from `grafen_system_new` which has not been freed
+/// and `path` must be null or a NUL-terminated string. The arguments are only borrowed
+/// for the call.
+pub unsafe extern "C" fn grafen_system_write(
+    system: *mut GrafenSystem,
+    path: *const c_char,
+) -> GrafenStatus {
+    run(|| {
+        let system = get_mut(system, "system")?;
+        system.0.output_path = PathBuf::from(get_str(path, "path")?);
+        system.0.write()?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_is_constructed_and_its_coordinates_are_copied() {
+        unsafe {
+            let database = grafen_database_library();
+            let name = CString::new("System").unwrap();
+            let system = grafen_system_new(name.as_ptr(), database);
+            grafen_database_free(database);
+
+            let sheet = CString::new("Graphene").unwrap();
+            let origin = [1.0, 2.0, 3.0];
+            assert_eq!(
+                grafen_system_add_sheet(system, sheet.as_ptr(), origin.as_ptr(), 1.0, 1.0),
+                GrafenStatus::Ok
+            );
+
+            let num_atoms = grafen_system_num_atoms(system) as usize;
+            assert!(num_atoms > 0);
+            assert_eq!(grafen_system_num_components(system), 1);
+
+            let mut coordinates = vec![0.0; 3 * num_atoms];
+            assert_eq!(
+                grafen_system_coordinates(system, coordinates.as_mut_ptr(), coordinates.len() - 1),
+                GrafenStatus::InvalidArgument
+            );
+            assert_eq!(
+                grafen_system_coordinates(system, coordinates.as_mut_ptr(), coordinates.len()),
+                GrafenStatus::Ok
+            );
+            assert_eq!(coordinates[2], 3.0);
+
+            assert_eq!(
+                grafen_system_prune_against(system, 0, 0, 0.0, 5),
+                GrafenStatus::InvalidArgument
+            );
+            let message = CStr::from_ptr(grafen_last_error()).to_str().unwrap();
+            assert_eq!(message, "unknown inclusion policy 5");
+
+            grafen_system_free(system);
+        }
+    }
+
+    #[test]
+    fn errors_return_status_codes_and_set_the_last_error() {
+        unsafe {
+            let database = grafen_database_library();
+            let name = CString::new("System").unwrap();
+            let system = grafen_system_new(name.as_ptr(), database);
+
+            let unknown = CString::new("Unknown").unwrap();
+            let origin = [0.0, 0.0, 0.0];
+            assert_eq!(
+                grafen_system_add_sheet(system, unknown.as_ptr(), origin.as_ptr(), 1.0, 1.0),
+                GrafenStatus::DataBase
+            );
+
+            let message = CStr::from_ptr(grafen_last_error()).to_str().unwrap();
+            assert!(message.contains("Unknown"));
+
+            assert_eq!(
+                grafen_system_add_sheet(system, ptr::null(), origin.as_ptr(), 1.0, 1.0),
+                GrafenStatus::NullPointer
+            );
+            assert!(grafen_system_new(name.as_ptr(), ptr::null()).is_null());
+
+            let path = CString::new("/not/a/database.json").unwrap();
+            assert!(grafen_database_read(path.as_ptr()).is_null());
+
+            let water = CString::new("Water (SPC/E)").unwrap();
+            let size = [1.0, 1.0, 1.0];
+            assert_eq!(
+                grafen_system_fill_cuboid(
+                    system,
+                    water.as_ptr(),
+                    origin.as_ptr(),
+                    size.as_ptr(),
+                    GrafenFillType::Molarity as c_int + 1,
+                    1.0
+                ),
+                GrafenStatus::InvalidArgument
+            );
+
+            grafen_system_free(system);
+            grafen_database_free(database);
+        }
+    }
+
+    #[test]
+    fn surfaces_and_volumes_are_constructed_from_named_definitions() {
+        let residue = r#"{ "code": "SOL", "atoms": [{ "code": "OW", "position": { "x": 0.0, "y": 0.0, "z": 0.0 } }] }"#;
+        let definitions = [
+            format!(
+                r#"{{ "VolumeSpheroid": {{ "name": "Sphere", "residue": {} }} }}"#,
+                residue
+            ),
+            format!(
+                r#"{{ "VolumeCone": {{ "name": "Cone", "residue": {}, "alignment": "Z" }} }}"#,
+                residue
+            ),
+            format!(
+                r#"{{ "SurfaceSheet": {{ "name": "Noise", "residue": {}, "lattice": {{ "BlueNoise": {{}} }}, "normal": "Z" }} }}"#,
+                residue
+            ),
+        ];
+
+        let mut database = library();
+        for definition in definitions.iter() {
+            database
+                .component_defs
+                .push(serde_json::from_str(definition).unwrap());
+        }
+
+        unsafe {
+            let database = Box::into_raw(Box::new(GrafenDataBase(database)));
+            let name = CString::new("System").unwrap();
+            let system = grafen_system_new(name.as_ptr(), database);
+            grafen_database_free(database);
+
+            let origin = [1.0, 1.0, 1.0];
+            let number = GrafenFillType::Number as c_int;
+
+            let nanotube = CString::new("Graphene Nanotube").unwrap();
+            assert_eq!(
+                grafen_system_add_cylinder(system, nanotube.as_ptr(), origin.as_ptr(), 1.0, 2.0),
+                GrafenStatus::Ok
+            );
+
+            let sphere = CString::new("Sphere").unwrap();
+            assert_eq!(
+                grafen_system_fill_sphere(
+                    system,
+                    sphere.as_ptr(),
+                    origin.as_ptr(),
+                    1.0,
+                    number,
+                    10.0
+                ),
+                GrafenStatus::Ok
+            );
+
+            let cone = CString::new("Cone").unwrap();
+            assert_eq!(
+                grafen_system_fill_cone(
+                    system,
+                    cone.as_ptr(),
+                    origin.as_ptr(),
+                    1.0,
+                    0.5,
+                    2.0,
+                    number,
+                    5.0
+                ),
+                GrafenStatus::Ok
+            );
+            assert_eq!(
+                grafen_system_fill_cone(
+                    system,
+                    cone.as_ptr(),
+                    origin.as_ptr(),
+                    1.0,
+                    0.5,
+                    2.0,
+                    number,
+                    2.5
+                ),
+                GrafenStatus::InvalidArgument
+            );
+
+            let noise = CString::new("Noise").unwrap();
+            assert_eq!(
+                grafen_system_add_sheet(system, noise.as_ptr(), origin.as_ptr(), 2.0, 2.0),
+                GrafenStatus::InvalidArgument
+            );
+            assert_eq!(
+                grafen_system_add_blue_noise_sheet(
+                    system,
+                    noise.as_ptr(),
+                    origin.as_ptr(),
+                    2.0,
+                    2.0,
+                    4
+                ),
+                GrafenStatus::Ok
+            );
+
+            let any_atom = GrafenInclusionPolicy::AnyAtom as c_int;
+            assert_eq!(
+                grafen_system_add_configuration(system, sphere.as_ptr(), origin.as_ptr(), any_atom),
+                GrafenStatus::DataBase
+            );
+
+            assert_eq!(grafen_system_num_components(system), 4);
+
+            let components = &(&*system).0.components;
+            assert_eq!(
+                components[1..]
+                    .iter()
+                    .map(|component| component.num_atoms())
+                    .collect::<Vec<_>>(),
+                vec![10, 5, 4]
+            );
+
+            grafen_system_free(system);
+        }
+    }
+
+    #[test]
+    fn fill_numbers_must_be_non_negative_integers() {
+        let number = GrafenFillType::Number as c_int;
+
+        assert_eq!(
+            get_fill_type(number, 10.0).ok(),
+            Some(Some(FillType::NumCoords(10)))
+        );
+        assert_eq!(
+            get_fill_type(number, 0.0).ok(),
+            Some(Some(FillType::NumCoords(0)))
+        );
+
+        for &value in &[-1.0, 2.5, f64::NAN, f64::INFINITY] {
+            match get_fill_type(number, value) {
+                Err(Error(status, _)) => assert_eq!(status, GrafenStatus::InvalidArgument),
+                Ok(_) => panic!("the number {} was accepted", value),
+            }
+        }
+
+        assert_eq!(
+            get_fill_type(GrafenFillType::Density as c_int, 2.5).ok(),
+            Some(Some(FillType::Density(2.5)))
+        );
+        assert_eq!(
+            get_fill_type(GrafenFillType::Default as c_int, -1.0).ok(),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn panics_are_caught_and_returned_as_errors() {
+        assert_eq!(run(|| panic!("a bad call")), GrafenStatus::Run);
+
+        let message = unsafe { CStr::from_ptr(grafen_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "internal error: a bad call");
+
+        let object: *mut GrafenDataBase = create(|| panic!("a bad call"));
+        assert!(object.is_null());
+    }
+}
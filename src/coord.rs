@@ -171,6 +171,67 @@ impl Coord {
             do_pbc(z, box_size.z),
         )
     }
+
+    /// Calculate the dot product of two coordinates.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::Coord;
+    /// let coord1 = Coord::new(1.0, 2.0, 3.0);
+    /// let coord2 = Coord::new(4.0, -5.0, 6.0);
+    /// assert_eq!(12.0, coord1.dot(coord2));
+    /// ```
+    pub fn dot(self, other: Coord) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Calculate the cross product of two coordinates.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::Coord;
+    /// let coord1 = Coord::new(1.0, 0.0, 0.0);
+    /// let coord2 = Coord::new(0.0, 1.0, 0.0);
+    /// assert_eq!(Coord::new(0.0, 0.0, 1.0), coord1.cross(coord2));
+    /// ```
+    pub fn cross(self, other: Coord) -> Coord {
+        Coord::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Return the length of the coordinate as a vector from origo.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::Coord;
+    /// assert_eq!(5.0, Coord::new(3.0, 0.0, 4.0).norm());
+    /// ```
+    pub fn norm(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Return the coordinate scaled to unit length.
+    ///
+    /// A coordinate at origo has no direction and is returned as is.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::Coord;
+    /// assert_eq!(Coord::new(0.6, 0.0, 0.8), Coord::new(3.0, 0.0, 4.0).normalize());
+    /// assert_eq!(Coord::ORIGO, Coord::ORIGO.normalize());
+    /// ```
+    pub fn normalize(self) -> Coord {
+        let norm = self.norm();
+
+        if norm > 0.0 {
+            self * (1.0 / norm)
+        } else {
+            self
+        }
+    }
 }
 
 impl From<RVec> for Coord {
@@ -336,6 +397,23 @@ pub enum Direction {
     Z,
 }
 
+impl Direction {
+    /// Return a unit vector along the positive direction.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::{Coord, Direction};
+    /// assert_eq!(Coord::new(0.0, 1.0, 0.0), Direction::Y.unit_vector());
+    /// ```
+    pub fn unit_vector(self) -> Coord {
+        match self {
+            Direction::X => Coord::new(1.0, 0.0, 0.0),
+            Direction::Y => Coord::new(0.0, 1.0, 0.0),
+            Direction::Z => Coord::new(0.0, 0.0, 1.0),
+        }
+    }
+}
+
 impl Display for Direction {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match *self {
@@ -346,6 +424,114 @@ impl Display for Direction {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// An arbitrary rotation in three dimensions, stored as a rotation matrix.
+///
+/// # Examples
+/// ```
+/// # use grafen::coord::{Coord, Rotation};
+/// let rotation = Rotation::between(Coord::new(1.0, 0.0, 0.0), Coord::new(0.0, 0.0, 1.0));
+/// assert_eq!(Coord::new(0.0, 0.0, 2.0), rotation.apply(Coord::new(2.0, 0.0, 0.0)));
+/// ```
+pub struct Rotation {
+    pub matrix: [[f64; 3]; 3],
+}
+
+impl Rotation {
+    /// A rotation which does nothing.
+    pub const IDENTITY: Self = Rotation {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    /// Construct a rotation by an angle (in radians) around an axis through origo.
+    ///
+    /// The rotation is counter-clockwise when looking along the axis towards origo.
+    ///
+    /// # Examples
+    /// ```
+    /// # use grafen::coord::{Coord, Rotation};
+    /// # use std::f64::consts::PI;
+    /// let rotation = Rotation::around_axis(Coord::new(0.0, 0.0, 1.0), PI / 2.0);
+    /// assert_eq!(Coord::new(0.0, 1.0, 0.0), rotation.apply(Coord::new(1.0, 0.0, 0.0)));
+    /// ```
+    pub fn around_axis(axis: Coord, angle: f64) -> Rotation {
+        let (x, y, z) = axis.normalize().to_tuple();
+        let (sin, cos) = angle.sin_cos();
+        let t = 1.0 - cos;
+
+        Rotation {
+            matrix: [
+                [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y],
+                [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x],
+                [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos],
+            ],
+        }
+    }
+
+    /// Construct the smallest rotation which turns one direction into another.
+    ///
+    /// If the directions are opposite the rotation is made around an arbitrary
+    /// perpendicular axis.
+    pub fn between(from: Coord, to: Coord) -> Rotation {
+        let (from, to) = (from.normalize(), to.normalize());
+
+        let axis = from.cross(to);
+        let sin = axis.norm();
+        let cos = from.dot(to);
+
+        if sin > 1e-9 {
+            Rotation::around_axis(axis, sin.atan2(cos))
+        } else if cos > 0.0 {
+            Rotation::IDENTITY
+        } else {
+            // Find any axis perpendicular to the direction by crossing it
+            // with the coordinate axis it is least aligned with
+            let (x, y, z) = from.to_tuple();
+
+            let least_aligned = if x.abs() <= y.abs() && x.abs() <= z.abs() {
+                Coord::new(1.0, 0.0, 0.0)
+            } else if y.abs() <= z.abs() {
+                Coord::new(0.0, 1.0, 0.0)
+            } else {
+                Coord::new(0.0, 0.0, 1.0)
+            };
+
+            Rotation::around_axis(from.cross(least_aligned), ::std::f64::consts::PI)
+        }
+    }
+
+    /// Return the rotation which first applies this and then the other rotation.
+    pub fn then(self, other: Rotation) -> Rotation {
+        let (a, b) = (other.matrix, self.matrix);
+        let mut matrix = [[0.0; 3]; 3];
+
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix[i][j] = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+            }
+        }
+
+        Rotation { matrix }
+    }
+
+    /// Rotate a coordinate around origo.
+    pub fn apply(&self, coord: Coord) -> Coord {
+        let row = |r: [f64; 3]| r[0] * coord.x + r[1] * coord.y + r[2] * coord.z;
+
+        Coord::new(
+            row(self.matrix[0]),
+            row(self.matrix[1]),
+            row(self.matrix[2]),
+        )
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Rotation {
+        Rotation::IDENTITY
+    }
+}
+
 /// Rotate a set of coordinates around an axis.
 pub fn rotate_coords(coords: &[Coord], axis: Direction) -> Vec<Coord> {
     coords.iter().map(|&coord| coord.rotate(axis)).collect()
@@ -414,7 +600,7 @@ pub trait Periodic {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
+    use std::{f64::consts::PI, str::FromStr};
 
     #[test]
     fn coord_origo_is_correct() {
@@ -685,4 +871,43 @@ mod tests {
 
         assert_eq!(coord, Coord::new(x, y, z));
     }
+
+    #[test]
+    fn rotation_between_directions_turns_one_into_the_other() {
+        let directions = vec![
+            Coord::new(1.0, 0.0, 0.0),
+            Coord::new(0.0, -1.0, 0.0),
+            Coord::new(1.0, 2.0, 3.0).normalize(),
+            Coord::new(-1.0, 0.5, -0.2).normalize(),
+        ];
+
+        for &from in &directions {
+            for &to in &directions {
+                assert_eq!(to, Rotation::between(from, to).apply(from));
+                assert_eq!(-to, Rotation::between(from, -to).apply(from));
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_preserves_lengths() {
+        let rotation = Rotation::around_axis(Coord::new(1.0, 1.0, 0.0), 1.2);
+        let coord = Coord::new(2.0, -1.0, 0.5);
+
+        assert!((rotation.apply(coord).norm() - coord.norm()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chained_rotations_are_applied_in_order() {
+        let first = Rotation::around_axis(Coord::new(0.0, 0.0, 1.0), PI / 2.0);
+        let second = Rotation::around_axis(Coord::new(1.0, 0.0, 0.0), PI / 2.0);
+        let coord = Coord::new(1.0, 0.0, 0.0);
+
+        // x -> y around z, then y -> z around x
+        assert_eq!(Coord::new(0.0, 0.0, 1.0), first.then(second).apply(coord));
+        assert_eq!(
+            second.apply(first.apply(coord)),
+            first.then(second).apply(coord)
+        );
+    }
 }
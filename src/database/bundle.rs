@@ -0,0 +1,417 @@
+//! Share a `DataBase` along with the configuration files it refers to.
+//!
+//! A bundle is a directory with the database in `database.json` and copies of
+//! every configuration file it refers to in `confs`. The paths to the configurations
+//! are relative to the bundle, which can thus be moved or archived as a whole.
+
+use super::{
+    merge::{get_database_dir, rewrite_conf_path},
+    merge_database, read_database, write_database, ComponentEntry, Conflict, DataBase, MergeAction,
+    MergeSummary,
+};
+use crate::error::{GrafenError, Result};
+
+use std::{
+    fs::{self, DirBuilder},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the database file in a bundle.
+pub const BUNDLE_DATABASE: &str = "database.json";
+
+/// Name of the directory with configuration files in a bundle.
+pub const BUNDLE_CONFS: &str = "confs";
+
+/// Export the definitions of a `DataBase` as a bundle in a directory.
+///
+/// The directory is created if it does not exist. Configuration files are copied into
+/// it and their paths are rewritten to the copies. Read-only layers are not exported.
+/// Returns the database of the bundle.
+///
+/// # Errors
+/// Returns an error if a configuration file could not be copied or if the database
+/// could not be written.
+pub fn export_bundle(database: &DataBase, dir: &Path) -> Result<DataBase> {
+    let source_dir = get_database_dir(database.path.as_ref());
+
+    let mut bundle = DataBase {
+        path: Some(dir.join(BUNDLE_DATABASE)),
+        residue_defs: database.residue_defs.clone(),
+        component_defs: database.component_defs.clone(),
+        ..DataBase::new()
+    };
+
+    create_dir(dir)?;
+    copy_configurations(bundle.component_defs.iter_mut(), &source_dir, dir)?;
+    write_database(&bundle)?;
+
+    Ok(bundle)
+}
+
+/// Read the `DataBase` of a bundle.
+///
+/// The input path is either the bundle directory or its database file.
+pub fn read_bundle(path: &Path) -> Result<DataBase> {
+    if path.is_dir() {
+        read_database(&path.join(BUNDLE_DATABASE))
+    } else {
+        read_database(path)
+    }
+}
+
+/// Import the definitions of a bundle into a `DataBase`.
+///
+/// The configuration files of the bundle are copied into the `confs` directory next
+/// to the database and the paths are rewritten to the copies, so that the bundle can
+/// be removed after it has been imported. Only the files of definitions which are
+/// added, overwritten or renamed are copied, and definitions which refer to a file
+/// that has already been copied are identical to the imported definition. If the
+/// database has no path the files are not copied and the paths refer to the bundle.
+/// Conflicting definitions are resolved as in `merge_database`.
+///
+/// The files are copied immediately, not when the database is written. The files
+/// which were created are returned in the summary, so that they can be removed
+/// if the import is discarded.
+///
+/// # Errors
+/// Returns an error if the bundle could not be read or a configuration file could
+/// not be copied.
+pub fn import_bundle<F>(database: &mut DataBase, path: &Path, resolve: F) -> Result<MergeSummary>
+where
+    F: FnMut(&Conflict) -> MergeAction,
+{
+    let mut bundle = read_bundle(path)?;
+
+    if database.path.is_none() {
+        return Ok(merge_database(database, &bundle, resolve));
+    }
+
+    let bundle_dir = get_database_dir(bundle.path.as_ref());
+    let target_dir = get_database_dir(database.path.as_ref());
+    let confs_dir = target_dir.join(BUNDLE_CONFS);
+
+    // Refer to the copies of files which have already been imported, so that
+    // definitions which were imported before are found to be identical
+    for component in bundle.component_defs.iter_mut() {
+        *component = rewrite_conf_path(component.clone(), &bundle_dir, &target_dir);
+
+        if let &mut ComponentEntry::ConfigurationFile(ref mut conf) = component {
+            let source = target_dir.join(&conf.path);
+
+            if let Some(file_name) = find_copy_in_dir(&source, &confs_dir) {
+                conf.path = PathBuf::from(BUNDLE_CONFS).join(file_name);
+            }
+        }
+    }
+
+    bundle.path = database.path.clone();
+
+    let mut summary = merge_database(database, &bundle, resolve);
+
+    let merged = database
+        .component_defs
+        .iter_mut()
+        .enumerate()
+        .filter(|(i, _)| summary.merged_components.contains(i))
+        .map(|(_, component)| component);
+
+    summary.copied_files = copy_configurations(merged, &target_dir, &target_dir)?;
+
+    Ok(summary)
+}
+
+/// Copy the configuration files of components into the `confs` directory of a target
+/// directory and rewrite their paths to be relative to it. Returns the paths to the files
+/// which were created.
+///
+/// Relative paths are relative to the source directory.
+fn copy_configurations<'a, I>(
+    components: I,
+    source_dir: &Path,
+    target_dir: &Path,
+) -> Result<Vec<PathBuf>>
+where
+    I: Iterator<Item = &'a mut ComponentEntry>,
+{
+    let confs_dir = target_dir.join(BUNDLE_CONFS);
+    let mut created = Vec::new();
+
+    for component in components {
+        if let &mut ComponentEntry::ConfigurationFile(ref mut conf) = component {
+            let source = source_dir.join(&conf.path);
+
+            create_dir(&confs_dir)?;
+            let (file_name, is_new) = copy_file_to_dir(&source, &confs_dir)
+                .map_err(|err| GrafenError::io(&source, err))?;
+
+            if is_new {
+                created.push(confs_dir.join(&file_name));
+            }
+
+            conf.path = PathBuf::from(BUNDLE_CONFS).join(file_name);
+        }
+    }
+
+    Ok(created)
+}
+
+fn create_dir(dir: &Path) -> Result<()> {
+    DirBuilder::new()
+        .recursive(true)
+        .create(dir)
+        .map_err(|err| GrafenError::io(dir, err))
+}
+
+/// Copy a file into a directory and return the name of the copy, and whether it
+/// was created.
+///
+/// If a file with the same name but a different content exists in the directory
+/// the copy is renamed with a number. Identical files are only copied once.
+fn copy_file_to_dir(source: &Path, dir: &Path) -> io::Result<(PathBuf, bool)> {
+    let contents = fs::read(source)?;
+
+    let file_name = source
+        .file_name()
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path is not a file"))?;
+
+    for candidate in candidate_names(&file_name) {
+        let target = dir.join(&candidate);
+
+        if !target.exists() {
+            fs::write(&target, &contents)?;
+            return Ok((candidate, true));
+        } else if fs::read(&target)? == contents {
+            return Ok((candidate, false));
+        }
+    }
+
+    unreachable!();
+}
+
+/// Find the name of an identical copy of a file in a directory, as it would be
+/// named by `copy_file_to_dir`.
+fn find_copy_in_dir(source: &Path, dir: &Path) -> Option<PathBuf> {
+    let contents = fs::read(source).ok()?;
+
+    for candidate in candidate_names(Path::new(source.file_name()?)) {
+        match fs::read(dir.join(&candidate)) {
+            Ok(existing) if existing == contents => return Some(candidate),
+            Ok(_) => (),
+            Err(_) => return None,
+        }
+    }
+
+    None
+}
+
+/// Iterate over the file name followed by numbered versions of it: `conf.gro`,
+/// `conf-2.gro`, `conf-3.gro`, etc.
+fn candidate_names<'a>(file_name: &'a Path) -> impl Iterator<Item = PathBuf> + 'a {
+    let stem = file_name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = file_name
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned());
+
+    Some(file_name.to_path_buf())
+        .into_iter()
+        .chain((2..).map(move |i| match extension {
+            Some(ref extension) => PathBuf::from(format!("{}-{}.{}", stem, i, extension)),
+            None => PathBuf::from(format!("{}-{}", stem, i)),
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coord::Coord,
+        read_conf::{ConfType, ReadConf},
+    };
+    use std::{env::temp_dir, fs::remove_dir_all};
+
+    fn conf_entry(description: &str, path: &Path) -> ComponentEntry {
+        ComponentEntry::ConfigurationFile(ReadConf {
+            conf: None,
+            backup_conf: None,
+            path: path.to_path_buf(),
+            description: description.to_string(),
+            volume_type: ConfType::Spheroid {
+                origin: Coord::ORIGO,
+                radius: 1.0,
+            },
+        })
+    }
+
+    fn conf_path(component: &ComponentEntry) -> PathBuf {
+        match component {
+            &ComponentEntry::ConfigurationFile(ref conf) => conf.path.clone(),
+            _ => panic!("not a configuration"),
+        }
+    }
+
+    #[test]
+    fn candidate_names_are_numbered_before_the_extension() {
+        let names = candidate_names(Path::new("conf.gro"))
+            .take(3)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("conf.gro"),
+                PathBuf::from("conf-2.gro"),
+                PathBuf::from("conf-3.gro")
+            ]
+        );
+    }
+
+    #[test]
+    fn candidate_names_keep_every_dot_of_the_file_stem() {
+        let names = candidate_names(Path::new("conf.v1.gro"))
+            .take(2)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![PathBuf::from("conf.v1.gro"), PathBuf::from("conf.v1-2.gro")]
+        );
+
+        let names = candidate_names(Path::new("conf")).take(2).collect::<Vec<_>>();
+        assert_eq!(names, vec![PathBuf::from("conf"), PathBuf::from("conf-2")]);
+    }
+
+    #[test]
+    fn exported_bundle_is_imported_with_configurations_copied_to_the_database() {
+        let root = temp_dir().join(format!("grafen-bundle-test-{}", std::process::id()));
+        let source_dir = root.join("source");
+        let bundle_dir = root.join("bundle");
+        let target_dir = root.join("target");
+
+        DirBuilder::new()
+            .recursive(true)
+            .create(source_dir.join("other"))
+            .unwrap();
+        DirBuilder::new()
+            .recursive(true)
+            .create(&target_dir)
+            .unwrap();
+
+        // Two different files with the same name, one with a relative path
+        fs::write(source_dir.join("conf.gro"), "first").unwrap();
+        fs::write(source_dir.join("other").join("conf.gro"), "second").unwrap();
+
+        let database = DataBase {
+            path: Some(source_dir.join("database.json")),
+            component_defs: vec![
+                conf_entry("first", Path::new("conf.gro")),
+                conf_entry("second", &source_dir.join("other").join("conf.gro")),
+            ],
+            ..DataBase::new()
+        };
+
+        let bundle = export_bundle(&database, &bundle_dir).unwrap();
+        assert_eq!(
+            conf_path(&bundle.component_defs[0]),
+            Path::new("confs/conf.gro")
+        );
+        assert_eq!(
+            conf_path(&bundle.component_defs[1]),
+            Path::new("confs/conf-2.gro")
+        );
+        assert!(bundle_dir.join(BUNDLE_DATABASE).is_file());
+
+        let mut target = DataBase {
+            path: Some(target_dir.join("database.json")),
+            ..DataBase::new()
+        };
+
+        let summary = import_bundle(&mut target, &bundle_dir, |_| MergeAction::Skip).unwrap();
+        assert_eq!(summary.added, 2);
+        assert_eq!(
+            summary.copied_files,
+            vec![
+                target_dir.join("confs/conf.gro"),
+                target_dir.join("confs/conf-2.gro")
+            ]
+        );
+
+        remove_dir_all(&bundle_dir).unwrap();
+
+        let contents = target
+            .component_defs
+            .iter()
+            .map(|component| fs::read_to_string(target_dir.join(conf_path(component))).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(contents, vec!["first", "second"]);
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn only_configurations_of_imported_definitions_are_copied() {
+        let root = temp_dir().join(format!("grafen-bundle-skip-test-{}", std::process::id()));
+        let source_dir = root.join("source");
+        let bundle_dir = root.join("bundle");
+        let target_dir = root.join("target");
+
+        DirBuilder::new()
+            .recursive(true)
+            .create(&source_dir)
+            .unwrap();
+        DirBuilder::new()
+            .recursive(true)
+            .create(&target_dir)
+            .unwrap();
+
+        fs::write(source_dir.join("conf.gro"), "bundled").unwrap();
+
+        let database = DataBase {
+            path: Some(source_dir.join("database.json")),
+            component_defs: vec![conf_entry("conf", Path::new("conf.gro"))],
+            ..DataBase::new()
+        };
+
+        export_bundle(&database, &bundle_dir).unwrap();
+
+        // A skipped definition leaves no copy of its file behind
+        let existing = conf_entry("conf", &root.join("other.gro"));
+        let mut target = DataBase {
+            path: Some(target_dir.join("database.json")),
+            component_defs: vec![existing.clone()],
+            ..DataBase::new()
+        };
+
+        let summary = import_bundle(&mut target, &bundle_dir, |_| MergeAction::Skip).unwrap();
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(target.component_defs.len(), 1);
+        assert!(!target_dir.join(BUNDLE_CONFS).exists());
+
+        // Importing the bundle again finds the definition to be identical
+        let mut target = DataBase {
+            path: Some(target_dir.join("database.json")),
+            ..DataBase::new()
+        };
+
+        import_bundle(&mut target, &bundle_dir, |_| MergeAction::Skip).unwrap();
+        let summary = import_bundle(&mut target, &bundle_dir, |_| {
+            panic!("there should be no conflicts")
+        })
+        .unwrap();
+
+        assert_eq!(summary.duplicates, 1);
+        assert!(summary.copied_files.is_empty());
+        assert_eq!(target.component_defs.len(), 1);
+        assert_eq!(
+            fs::read_dir(target_dir.join(BUNDLE_CONFS)).unwrap().count(),
+            1
+        );
+
+        remove_dir_all(&root).unwrap();
+    }
+}
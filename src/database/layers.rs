@@ -0,0 +1,220 @@
+//! Stack databases of different scopes into a single view of definitions.
+//!
+//! A `DataBase` is the writable layer of definitions. Read-only databases can be
+//! added as layers below it. Definitions in higher layers shadow definitions with
+//! the same name in lower layers.
+
+use super::{ComponentEntry, DataBase};
+use crate::{describe::Describe, system::Residue};
+
+use std::{fmt, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The origin of a `DataBase`.
+pub enum Scope {
+    /// A shared database, eg. for a lab or group.
+    Shared,
+    /// The default database of the user.
+    User,
+    /// A database in the project (working) directory.
+    Project,
+    /// A database which was explicitly given by the user.
+    File,
+    /// The built-in library of definitions.
+    Library,
+}
+
+impl Default for Scope {
+    fn default() -> Scope {
+        Scope::User
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Scope::Shared => write!(f, "shared"),
+            Scope::User => write!(f, "user"),
+            Scope::Project => write!(f, "project"),
+            Scope::File => write!(f, "file"),
+            Scope::Library => write!(f, "library"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A definition along with the scope and path of the database it is defined in.
+pub struct ScopedDefinition<'a, T: 'a> {
+    pub scope: Scope,
+    /// Path to the database, which relative paths in the definition are relative to.
+    pub path: Option<&'a PathBuf>,
+    pub definition: &'a T,
+}
+
+impl<'a, T: Describe> Describe for ScopedDefinition<'a, T> {
+    fn describe(&self) -> String {
+        format!("{} [{}]", self.definition.describe(), self.scope)
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} [{}]", self.definition.describe_short(), self.scope)
+    }
+}
+
+impl DataBase {
+    /// Add a read-only database as a layer below the current layers.
+    pub fn add_layer(&mut self, layer: DataBase) {
+        self.layers.push(layer);
+    }
+
+    /// Iterate over the writable database and the read-only layers, from the top.
+    fn iter_layers<'a>(&'a self) -> impl Iterator<Item = &'a DataBase> {
+        Some(self).into_iter().chain(self.layers.iter())
+    }
+
+    /// Get the residue definitions of all layers.
+    ///
+    /// Residues with the same code as one in a higher layer are shadowed.
+    pub fn residues(&self) -> Vec<ScopedDefinition<Residue>> {
+        collect_definitions(
+            self,
+            |layer| &layer.residue_defs,
+            |residue| Some(residue.code.clone()),
+        )
+    }
+
+    /// Get the component definitions of all layers.
+    ///
+    /// Components with the same name as one in a higher layer are shadowed.
+    pub fn components(&self) -> Vec<ScopedDefinition<ComponentEntry>> {
+        collect_definitions(
+            self,
+            |layer| &layer.component_defs,
+            |component| component.name(),
+        )
+    }
+}
+
+fn collect_definitions<'a, T, F, G>(
+    database: &'a DataBase,
+    get_list: F,
+    get_name: G,
+) -> Vec<ScopedDefinition<'a, T>>
+where
+    F: Fn(&'a DataBase) -> &'a Vec<T>,
+    G: Fn(&T) -> Option<String>,
+{
+    let mut names: Vec<String> = Vec::new();
+    let mut definitions = Vec::new();
+
+    for layer in database.iter_layers() {
+        let mut layer_names = Vec::new();
+
+        for definition in get_list(layer) {
+            let name = get_name(definition);
+
+            if let Some(ref name) = name {
+                if names.contains(name) {
+                    continue;
+                }
+            }
+
+            layer_names.extend(name);
+            definitions.push(ScopedDefinition {
+                scope: layer.scope,
+                path: layer.path.as_ref(),
+                definition,
+            });
+        }
+
+        // Names are only shadowed by higher layers, not within the same layer
+        names.append(&mut layer_names);
+    }
+
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coord::Coord, system::Atom, volume::Cuboid};
+
+    fn named_cuboid(name: &str) -> ComponentEntry {
+        ComponentEntry::from(Cuboid {
+            name: Some(name.to_string()),
+            ..Cuboid::default()
+        })
+    }
+
+    fn database(scope: Scope, residues: Vec<Residue>, components: Vec<ComponentEntry>) -> DataBase {
+        DataBase {
+            scope,
+            residue_defs: residues,
+            component_defs: components,
+            ..DataBase::new()
+        }
+    }
+
+    #[test]
+    fn definitions_in_higher_layers_shadow_lower_layers() {
+        let mut top = database(
+            Scope::Project,
+            vec![resbase!["SOL", ("OW", 0.0, 0.0, 0.0)]],
+            vec![named_cuboid("box")],
+        );
+
+        top.add_layer(database(
+            Scope::User,
+            vec![
+                resbase!["SOL", ("OW", 1.0, 0.0, 0.0)],
+                resbase!["NA", ("NA", 0.0, 0.0, 0.0)],
+            ],
+            vec![],
+        ));
+
+        top.add_layer(database(
+            Scope::Shared,
+            vec![resbase!["NA", ("NA", 1.0, 0.0, 0.0)]],
+            vec![named_cuboid("box"), named_cuboid("water")],
+        ));
+
+        let residues = top.residues();
+        assert_eq!(residues.len(), 2);
+        assert_eq!(residues[0].scope, Scope::Project);
+        assert_eq!(residues[0].definition.atoms[0].position, Coord::ORIGO);
+        assert_eq!(residues[1].scope, Scope::User);
+        assert_eq!(residues[1].definition.code, "NA");
+
+        let components = top.components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].scope, Scope::Project);
+        assert_eq!(components[1].scope, Scope::Shared);
+        assert_eq!(components[1].definition.name(), Some("water".to_string()));
+    }
+
+    #[test]
+    fn definitions_with_the_same_name_in_one_layer_are_kept() {
+        let top = database(
+            Scope::User,
+            vec![
+                resbase!["SOL", ("OW", 0.0, 0.0, 0.0)],
+                resbase!["SOL", ("OW", 1.0, 0.0, 0.0)],
+            ],
+            vec![],
+        );
+
+        assert_eq!(top.residues().len(), 2);
+    }
+
+    #[test]
+    fn scoped_definitions_describe_their_scope() {
+        let residue = resbase!["SOL", ("OW", 0.0, 0.0, 0.0)];
+        let scoped = ScopedDefinition {
+            scope: Scope::Shared,
+            path: None,
+            definition: &residue,
+        };
+
+        assert_eq!(scoped.describe_short(), "SOL [shared]");
+    }
+}
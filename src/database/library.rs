@@ -0,0 +1,68 @@
+//! A built-in library of common residue and component definitions.
+//!
+//! The library is compiled into the binary from `assets/library.json`. It contains
+//! graphene, silica and gold (111) surfaces, SPC/E and TIP4P water and sodium and
+//! chloride ions.
+
+use super::{DataBase, Scope};
+
+const LIBRARY: &str = include_str!("../../assets/library.json");
+
+/// Get the read-only library of common definitions.
+pub fn library() -> DataBase {
+    let database = DataBase::from_reader(LIBRARY.as_bytes())
+        .expect("the built-in library is not a valid database");
+
+    DataBase {
+        scope: Scope::Library,
+        ..database
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::ComponentEntry, volume::FillType};
+
+    #[test]
+    fn library_is_read_and_has_no_problems() {
+        let library = library();
+
+        assert_eq!(library.scope, Scope::Library);
+        assert!(!library.residue_defs.is_empty());
+        assert!(!library.component_defs.is_empty());
+        assert_eq!(library.validate(), vec![]);
+    }
+
+    #[test]
+    fn library_water_models_are_neutral_and_can_be_filled_by_mass_density() {
+        let library = library();
+
+        let water_models = library
+            .component_defs
+            .iter()
+            .filter_map(|component| match component {
+                &ComponentEntry::VolumeCuboid(ref cuboid) => Some(cuboid),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(water_models.len(), 2);
+
+        for water in water_models {
+            let residue = water.residue.as_ref().unwrap();
+
+            assert!(residue.charge().abs() < 1e-9);
+            assert!(water
+                .fill_type
+                .unwrap()
+                .number_density(Some(residue))
+                .is_some());
+
+            match water.fill_type {
+                Some(FillType::MassDensity(_)) => (),
+                _ => panic!("water should be filled by mass density"),
+            }
+        }
+    }
+}
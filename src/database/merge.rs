@@ -0,0 +1,451 @@
+//! Merge the definitions of one `DataBase` into another.
+
+use super::{ComponentEntry, DataBase};
+use crate::{describe::Describe, system::Residue};
+
+use serde::Serialize;
+use serde_json;
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Kinds of definitions in a `DataBase`.
+pub enum Definition {
+    Residue,
+    Component,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// An imported definition with the same name as an existing definition of the same kind,
+/// but with a different content.
+pub struct Conflict {
+    /// The kind of conflicting definition.
+    pub definition: Definition,
+    /// The name shared by the definitions.
+    pub name: String,
+    /// A name which is not used by any definition of the same kind.
+    pub suggested_name: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// How to resolve a `Conflict`.
+pub enum MergeAction {
+    /// Keep the existing definition and do not import the new.
+    Skip,
+    /// Replace the existing definition with the imported.
+    Overwrite,
+    /// Import the definition with a new name.
+    Rename(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Number of definitions for every outcome of a merge.
+pub struct MergeSummary {
+    /// Definitions which were added without conflicts.
+    pub added: usize,
+    /// Definitions which were identical to an existing definition and not added.
+    pub duplicates: usize,
+    /// Conflicting definitions which were not added.
+    pub skipped: usize,
+    /// Conflicting definitions which replaced an existing definition.
+    pub overwritten: usize,
+    /// Conflicting definitions which were added with a new name.
+    pub renamed: usize,
+    /// Indices of the component definitions in the target database which were added,
+    /// overwritten or renamed.
+    pub merged_components: Vec<usize>,
+    /// Configuration files which were created when importing a bundle.
+    pub copied_files: Vec<PathBuf>,
+}
+
+impl MergeSummary {
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Added => self.added += 1,
+            Outcome::Duplicate => self.duplicates += 1,
+            Outcome::Skipped => self.skipped += 1,
+            Outcome::Overwritten => self.overwritten += 1,
+            Outcome::Renamed => self.renamed += 1,
+        }
+    }
+}
+
+impl Describe for MergeSummary {
+    fn describe(&self) -> String {
+        format!(
+            "{} added, {} identical duplicates ignored, {} skipped, {} overwritten, {} renamed",
+            self.added, self.duplicates, self.skipped, self.overwritten, self.renamed
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+/// Merge the residue and component definitions of a `DataBase` into another.
+///
+/// Definitions which are identical to an existing definition are not added. If a definition
+/// has the same name as an existing definition of the same kind but a different content,
+/// the input function is called to decide how to resolve the conflict. If the definition
+/// is renamed to another name which is in use it is resolved again.
+///
+/// Relative paths of configuration files are relative to the location of the database
+/// they are defined in. They are rewritten to stay valid from the location of the
+/// target database, or made absolute if they are not inside of its directory.
+pub fn merge_database<F>(database: &mut DataBase, other: &DataBase, mut resolve: F) -> MergeSummary
+where
+    F: FnMut(&Conflict) -> MergeAction,
+{
+    let mut summary = MergeSummary::default();
+
+    for residue in &other.residue_defs {
+        let (outcome, _) = merge_definition(
+            &mut database.residue_defs,
+            residue.clone(),
+            Definition::Residue,
+            &mut resolve,
+        );
+
+        summary.add(outcome);
+    }
+
+    let source_dir = get_database_dir(other.path.as_ref());
+    let target_dir = get_database_dir(database.path.as_ref());
+
+    for component in &other.component_defs {
+        let component = rewrite_conf_path(component.clone(), &source_dir, &target_dir);
+
+        let (outcome, index) = merge_definition(
+            &mut database.component_defs,
+            component,
+            Definition::Component,
+            &mut resolve,
+        );
+
+        summary.add(outcome);
+        summary.merged_components.extend(index);
+    }
+
+    summary
+}
+
+/// Outcome of merging a single definition.
+enum Outcome {
+    Added,
+    Duplicate,
+    Skipped,
+    Overwritten,
+    Renamed,
+}
+
+/// Definitions which are identified by their name.
+trait Named {
+    fn get_name(&self) -> Option<String>;
+    fn set_new_name(&mut self, name: &str);
+}
+
+impl Named for Residue {
+    fn get_name(&self) -> Option<String> {
+        Some(self.code.clone())
+    }
+
+    fn set_new_name(&mut self, name: &str) {
+        self.code = name.to_string();
+    }
+}
+
+impl Named for ComponentEntry {
+    fn get_name(&self) -> Option<String> {
+        self.name()
+    }
+
+    fn set_new_name(&mut self, name: &str) {
+        self.set_name(name);
+    }
+}
+
+/// Merge a definition into a list and return the outcome, along with the index
+/// of the definition in the list if it was merged.
+fn merge_definition<T, F>(
+    list: &mut Vec<T>,
+    mut item: T,
+    definition: Definition,
+    resolve: &mut F,
+) -> (Outcome, Option<usize>)
+where
+    T: Named + Serialize,
+    F: FnMut(&Conflict) -> MergeAction,
+{
+    if list.iter().any(|existing| is_identical(existing, &item)) {
+        return (Outcome::Duplicate, None);
+    }
+
+    let mut outcome = Outcome::Added;
+
+    loop {
+        let conflict_index = item.get_name().and_then(|name| {
+            list.iter()
+                .position(|existing| existing.get_name().as_ref() == Some(&name))
+                .map(|index| (index, name))
+        });
+
+        let (index, name) = match conflict_index {
+            Some(value) => value,
+            None => {
+                list.push(item);
+                return (outcome, Some(list.len() - 1));
+            }
+        };
+
+        let conflict = Conflict {
+            definition,
+            suggested_name: unique_name(&name, &list),
+            name,
+        };
+
+        match resolve(&conflict) {
+            MergeAction::Skip => return (Outcome::Skipped, None),
+            MergeAction::Overwrite => {
+                list[index] = item;
+                return (Outcome::Overwritten, Some(index));
+            }
+            MergeAction::Rename(new_name) => {
+                item.set_new_name(&new_name);
+                outcome = Outcome::Renamed;
+            }
+        }
+    }
+}
+
+/// Definitions are compared by their serialized form, since that is what is stored.
+fn is_identical<T: Serialize>(first: &T, second: &T) -> bool {
+    match (serde_json::to_value(first), serde_json::to_value(second)) {
+        (Ok(value1), Ok(value2)) => value1 == value2,
+        _ => false,
+    }
+}
+
+/// Add the lowest numbered suffix to a name which makes it unique in the list.
+fn unique_name<T: Named>(name: &str, list: &[T]) -> String {
+    let names = list
+        .iter()
+        .filter_map(|item| item.get_name())
+        .collect::<Vec<_>>();
+
+    super::unique_name(name, &names)
+}
+
+/// Get the absolute directory of a database, or the current directory if it has no path.
+pub(super) fn get_database_dir(path: Option<&PathBuf>) -> PathBuf {
+    let dir = path
+        .and_then(|path| path.parent())
+        .map(|dir| PathBuf::from(dir))
+        .unwrap_or(PathBuf::new());
+
+    if dir.is_absolute() {
+        dir
+    } else {
+        current_dir().unwrap_or(PathBuf::new()).join(dir)
+    }
+}
+
+/// Rewrite the relative path of a configuration file from the source directory
+/// to the target directory, or make it absolute if it is not inside of it.
+pub(super) fn rewrite_conf_path(
+    mut component: ComponentEntry,
+    source_dir: &Path,
+    target_dir: &Path,
+) -> ComponentEntry {
+    if let ComponentEntry::ConfigurationFile(ref mut conf) = component {
+        if conf.path.is_relative() {
+            let full_path = source_dir.join(&conf.path);
+
+            conf.path = match full_path.strip_prefix(target_dir) {
+                Ok(relative_path) => relative_path.to_path_buf(),
+                Err(_) => full_path.clone(),
+            };
+        }
+    }
+
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coord::Coord,
+        read_conf::{ConfType, ReadConf},
+        system::Atom,
+        volume::{Cuboid, FillType},
+    };
+
+    fn named_cuboid(name: &str, density: f64) -> ComponentEntry {
+        ComponentEntry::from(Cuboid {
+            name: Some(name.to_string()),
+            fill_type: Some(FillType::Density(density)),
+            ..Cuboid::default()
+        })
+    }
+
+    fn conf_entry(path: &str) -> ComponentEntry {
+        ComponentEntry::ConfigurationFile(ReadConf {
+            conf: None,
+            backup_conf: None,
+            path: PathBuf::from(path),
+            description: "conf".to_string(),
+            volume_type: ConfType::Spheroid {
+                origin: Coord::ORIGO,
+                radius: 1.0,
+            },
+        })
+    }
+
+    fn database_with(residues: Vec<Residue>, components: Vec<ComponentEntry>) -> DataBase {
+        DataBase {
+            residue_defs: residues,
+            component_defs: components,
+            ..DataBase::new()
+        }
+    }
+
+    #[test]
+    fn merging_adds_new_and_ignores_identical_definitions() {
+        let mut database = database_with(
+            vec![resbase!["SOL", ("OW", 0.0, 0.0, 0.0)]],
+            vec![named_cuboid("box", 1.0)],
+        );
+
+        let other = database_with(
+            vec![
+                resbase!["SOL", ("OW", 0.0, 0.0, 0.0)],
+                resbase!["NA", ("NA", 0.0, 0.0, 0.0)],
+            ],
+            vec![named_cuboid("box", 1.0), named_cuboid("large box", 5.0)],
+        );
+
+        let summary = merge_database(&mut database, &other, |_| {
+            panic!("there should be no conflicts")
+        });
+
+        assert_eq!(summary.added, 2);
+        assert_eq!(summary.duplicates, 2);
+        assert_eq!(summary.merged_components, vec![1]);
+        assert_eq!(database.residue_defs.len(), 2);
+        assert_eq!(database.component_defs.len(), 2);
+        assert_eq!(database.residue_defs[1].code, "NA");
+        assert_eq!(
+            database.component_defs[1].name(),
+            Some("large box".to_string())
+        );
+    }
+
+    #[test]
+    fn conflicts_are_resolved_by_skipping_overwriting_or_renaming() {
+        let existing = database_with(
+            vec![resbase!["SOL", ("OW", 0.0, 0.0, 0.0)]],
+            vec![named_cuboid("box", 1.0)],
+        );
+
+        let other = database_with(
+            vec![resbase!["SOL", ("OW", 1.0, 0.0, 0.0)]],
+            vec![named_cuboid("box", 2.0)],
+        );
+
+        let mut database = existing.clone();
+        let summary = merge_database(&mut database, &other, |_| MergeAction::Skip);
+        assert_eq!(summary.skipped, 2);
+        assert!(summary.merged_components.is_empty());
+        assert_eq!(database.residue_defs, existing.residue_defs);
+
+        let mut database = existing.clone();
+        let summary = merge_database(&mut database, &other, |_| MergeAction::Overwrite);
+        assert_eq!(summary.overwritten, 2);
+        assert_eq!(summary.merged_components, vec![0]);
+        assert_eq!(database.residue_defs, other.residue_defs);
+        assert_eq!(database.component_defs.len(), 1);
+
+        let mut conflicts = Vec::new();
+        let mut database = existing.clone();
+        let summary = merge_database(&mut database, &other, |conflict| {
+            conflicts.push(conflict.clone());
+            MergeAction::Rename(conflict.suggested_name.clone())
+        });
+
+        assert_eq!(summary.renamed, 2);
+        assert_eq!(conflicts[0].definition, Definition::Residue);
+        assert_eq!(conflicts[1].definition, Definition::Component);
+        assert_eq!(database.residue_defs[1].code, "SOL-2");
+        assert_eq!(database.component_defs[1].name(), Some("box-2".to_string()));
+    }
+
+    #[test]
+    fn renaming_to_a_used_name_is_resolved_again() {
+        let mut database = database_with(
+            vec![
+                resbase!["SOL", ("OW", 0.0, 0.0, 0.0)],
+                resbase!["WAT", ("OW", 0.0, 0.0, 0.0)],
+            ],
+            vec![],
+        );
+
+        let other = database_with(vec![resbase!["SOL", ("OW", 1.0, 0.0, 0.0)]], vec![]);
+
+        let mut names = vec!["WAT", "HOH"].into_iter();
+        let summary = merge_database(&mut database, &other, |_| {
+            MergeAction::Rename(names.next().unwrap().to_string())
+        });
+
+        assert_eq!(summary.renamed, 1);
+        assert_eq!(database.residue_defs[2].code, "HOH");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relative_configuration_paths_are_rewritten_to_the_target_database() {
+        let mut database = DataBase::new();
+        database.path = Some(PathBuf::from("/data/db/database.json"));
+
+        let mut other = database_with(
+            vec![],
+            vec![conf_entry("confs/inside.gro"), conf_entry("/abs/conf.gro")],
+        );
+        other.path = Some(PathBuf::from("/data/db/team/database.json"));
+
+        merge_database(&mut database, &other, |_| {
+            MergeAction::Rename("x".to_string())
+        });
+
+        let paths = database
+            .component_defs
+            .iter()
+            .map(|component| match component {
+                &ComponentEntry::ConfigurationFile(ref conf) => conf.path.clone(),
+                _ => panic!("only configurations were added"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(paths[0], PathBuf::from("team/confs/inside.gro"));
+        assert_eq!(paths[1], PathBuf::from("/abs/conf.gro"));
+
+        let mut database = DataBase::new();
+        database.path = Some(PathBuf::from("/elsewhere/database.json"));
+
+        let other = DataBase {
+            path: Some(PathBuf::from("/data/db/database.json")),
+            ..database_with(vec![], vec![conf_entry("conf.gro")])
+        };
+
+        merge_database(&mut database, &other, |_| MergeAction::Skip);
+
+        match database.component_defs[0] {
+            ComponentEntry::ConfigurationFile(ref conf) => {
+                assert_eq!(conf.path, PathBuf::from("/data/db/conf.gro"));
+            }
+            _ => panic!("only configurations were added"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Upgrade `DataBase` files written with older layouts to the current.
+//!
+//! The layout version is stored in the file under the `version` key. Files without
+//! it were written before the layout was versioned and have version 0. Every
+//! migration upgrades the layout by one version.
+
+use crate::error::{GrafenError, Result};
+
+use serde_json::{Map, Value};
+
+/// Version of the `DataBase` layout written by this version of the library.
+pub const DATABASE_VERSION: u64 = 1;
+
+/// Key of the layout version in a `DataBase` file.
+pub const VERSION_KEY: &str = "version";
+
+/// Migrations from every version to the next, in order.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v0_to_v1];
+
+/// Upgrade a `DataBase` object to the current layout and return its original version.
+///
+/// # Errors
+/// Returns an error if the object is not a `DataBase` or if it was written with
+/// a newer layout than is supported.
+pub fn migrate(value: &mut Value) -> Result<u64> {
+    let database = value
+        .as_object_mut()
+        .ok_or_else(|| invalid_data("the file does not contain a database object".to_string()))?;
+
+    let version = match database.get(VERSION_KEY) {
+        None => 0,
+        Some(version) => version.as_u64().ok_or_else(|| {
+            invalid_data(format!("the database version ({}) is invalid", version))
+        })?,
+    };
+
+    if version > DATABASE_VERSION {
+        return Err(invalid_data(format!(
+            "the database has version {} but this version of {} only supports versions up to {}. \
+             Update {} to read it.",
+            version,
+            env!("CARGO_PKG_NAME"),
+            DATABASE_VERSION,
+            env!("CARGO_PKG_NAME")
+        )));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(database);
+    }
+
+    database.insert(VERSION_KEY.to_string(), Value::from(DATABASE_VERSION));
+
+    Ok(version)
+}
+
+fn invalid_data(message: String) -> GrafenError {
+    GrafenError::DataBaseError {
+        path: None,
+        entry: None,
+        message,
+    }
+}
+
+/// Get the mutable component definitions of a `DataBase` object, if any.
+fn component_definitions<'a>(
+    database: &'a mut Map<String, Value>,
+) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+    database
+        .get_mut("component_definitions")
+        .and_then(|defs| defs.as_array_mut())
+        .into_iter()
+        .flat_map(|defs| defs.iter_mut())
+        .filter_map(|def| def.as_object_mut())
+}
+
+/// Version 1 replaced the number `density` of volumes with a `fill_type`.
+fn migrate_v0_to_v1(database: &mut Map<String, Value>) {
+    for definition in component_definitions(database) {
+        for (variant, object) in definition.iter_mut() {
+            let object = match object.as_object_mut() {
+                Some(object) if variant.starts_with("Volume") => object,
+                _ => continue,
+            };
+
+            if let Some(density) = object.remove("density") {
+                if !density.is_null() {
+                    let mut fill_type = Map::new();
+                    fill_type.insert("Density".to_string(), density);
+                    object.insert("fill_type".to_string(), Value::Object(fill_type));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_database_is_migrated_to_the_current_version() {
+        let mut value = json!({
+            "residue_definitions": [],
+            "component_definitions": [
+                { "VolumeSpheroid": { "name": "ball", "density": 10.0 } },
+                { "VolumeCuboid": { "name": "box", "density": null } },
+                { "SurfaceSheet": { "name": "sheet", "density": 1.0 } }
+            ]
+        });
+
+        assert_eq!(migrate(&mut value).unwrap(), 0);
+
+        assert_eq!(value[VERSION_KEY], json!(DATABASE_VERSION));
+        assert_eq!(
+            value["component_definitions"][0],
+            json!({ "VolumeSpheroid": { "name": "ball", "fill_type": { "Density": 10.0 } } })
+        );
+        assert_eq!(
+            value["component_definitions"][1],
+            json!({ "VolumeCuboid": { "name": "box" } })
+        );
+        assert_eq!(
+            value["component_definitions"][2],
+            json!({ "SurfaceSheet": { "name": "sheet", "density": 1.0 } })
+        );
+    }
+
+    #[test]
+    fn current_database_is_not_changed() {
+        let mut value = json!({
+            "version": DATABASE_VERSION,
+            "component_definitions": [
+                { "VolumeSpheroid": { "name": "ball", "density": 10.0 } }
+            ]
+        });
+        let original = value.clone();
+
+        assert_eq!(migrate(&mut value).unwrap(), DATABASE_VERSION);
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn newer_or_invalid_database_versions_are_errors() {
+        let mut value = json!({ "version": DATABASE_VERSION + 1 });
+        let err = migrate(&mut value).unwrap_err();
+        assert!(err.to_string().contains("Update"));
+
+        assert!(migrate(&mut json!({ "version": "one" })).is_err());
+        assert!(migrate(&mut json!([])).is_err());
+    }
+}
@@ -0,0 +1,1181 @@
+//! Collect definitions for `Residue` and `SheetConf` objects
+//! into a `DataBase` which can be read from or saved to disk.
+
+mod bundle;
+mod layers;
+mod library;
+mod merge;
+mod migrate;
+mod validate;
+
+use crate::{
+    coord::{Coord, Direction, Translate},
+    describe::{describe_list, describe_list_short, Describe},
+    error::GrafenError,
+    iterator::{ResidueIter, ResidueIterOut},
+    read_conf, surface,
+    system::{Component, Residue},
+    volume::{self, Contains},
+};
+
+pub use self::bundle::{export_bundle, import_bundle, read_bundle, BUNDLE_CONFS, BUNDLE_DATABASE};
+pub use self::layers::{Scope, ScopedDefinition};
+pub use self::library::library;
+pub use self::merge::{merge_database, Conflict, Definition, MergeAction, MergeSummary};
+pub use self::migrate::DATABASE_VERSION;
+pub use self::validate::{Diagnostic, Severity};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json;
+use std::{
+    convert::From,
+    ffi::OsStr,
+    fmt::Write,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    result,
+};
+
+#[derive(Copy, Clone, Debug)]
+pub enum DataBaseError {
+    BadPath,
+}
+
+#[macro_export]
+/// Macro to wrap every object constructor into an enum with desired traits.
+///
+/// The traits are those important for the creation and display of system components.
+/// The enum is used to hold created objects of different types in one container,
+/// sharing one interface.
+///
+/// Implements `Describe`, `Component` and `Translate` for the enum.
+///
+/// # Requires
+/// Wrapped objects have to implement the above traits and `Clone`, `Debug`,
+/// `Deserialize` and `Serialize` (the last two from `serde`).
+///
+/// # Examples
+/// Create two objects and let the macro create the wrapper and implement the traits for it.
+///
+/// ```
+/// # #[macro_use] extern crate grafen;
+/// # extern crate serde_json;
+/// # #[macro_use] extern crate serde_derive;
+/// # use grafen::coord::{Coord, Translate};
+/// # use grafen::describe::Describe;
+/// # use grafen::iterator::{ResidueIter, ResidueIterOut};
+/// # use grafen::system::{Component, Residue};
+/// #
+/// #[derive(Clone, Debug, Deserialize, Serialize)]
+/// pub struct StructOne {
+///     origin: Coord,
+///     residue: Option<Residue>,
+///     coords: Vec<Coord>
+/// }
+///
+/// #[derive(Clone, Debug, Deserialize, Serialize)]
+/// pub struct StructTwo {
+///     origin: Coord,
+///     residue: Option<Residue>,
+///     coords: Vec<Coord>
+/// }
+///
+/// // Not shown: implement required traits
+/// # impl StructOne { fn calc_box_size(&self) -> Coord { Coord::default() } }
+/// # impl StructTwo { fn calc_box_size(&self) -> Coord { Coord::default() } }
+/// # impl Describe for StructOne {
+/// #     fn describe(&self) -> String { "StructOne".to_string() }
+/// #     fn describe_short(&self) -> String { self.describe() }
+/// # }
+/// # impl Describe for StructTwo {
+/// #     fn describe(&self) -> String { "StructTwo".to_string() }
+/// #     fn describe_short(&self) -> String { self.describe() }
+/// # }
+/// # impl_component![StructOne, StructTwo];
+/// # impl_translate![StructOne, StructTwo];
+///
+/// // Construct the wrapping enum container
+/// create_entry_wrapper![
+///     Wrapper, // enum identifier
+///     (StructOne => One), // Wrapper::One(StructOne)
+///     (StructTwo => Two)  // Wrapper::Two(StructTwo)
+/// ];
+///
+/// #
+/// # fn main() {
+/// let objects = vec![
+///     Wrapper::One(StructOne {
+///         origin: Coord::default(),
+///         residue: None,
+///         coords: vec![]
+///     }),
+///     Wrapper::Two(StructTwo {
+///         origin: Coord::default(),
+///         residue: None,
+///         coords: vec![]
+///     })
+/// ];
+///
+/// assert_eq!("StructOne", &objects[0].describe());
+/// assert!(objects[0].iter_residues().next().is_none());
+///
+/// assert_eq!("StructTwo", &objects[1].describe());
+/// assert!(objects[1].iter_residues().next().is_none());
+/// # }
+/// ```
+macro_rules! create_entry_wrapper {
+    (
+        $name:ident, // enum Identifier
+        $( ($class:path => $entry:ident) ),+ // Identifier::Entry(Class)
+    ) => {
+        #[derive(Clone, Debug, Deserialize, Serialize)]
+        /// Wrapper for accessing a shared interface from different components constructors.
+        pub enum $name {
+            $(
+                $entry($class),
+            )*
+        }
+
+        impl Describe for $name {
+            fn describe(&self) -> String {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.describe(),
+                    )*
+                }
+            }
+
+            fn describe_short(&self) -> String {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.describe_short(),
+                    )*
+                }
+            }
+        }
+
+        impl<'a> Component<'a> for $name {
+            fn assign_residues(&mut self, residues: &[ResidueIterOut]) {
+                match *self {
+                    $(
+                        $name::$entry(ref mut object) => object.assign_residues(residues),
+                    )*
+                }
+            }
+
+            fn box_size(&self) -> Coord {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.box_size(),
+                    )*
+                }
+            }
+
+            fn get_origin(&self) -> Coord {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.get_origin(),
+                    )*
+                }
+            }
+
+            fn iter_residues(&self) -> ResidueIter {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.iter_residues(),
+                    )*
+                }
+            }
+
+
+            fn num_atoms(&self) -> u64 {
+                match *self {
+                    $(
+                        $name::$entry(ref object) => object.num_atoms(),
+                    )*
+                }
+            }
+
+            fn with_pbc(self) -> Self {
+                match self {
+                    $(
+                        $name::$entry(object) => $name::$entry(object.with_pbc()),
+                    )*
+                }
+            }
+        }
+
+        impl Translate for $name {
+            fn translate(self, coord: Coord) -> Self {
+                match self {
+                    $(
+                        $name::$entry(object) => $name::$entry(object.translate(coord)),
+                    )*
+                }
+            }
+
+            fn translate_in_place(&mut self, coord: Coord) {
+                match *self {
+                    $(
+                        $name::$entry(ref mut object)
+                            => { object.translate_in_place(coord); }
+                    )*
+                }
+            }
+        }
+
+        $(
+            impl From<$class> for $name {
+                fn from(object: $class) -> $name {
+                    $name::$entry(object)
+                }
+            }
+        )*
+    }
+}
+
+// Our wrapper for object constructors is `ComponentEntry`. Use the macro to construct it.
+create_entry_wrapper![
+    ComponentEntry,
+    (volume::Cuboid => VolumeCuboid),
+    (volume::Cylinder => VolumeCylinder),
+    (volume::Spheroid => VolumeSpheroid),
+    (volume::Ellipsoid => VolumeEllipsoid),
+    (volume::Cone => VolumeCone),
+    (volume::Torus => VolumeTorus),
+    (volume::Capsule => VolumeCapsule),
+    (volume::Slab => VolumeSlab),
+    (surface::Sheet => SurfaceSheet),
+    (surface::Cuboid => SurfaceCuboid),
+    (surface::Cylinder => SurfaceCylinder),
+    (surface::Sphere => SurfaceSphere),
+    (surface::Ellipsoid => SurfaceEllipsoid),
+    (surface::Bilayer => SurfaceBilayer),
+    (read_conf::ReadConf => ConfigurationFile)
+];
+
+impl ComponentEntry {
+    /// Get the name of the component definition.
+    ///
+    /// Configurations read from files are named by their description.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            &ComponentEntry::VolumeCuboid(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeCylinder(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeSpheroid(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeEllipsoid(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeCone(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeTorus(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeCapsule(ref object) => object.name.clone(),
+            &ComponentEntry::VolumeSlab(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceSheet(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceCuboid(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceCylinder(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceSphere(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceEllipsoid(ref object) => object.name.clone(),
+            &ComponentEntry::SurfaceBilayer(ref object) => object.name.clone(),
+            &ComponentEntry::ConfigurationFile(ref object) => Some(object.description.clone()),
+        }
+    }
+
+    /// Set the name of the component definition.
+    pub fn set_name(&mut self, name: &str) {
+        match self {
+            &mut ComponentEntry::VolumeCuboid(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeCylinder(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeSpheroid(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeEllipsoid(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeCone(ref mut object) => object.name = Some(name.to_string()),
+            &mut ComponentEntry::VolumeTorus(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeCapsule(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::VolumeSlab(ref mut object) => object.name = Some(name.to_string()),
+            &mut ComponentEntry::SurfaceSheet(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::SurfaceCuboid(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::SurfaceCylinder(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::SurfaceSphere(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::SurfaceEllipsoid(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::SurfaceBilayer(ref mut object) => {
+                object.name = Some(name.to_string())
+            }
+            &mut ComponentEntry::ConfigurationFile(ref mut object) => {
+                object.description = name.to_string()
+            }
+        }
+    }
+}
+
+impl ComponentEntry {
+    /// Return whether the component has a volume, ie. if `to_volume` returns one.
+    pub fn has_volume(&self) -> bool {
+        match self {
+            ComponentEntry::SurfaceSheet(_)
+            | ComponentEntry::SurfaceCuboid(_)
+            | ComponentEntry::ConfigurationFile(_) => false,
+            _ => true,
+        }
+    }
+
+    /// Get the space of a volume component with a margin added around it,
+    /// eg. to prune residues which overlap it.
+    ///
+    /// Cylindrical, spherical and ellipsoidal surfaces enclose the space of the
+    /// corresponding volume. Bilayers enclose the cuboid which is spanned by the atoms
+    /// of their lipids along the normal, which includes atoms beyond the head groups.
+    /// The volume does not contain the coordinates of the component. Returns `None`
+    /// if the component has no volume.
+    pub fn to_volume(&self, margin: f64) -> Option<Box<dyn Contains>> {
+        let coord_margins = Coord::new(margin, margin, margin);
+
+        let volume: Box<dyn Contains> = match self {
+            ComponentEntry::VolumeCuboid(obj) => Box::new(volume::Cuboid {
+                name: obj.name.clone(),
+                residue: None,
+                origin: obj.origin - coord_margins,
+                size: obj.size + coord_margins * 2.0,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeCylinder(obj) => Box::new(cylinder_with_margin(
+                &obj.name,
+                obj.alignment,
+                obj.origin,
+                obj.radius,
+                obj.height,
+                margin,
+            )),
+            ComponentEntry::VolumeSpheroid(obj) => Box::new(volume::Spheroid {
+                name: obj.name.clone(),
+                residue: None,
+                origin: obj.origin,
+                radius: obj.radius + margin,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeEllipsoid(obj) => Box::new(volume::Ellipsoid {
+                name: obj.name.clone(),
+                residue: None,
+                origin: obj.origin,
+                semi_axes: obj.semi_axes + coord_margins,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeCone(obj) => Box::new(volume::Cone {
+                name: obj.name.clone(),
+                residue: None,
+                alignment: obj.alignment,
+                origin: obj.origin + get_axial_margin(margin, obj.alignment),
+                bottom_radius: obj.bottom_radius + margin,
+                top_radius: obj.top_radius + margin,
+                height: obj.height + 2.0 * margin,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeTorus(obj) => Box::new(volume::Torus {
+                name: obj.name.clone(),
+                residue: None,
+                alignment: obj.alignment,
+                origin: obj.origin,
+                major_radius: obj.major_radius,
+                minor_radius: obj.minor_radius + margin,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeCapsule(obj) => Box::new(volume::Capsule {
+                name: obj.name.clone(),
+                residue: None,
+                alignment: obj.alignment,
+                origin: obj.origin,
+                radius: obj.radius + margin,
+                height: obj.height,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::VolumeSlab(obj) => Box::new(volume::Slab {
+                name: obj.name.clone(),
+                residue: None,
+                normal: obj.normal,
+                origin: obj.origin + get_axial_margin(margin, obj.normal),
+                thickness: obj.thickness + 2.0 * margin,
+                length: obj.length,
+                width: obj.width,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::SurfaceCylinder(obj) => Box::new(cylinder_with_margin(
+                &obj.name,
+                obj.alignment,
+                obj.origin,
+                obj.radius,
+                obj.height,
+                margin,
+            )),
+            ComponentEntry::SurfaceSphere(obj) => Box::new(volume::Spheroid {
+                name: obj.name.clone(),
+                residue: None,
+                origin: obj.origin,
+                radius: obj.radius + margin,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::SurfaceEllipsoid(obj) => Box::new(volume::Ellipsoid {
+                name: obj.name.clone(),
+                residue: None,
+                origin: obj.origin,
+                semi_axes: obj.semi_axes + coord_margins,
+                fill_type: None,
+                coords: vec![],
+            }),
+            ComponentEntry::SurfaceBilayer(obj) => {
+                let (z0, z1) = obj.normal_extent();
+
+                Box::new(volume::Cuboid {
+                    name: obj.name.clone(),
+                    residue: None,
+                    origin: obj.origin + Coord::new(0.0, 0.0, z0) - coord_margins,
+                    size: Coord::new(obj.length, obj.width, z1 - z0) + coord_margins * 2.0,
+                    fill_type: None,
+                    coords: vec![],
+                })
+            }
+            ComponentEntry::SurfaceSheet(_)
+            | ComponentEntry::SurfaceCuboid(_)
+            | ComponentEntry::ConfigurationFile(_) => return None,
+        };
+
+        Some(volume)
+    }
+}
+
+/// Return a cylinder volume with a margin added to its radius and both ends.
+fn cylinder_with_margin(
+    name: &Option<String>,
+    alignment: Direction,
+    origin: Coord,
+    radius: f64,
+    height: f64,
+    margin: f64,
+) -> volume::Cylinder {
+    volume::Cylinder {
+        name: name.clone(),
+        residue: None,
+        alignment,
+        origin: origin + get_axial_margin(margin, alignment),
+        radius: radius + margin,
+        height: height + 2.0 * margin,
+        fill_type: None,
+        coords: vec![],
+    }
+}
+
+/// Return the translation which moves a volume down along an axis by a margin.
+fn get_axial_margin(margin: f64, alignment: Direction) -> Coord {
+    match alignment {
+        Direction::X => Coord::new(-margin, 0.0, 0.0),
+        Direction::Y => Coord::new(0.0, -margin, 0.0),
+        Direction::Z => Coord::new(0.0, 0.0, -margin),
+    }
+}
+
+/// Add the lowest numbered suffix to a name which makes it unique among a list of names,
+/// eg. to name a copy of a definition.
+///
+/// # Examples
+/// ```
+/// # use grafen::database::unique_name;
+/// let names = vec!["Water".to_string(), "Water-2".to_string()];
+/// assert_eq!(unique_name("Water", &names), "Water-3");
+/// ```
+pub fn unique_name(name: &str, names: &[String]) -> String {
+    (2..)
+        .map(|i| format!("{}-{}", name, i))
+        .find(|candidate| !names.contains(candidate))
+        .unwrap()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A collection of residues and substrate configurations
+/// which can be saved to and read from disk.
+pub struct DataBase {
+    #[serde(skip)]
+    /// A path to the `DataBase` location on the hard drive.
+    pub path: Option<PathBuf>,
+
+    #[serde(skip)]
+    /// The scope which the `DataBase` was read from.
+    pub scope: Scope,
+
+    #[serde(rename = "residue_definitions", default = "Vec::new")]
+    /// Definitions of `Residue` objects.
+    pub residue_defs: Vec<Residue>,
+
+    #[serde(rename = "component_definitions", default = "Vec::new")]
+    /// New component constructors.
+    pub component_defs: Vec<ComponentEntry>,
+
+    #[serde(skip)]
+    /// Read-only databases below this, from the highest to the lowest priority.
+    pub layers: Vec<DataBase>,
+}
+
+impl DataBase {
+    /// Construct an empty `DataBase`.
+    pub fn new() -> DataBase {
+        DataBase {
+            path: None,
+            scope: Scope::default(),
+            residue_defs: vec![],
+            component_defs: vec![],
+            layers: vec![],
+        }
+    }
+
+    /// Get the database path enclosed in single quotes if it exists,
+    /// otherwise the unenclosed string "None".
+    pub fn get_path_pretty(&self) -> String {
+        self.path
+            .as_ref()
+            .map(|path| format!("'{}'", path.display()))
+            .unwrap_or("None".to_string())
+    }
+
+    /// Set a new path for the `DataBase`. The input path is asserted to
+    /// be a file and the extension is set to 'json'.
+    pub fn set_path<T>(&mut self, new_path: &T) -> Result<(), DataBaseError>
+    where
+        T: ?Sized + AsRef<OsStr>,
+    {
+        let mut path = PathBuf::from(new_path);
+
+        if path.file_stem().is_some() {
+            path.set_extension("json");
+            self.path = Some(path);
+            Ok(())
+        } else {
+            Err(DataBaseError::BadPath)
+        }
+    }
+
+    /// Parse a reader for a JSON formatted `DataBase`.
+    ///
+    /// This and the `to_writer` method are defined to enable a unit
+    /// test which ensures that the behaviour for reading and writing
+    /// a `DataBase` is consistent.
+    fn from_reader<R: io::Read>(reader: R) -> Result<DataBase, GrafenError> {
+        let mut value: serde_json::Value =
+            serde_json::from_reader(reader).map_err(|err| GrafenError::ParseError {
+                path: None,
+                line: Some(err.line()).filter(|&line| line > 0),
+                message: err.to_string(),
+            })?;
+
+        migrate::migrate(&mut value)?;
+
+        serde_json::from_value(value.clone()).map_err(|err| describe_parse_error(&value, err))
+    }
+
+    /// Write a `DataBase` as a JSON formatted object to an input writer.
+    ///
+    /// The current layout version is written along with the definitions.
+    fn to_writer<W: io::Write>(&self, writer: &mut W) -> result::Result<(), io::Error> {
+        let versioned = VersionedDataBase {
+            version: DATABASE_VERSION,
+            database: self,
+        };
+
+        serde_json::to_writer_pretty(writer, &versioned).map_err(|e| io::Error::from(e))
+    }
+}
+
+#[derive(Serialize)]
+struct VersionedDataBase<'a> {
+    version: u64,
+    #[serde(flatten)]
+    database: &'a DataBase,
+}
+
+/// Find the definition which could not be parsed and return an error for it.
+///
+/// Definitions are parsed one by one since errors from parsing a `serde_json::Value`
+/// do not contain the location of the error. The definition is named by its residue
+/// code or component name if it has one, otherwise by its kind and number.
+fn describe_parse_error(value: &serde_json::Value, err: serde_json::Error) -> GrafenError {
+    fn find_error<T>(value: &serde_json::Value, key: &str, kind: &str) -> Option<GrafenError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        value[key].as_array().and_then(|definitions| {
+            definitions.iter().enumerate().find_map(|(i, definition)| {
+                serde_json::from_value::<T>(definition.clone())
+                    .err()
+                    .map(|err| GrafenError::DataBaseError {
+                        path: None,
+                        entry: Some(get_definition_name(definition).unwrap_or(format!(
+                            "{} definition {}",
+                            kind,
+                            i + 1
+                        ))),
+                        message: format!("could not parse the {}: {}", kind, err),
+                    })
+            })
+        })
+    }
+
+    find_error::<Residue>(value, "residue_definitions", "residue")
+        .or_else(|| find_error::<ComponentEntry>(value, "component_definitions", "component"))
+        .unwrap_or(GrafenError::DataBaseError {
+            path: None,
+            entry: None,
+            message: format!("could not parse the database: {}", err),
+        })
+}
+
+/// Get the residue code or component name of a definition which is not parsed.
+fn get_definition_name(definition: &serde_json::Value) -> Option<String> {
+    let name = definition["code"].as_str().or_else(|| {
+        definition
+            .as_object()
+            .and_then(|object| object.values().next())
+            .and_then(|variant| {
+                variant["name"]
+                    .as_str()
+                    .or_else(|| variant["description"].as_str())
+            })
+    });
+
+    name.map(|name| name.to_string())
+}
+
+impl Describe for DataBase {
+    fn describe(&self) -> String {
+        let mut description = String::new();
+        const ERR: &'static str = "Could not construct a string";
+
+        writeln!(
+            description,
+            "Database path: {} [{}]",
+            self.get_path_pretty(),
+            self.scope
+        )
+        .expect(ERR);
+
+        for layer in &self.layers {
+            writeln!(
+                description,
+                "Read-only database: {} [{}]",
+                layer.get_path_pretty(),
+                layer.scope
+            )
+            .expect(ERR);
+        }
+
+        writeln!(description, "").expect(ERR);
+
+        if self.layers.is_empty() {
+            writeln!(
+                description,
+                "{}",
+                describe_list_short("Component definitions", &self.component_defs)
+            )
+            .expect(ERR);
+            writeln!(
+                description,
+                "{}",
+                describe_list("Residue definitions", &self.residue_defs)
+            )
+            .expect(ERR);
+        } else {
+            writeln!(
+                description,
+                "{}",
+                describe_list_short("Component definitions", &self.components())
+            )
+            .expect(ERR);
+            writeln!(
+                description,
+                "{}",
+                describe_list("Residue definitions", &self.residues())
+            )
+            .expect(ERR);
+        }
+
+        description
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+/// Read a `DataBase` from a JSON formatted file.
+/// The owned path is set to the input path.
+///
+/// Databases written with an older layout are upgraded to the current.
+///
+/// # Errors
+/// Returns an `IoError` if the file could not be read, a `ParseError` if it is not
+/// JSON, or a `DataBaseError` if a definition could not be parsed or if it was written
+/// by a newer version of the library. The errors contain the path.
+pub fn read_database(path: &Path) -> Result<DataBase, GrafenError> {
+    let add_path = |err: GrafenError| match err {
+        GrafenError::ParseError { line, message, .. } => GrafenError::ParseError {
+            path: Some(path.to_path_buf()),
+            line,
+            message,
+        },
+        GrafenError::DataBaseError { entry, message, .. } => GrafenError::DataBaseError {
+            path: Some(path.to_path_buf()),
+            entry,
+            message,
+        },
+        err => err,
+    };
+
+    let buffer = File::open(&path).map_err(|err| GrafenError::io(path, err))?;
+    let mut database = DataBase::from_reader(buffer).map_err(add_path)?;
+
+    database.path = Some(PathBuf::from(&path));
+
+    Ok(database)
+}
+
+/// Write a `DataBase` as a JSON formatted file.
+/// The function writes to that owned by the object.
+///
+/// # Errors
+/// Returns a `DataBaseError` if the database has no path or an `IoError`
+/// if it could not be written.
+pub fn write_database(database: &DataBase) -> Result<(), GrafenError> {
+    if let Some(ref path) = database.path {
+        File::create(&path)
+            .and_then(|mut buffer| database.to_writer(&mut buffer))
+            .map_err(|err| GrafenError::io(path, err))?;
+
+        return Ok(());
+    }
+
+    Err(GrafenError::DataBaseError {
+        path: None,
+        entry: None,
+        message: "no path was set when trying to write the database to disk".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Direction;
+    use crate::surface::{LatticeType, Sheet};
+    use crate::system::*;
+    use crate::volume::Cuboid;
+
+    #[test]
+    fn serialize_and_deserialize_residue_entry() {
+        let base = Residue {
+            code: "RES".to_string(),
+            atoms: vec![
+                Atom {
+                    code: "A1".to_string(),
+                    position: Coord::new(0.0, 1.0, 2.0),
+                    metadata: AtomMetadata::default(),
+                },
+                Atom {
+                    code: "A2".to_string(),
+                    position: Coord::new(3.0, 4.0, 5.0),
+                    metadata: AtomMetadata::default(),
+                },
+            ],
+        };
+
+        let serialized = serde_json::to_string(&base).unwrap();
+        let deserialized: Residue = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(base, deserialized);
+    }
+
+    #[test]
+    fn database_by_default_sets_empty_vectors_if_not_available() {
+        let database: DataBase = serde_json::from_str("{}").unwrap();
+        assert!(database.residue_defs.is_empty());
+        assert!(database.component_defs.is_empty());
+    }
+
+    #[test]
+    fn read_and_write_database() {
+        let base = Residue {
+            code: "RES".to_string(),
+            atoms: vec![
+                Atom {
+                    code: "A1".to_string(),
+                    position: Coord::new(0.0, 1.0, 2.0),
+                    metadata: AtomMetadata::default(),
+                },
+                Atom {
+                    code: "A2".to_string(),
+                    position: Coord::new(3.0, 4.0, 5.0),
+                    metadata: AtomMetadata::default(),
+                },
+            ],
+        };
+
+        let database = DataBase {
+            path: Some(PathBuf::from("This/will/be/removed")),
+            residue_defs: vec![base.clone()],
+            ..DataBase::new()
+        };
+
+        let mut serialized: Vec<u8> = Vec::new();
+        database.to_writer(&mut serialized).unwrap();
+        let deserialized = DataBase::from_reader(serialized.as_slice()).unwrap();
+
+        assert_eq!(None, deserialized.path);
+        assert_eq!(database.residue_defs, deserialized.residue_defs);
+    }
+
+    #[test]
+    fn written_database_contains_the_current_version() {
+        let mut serialized: Vec<u8> = Vec::new();
+        DataBase::new().to_writer(&mut serialized).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&serialized).unwrap();
+        assert_eq!(value["version"], serde_json::Value::from(DATABASE_VERSION));
+    }
+
+    #[test]
+    fn parse_errors_name_the_definition_which_could_not_be_parsed() {
+        let content = r#"{
+            "component_definitions": [
+                { "VolumeCuboid": { "name": "box" } },
+                { "VolumeBlob": { "name": "blob" } }
+            ]
+        }"#;
+
+        match DataBase::from_reader(content.as_bytes()).unwrap_err() {
+            GrafenError::DataBaseError { entry, .. } => assert_eq!(entry, Some("blob".into())),
+            err => panic!("expected a database error, got {:?}", err),
+        }
+
+        let content = r#"{ "component_definitions": [ { "VolumeBlob": {} } ] }"#;
+        let err = DataBase::from_reader(content.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("component definition 1"));
+    }
+
+    #[test]
+    fn set_database_path() {
+        let mut database = DataBase::new();
+        assert!(database.set_path("test").is_ok());
+        assert_eq!("test.json", database.path.unwrap().to_str().unwrap());
+    }
+
+    #[test]
+    fn set_database_to_empty_path_is_error() {
+        let mut database = DataBase::new();
+        database.path = Some(PathBuf::from("unchanged.json"));
+        assert!(database.set_path("").is_err());
+        assert_eq!("unchanged.json", database.path.unwrap().to_str().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn get_database_path() {
+        let mut database = DataBase::new();
+        assert_eq!("None", &database.get_path_pretty());
+
+        database.set_path("/a/file.json").unwrap();
+        assert_eq!("'/a/file.json'", &database.get_path_pretty());
+    }
+
+    #[test]
+    fn create_entry_macro_adds_from_method() {
+        let cuboid = Cuboid::default();
+        let component = ComponentEntry::from(cuboid.clone());
+
+        match component {
+            ComponentEntry::VolumeCuboid(object) => {
+                assert_eq!(object.name, cuboid.name);
+                assert_eq!(object.residue, cuboid.residue);
+                assert_eq!(object.size, cuboid.size);
+                assert_eq!(object.origin, cuboid.origin);
+                assert_eq!(object.coords, cuboid.coords);
+            }
+            _ => panic!["Incorrect object was created"],
+        }
+    }
+
+    #[test]
+    fn component_entry_adds_with_pbc_method() {
+        let sheet = Sheet {
+            name: None,
+            residue: None,
+            lattice: LatticeType::Hexagonal { a: 0.1 },
+            std_z: None,
+            origin: Coord::ORIGO,
+            normal: Direction::Z,
+            orientation: None,
+            length: 2.0,
+            width: 1.0,
+            coords: vec![
+                Coord::new(0.5, 0.0, 0.0), // inside box
+                Coord::new(1.5, 0.0, 0.0), // inside box
+                Coord::new(2.5, 0.0, 0.0), // outside box by 0.5 along x
+                Coord::new(0.0, 1.5, 0.0), // outside box by 0.5 along y
+            ],
+        };
+
+        let component = ComponentEntry::from(sheet);
+
+        let pbc_coords = vec![
+            Coord::new(0.5, 0.0, 0.0), // unchanged
+            Coord::new(1.5, 0.0, 0.0), // unchanged
+            Coord::new(0.5, 0.0, 0.0), // moved to within box
+            Coord::new(0.0, 0.5, 0.0), // moved to within box
+        ];
+        let pbc_component = component.with_pbc();
+
+        if let ComponentEntry::SurfaceSheet(ref sheet) = pbc_component {
+            assert_eq!(&sheet.coords, &pbc_coords);
+        } else {
+            panic!("From component was selected in the constructed test")
+        }
+    }
+
+    /// Assert that a component has a volume which contains a coordinate just inside
+    /// the input position and not one just outside, moved by a small step along `step`.
+    fn assert_volume_edge(component: &ComponentEntry, margin: f64, edge: Coord, step: Coord) {
+        let volume = component.to_volume(margin).unwrap();
+        let eps = 1e-6;
+
+        assert!(volume.contains(edge - step * eps));
+        assert!(!volume.contains(edge + step * eps));
+    }
+
+    #[test]
+    fn volume_cuboid_to_volume_adds_margin_on_all_sides() {
+        let component = ComponentEntry::from(Cuboid {
+            origin: Coord::new(1.0, 1.0, 1.0),
+            size: Coord::new(2.0, 3.0, 4.0),
+            coords: vec![Coord::new(2.0, 2.0, 2.0)],
+            ..Cuboid::default()
+        });
+
+        let (x, y, z) = (
+            Direction::X.unit_vector(),
+            Direction::Y.unit_vector(),
+            Direction::Z.unit_vector(),
+        );
+        let center = Coord::new(2.0, 2.5, 3.0);
+
+        assert_volume_edge(&component, 0.0, Coord::new(3.0, 2.5, 3.0), x);
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 2.5, 3.0), x * -1.0);
+        assert_volume_edge(&component, 0.0, Coord::new(2.0, 4.0, 3.0), y);
+        assert_volume_edge(&component, 0.0, Coord::new(2.0, 2.5, 5.0), z);
+
+        assert_volume_edge(&component, 0.5, Coord::new(3.5, 2.5, 3.0), x);
+        assert_volume_edge(&component, 0.5, Coord::new(0.5, 2.5, 3.0), x * -1.0);
+        assert_volume_edge(&component, 0.5, Coord::new(2.0, 0.5, 3.0), y * -1.0);
+        assert_volume_edge(&component, 0.5, Coord::new(2.0, 2.5, 5.5), z);
+        assert!(component.to_volume(0.5).unwrap().contains(center));
+    }
+
+    #[test]
+    fn volume_slab_to_volume_adds_margin_along_the_normal() {
+        let component = ComponentEntry::from(volume::Slab {
+            name: None,
+            residue: None,
+            normal: Direction::Y,
+            origin: Coord::new(0.0, 1.0, 0.0),
+            thickness: 2.0,
+            length: 1.0,
+            width: 1.0,
+            fill_type: None,
+            coords: vec![],
+        });
+
+        let y = Direction::Y.unit_vector();
+
+        assert_volume_edge(&component, 0.0, Coord::new(0.0, 3.0, 0.0), y);
+        assert_volume_edge(&component, 0.0, Coord::new(0.0, 1.0, 0.0), y * -1.0);
+        assert_volume_edge(&component, 1.0, Coord::new(0.0, 4.0, 0.0), y);
+        assert_volume_edge(&component, 1.0, Coord::new(0.0, 0.0, 0.0), y * -1.0);
+    }
+
+    #[test]
+    fn surface_cylinder_to_volume_is_the_enclosed_cylinder() {
+        let component = ComponentEntry::from(surface::Cylinder {
+            name: None,
+            residue: None,
+            lattice: LatticeType::Hexagonal { a: 0.1 },
+            alignment: Direction::Z,
+            cap: None,
+            orientation: None,
+            origin: Coord::new(1.0, 1.0, 1.0),
+            radius: 2.0,
+            height: 3.0,
+            coords: vec![],
+        });
+
+        let (x, z) = (Direction::X.unit_vector(), Direction::Z.unit_vector());
+
+        assert_volume_edge(&component, 0.0, Coord::new(3.0, 1.0, 2.0), x);
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 1.0, 4.0), z);
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 1.0, 1.0), z * -1.0);
+
+        assert_volume_edge(&component, 0.5, Coord::new(3.5, 1.0, 2.0), x);
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, 1.0, 4.5), z);
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, 1.0, 0.5), z * -1.0);
+    }
+
+    #[test]
+    fn surface_sphere_to_volume_is_the_enclosed_sphere() {
+        let component = ComponentEntry::from(surface::Sphere {
+            name: None,
+            residue: None,
+            lattice: surface::SphereLattice::Fibonacci { density: 1.0 },
+            orientation: None,
+            origin: Coord::new(1.0, 1.0, 1.0),
+            radius: 2.0,
+            coords: vec![],
+        });
+
+        let y = Direction::Y.unit_vector();
+
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 3.0, 1.0), y);
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, -1.0, 1.0), y * -1.0);
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, 3.5, 1.0), y);
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, -1.5, 1.0), y * -1.0);
+    }
+
+    #[test]
+    fn surface_ellipsoid_to_volume_is_the_enclosed_ellipsoid() {
+        let component = ComponentEntry::from(surface::Ellipsoid {
+            name: None,
+            residue: None,
+            lattice: surface::SphereLattice::Fibonacci { density: 1.0 },
+            orientation: None,
+            origin: Coord::ORIGO,
+            semi_axes: Coord::new(1.0, 2.0, 3.0),
+            coords: vec![],
+        });
+
+        let (x, y, z) = (
+            Direction::X.unit_vector(),
+            Direction::Y.unit_vector(),
+            Direction::Z.unit_vector(),
+        );
+
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 0.0, 0.0), x);
+        assert_volume_edge(&component, 0.0, Coord::new(0.0, 2.0, 0.0), y);
+        assert_volume_edge(&component, 0.0, Coord::new(0.0, 0.0, 3.0), z);
+
+        assert_volume_edge(&component, 0.5, Coord::new(1.5, 0.0, 0.0), x);
+        assert_volume_edge(&component, 0.5, Coord::new(0.0, 2.5, 0.0), y);
+        assert_volume_edge(&component, 0.5, Coord::new(0.0, 0.0, 3.5), z);
+    }
+
+    #[test]
+    fn surface_bilayer_to_volume_is_the_cuboid_spanned_by_its_lipids() {
+        let lipid = surface::Lipid {
+            residue: resbase![
+                "LIP",
+                ("P", 0.0, 0.0, 0.0),
+                ("N", 0.0, 0.0, 0.5),
+                ("C", 0.0, 0.0, -1.0)
+            ],
+            fraction: 1.0,
+        };
+
+        let component = ComponentEntry::from(surface::Bilayer {
+            name: None,
+            upper_leaflet: vec![lipid],
+            lower_leaflet: vec![],
+            area_per_lipid: 0.6,
+            thickness: 4.0,
+            lipid_axis: Coord::new(0.0, 0.0, -1.0),
+            random_rotation: false,
+            origin: Coord::new(0.0, 0.0, 1.0),
+            length: 2.0,
+            width: 3.0,
+            lipids: vec![],
+        });
+
+        let (x, z) = (Direction::X.unit_vector(), Direction::Z.unit_vector());
+
+        // The head group atoms extend 0.5 beyond the head group planes at z = 1 and 5
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 1.0, 5.5), z);
+        assert_volume_edge(&component, 0.0, Coord::new(1.0, 1.0, 0.5), z * -1.0);
+        assert_volume_edge(&component, 0.0, Coord::new(2.0, 1.0, 3.0), x);
+
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, 1.0, 6.0), z);
+        assert_volume_edge(&component, 0.5, Coord::new(1.0, 1.0, 0.0), z * -1.0);
+        assert_volume_edge(&component, 0.5, Coord::new(2.5, 1.0, 3.0), x);
+    }
+
+    #[test]
+    fn components_without_a_volume_return_none() {
+        let sheet = ComponentEntry::from(Sheet {
+            name: None,
+            residue: None,
+            lattice: LatticeType::Hexagonal { a: 0.1 },
+            std_z: None,
+            origin: Coord::ORIGO,
+            normal: Direction::Z,
+            orientation: None,
+            length: 2.0,
+            width: 1.0,
+            coords: vec![],
+        });
+
+        let cuboid = ComponentEntry::from(surface::Cuboid {
+            name: None,
+            residue: None,
+            lattice: LatticeType::Hexagonal { a: 0.1 },
+            std_z: None,
+            origin: Coord::ORIGO,
+            size: Coord::new(1.0, 1.0, 1.0),
+            sides: surface::Sides::all(),
+            orientation: None,
+            coords: vec![],
+        });
+
+        let conf = ComponentEntry::from(read_conf::ReadConf {
+            conf: None,
+            backup_conf: None,
+            path: PathBuf::from("conf.gro"),
+            description: "conf".to_string(),
+            volume_type: read_conf::ConfType::Spheroid {
+                origin: Coord::ORIGO,
+                radius: 1.0,
+            },
+        });
+
+        for component in &[sheet, cuboid, conf] {
+            assert!(!component.has_volume());
+            assert!(component.to_volume(0.0).is_none());
+            assert!(component.to_volume(1.0).is_none());
+        }
+
+        assert!(ComponentEntry::from(Cuboid::default()).has_volume());
+    }
+}
@@ -0,0 +1,512 @@
+//! Find definitions in a `DataBase` which cannot be constructed.
+//!
+//! Every problem is reported as a `Diagnostic` with the JSON path to the value
+//! in the database file, eg. `component_definitions[2].SurfaceSheet.lattice`.
+
+use super::{ComponentEntry, DataBase};
+use crate::{
+    coord::Coord,
+    describe::Describe,
+    error::{GrafenError, Result},
+    surface::Lipid,
+    system::Residue,
+    volume::FillType,
+};
+
+use serde_json;
+use std::{fmt, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// How severe a problem with a definition is.
+pub enum Severity {
+    /// The definition can be used but may not behave as expected.
+    Warning,
+    /// The definition cannot be constructed.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A problem with a definition in a `DataBase`.
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// JSON path to the value in the database.
+    pub path: String,
+    /// Name of the definition, if it has one.
+    pub name: Option<String>,
+    pub message: String,
+}
+
+impl Describe for Diagnostic {
+    fn describe(&self) -> String {
+        match self.name {
+            Some(ref name) => format!(
+                "{}: {} ('{}'): {}",
+                self.severity, self.path, name, self.message
+            ),
+            None => format!("{}: {}: {}", self.severity, self.path, self.message),
+        }
+    }
+
+    fn describe_short(&self) -> String {
+        self.describe()
+    }
+}
+
+impl DataBase {
+    /// Check all residue and component definitions for problems.
+    ///
+    /// Relative paths to configuration files are checked relative to the database location.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let mut residue_codes: Vec<&str> = Vec::new();
+
+        for (i, residue) in self.residue_defs.iter().enumerate() {
+            let mut checker = Checker {
+                path: format!("residue_definitions[{}]", i),
+                name: Some(residue.code.clone()),
+                diagnostics: &mut diagnostics,
+            };
+
+            if residue_codes.contains(&residue.code.as_str()) {
+                checker.warning("", "another residue has the same code");
+            }
+
+            checker.residue("", residue);
+            residue_codes.push(&residue.code);
+        }
+
+        let database_dir = self
+            .path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| PathBuf::from(dir))
+            .unwrap_or(PathBuf::new());
+
+        let mut component_names = Vec::new();
+
+        for (i, component) in self.component_defs.iter().enumerate() {
+            let name = component.name();
+
+            let mut checker = Checker {
+                path: format!("component_definitions[{}].{}", i, variant_name(component)),
+                name: name.clone(),
+                diagnostics: &mut diagnostics,
+            };
+
+            if let Some(name) = name {
+                if component_names.contains(&name) {
+                    checker.warning("", "another component has the same name");
+                }
+
+                component_names.push(name);
+            }
+
+            checker.component(component, &database_dir);
+        }
+
+        diagnostics
+    }
+}
+
+/// Get the name of the variant as it is written in the database.
+fn variant_name(component: &ComponentEntry) -> String {
+    serde_json::to_value(component)
+        .ok()
+        .and_then(|value| {
+            value
+                .as_object()
+                .and_then(|object| object.keys().next().cloned())
+        })
+        .unwrap_or("?".to_string())
+}
+
+/// Collect diagnostics for a single definition.
+struct Checker<'a> {
+    path: String,
+    name: Option<String>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn add(&mut self, severity: Severity, field: &str, message: &str) {
+        let path = if field.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}.{}", self.path, field)
+        };
+
+        self.diagnostics.push(Diagnostic {
+            severity,
+            path,
+            name: self.name.clone(),
+            message: message.to_string(),
+        });
+    }
+
+    fn error(&mut self, field: &str, message: &str) {
+        self.add(Severity::Error, field, message);
+    }
+
+    fn warning(&mut self, field: &str, message: &str) {
+        self.add(Severity::Warning, field, message);
+    }
+
+    fn positive(&mut self, field: &str, value: f64) {
+        if !(value > 0.0 && value.is_finite()) {
+            self.error(field, &format!("must be positive (is {})", value));
+        }
+    }
+
+    fn non_negative(&mut self, field: &str, value: f64) {
+        if !(value >= 0.0 && value.is_finite()) {
+            self.error(field, &format!("must not be negative (is {})", value));
+        }
+    }
+
+    fn residue(&mut self, field: &str, residue: &Residue) {
+        let join = |sub: &str| {
+            if field.is_empty() {
+                sub.to_string()
+            } else {
+                format!("{}.{}", field, sub)
+            }
+        };
+
+        if residue.code.trim().is_empty() {
+            self.error(&join("code"), "the residue has no code");
+        }
+
+        if residue.atoms.is_empty() {
+            self.error(&join("atoms"), "the residue has no atoms");
+        }
+
+        for (i, atom) in residue.atoms.iter().enumerate() {
+            if let Some(mass) = atom.metadata.mass {
+                // Virtual sites have no mass
+                self.non_negative(&join(&format!("atoms[{}].mass", i)), mass);
+            }
+        }
+    }
+
+    fn component_residue(&mut self, residue: &Option<Residue>) {
+        match residue {
+            &Some(ref residue) => self.residue("residue", residue),
+            &None => self.error("residue", "no residue is set"),
+        }
+    }
+
+    fn fill_type(&mut self, fill_type: &Option<FillType>, residue: &Option<Residue>) {
+        let value = match fill_type {
+            &Some(FillType::Density(value))
+            | &Some(FillType::MassDensity(value))
+            | &Some(FillType::Molarity(value)) => value,
+            &Some(FillType::NumCoords(_)) | &None => return,
+        };
+
+        if !(value > 0.0 && value.is_finite()) {
+            self.positive("fill_type", value);
+            return;
+        }
+
+        // A missing residue is reported by itself, but a residue without a known
+        // mass cannot be filled by mass density
+        if let (&Some(fill_type), &Some(ref residue)) = (fill_type, residue) {
+            match fill_type.check(Some(residue)) {
+                Ok(_) => (),
+                Err(GrafenError::InvalidParameter { message, .. }) => {
+                    self.error("fill_type", &message)
+                }
+                Err(err) => self.error("fill_type", &err.to_string()),
+            }
+        }
+    }
+
+    fn lattice(&mut self, check: Result<()>) {
+        match check {
+            Ok(()) => (),
+            Err(GrafenError::LatticeError(message)) => self.error("lattice", &message),
+            Err(err) => self.error("lattice", &err.to_string()),
+        }
+    }
+
+    fn leaflet(&mut self, field: &str, lipids: &[Lipid]) {
+        for (i, lipid) in lipids.iter().enumerate() {
+            self.residue(&format!("{}[{}].residue", field, i), &lipid.residue);
+            self.positive(&format!("{}[{}].fraction", field, i), lipid.fraction);
+        }
+    }
+
+    fn component(&mut self, component: &ComponentEntry, database_dir: &PathBuf) {
+        match component {
+            &ComponentEntry::VolumeCuboid(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeCylinder(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeSpheroid(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeEllipsoid(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeCone(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeTorus(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeCapsule(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::VolumeSlab(ref object) => {
+                self.component_residue(&object.residue);
+                self.fill_type(&object.fill_type, &object.residue);
+            }
+            &ComponentEntry::SurfaceSheet(ref object) => {
+                self.component_residue(&object.residue);
+                self.lattice(object.lattice.check());
+
+                if let Some(std_z) = object.std_z {
+                    self.non_negative("std_z", std_z);
+                }
+            }
+            &ComponentEntry::SurfaceCuboid(ref object) => {
+                self.component_residue(&object.residue);
+                self.lattice(object.lattice.check());
+            }
+            &ComponentEntry::SurfaceCylinder(ref object) => {
+                self.component_residue(&object.residue);
+                self.lattice(object.lattice.check());
+            }
+            &ComponentEntry::SurfaceSphere(ref object) => {
+                self.component_residue(&object.residue);
+                self.lattice(object.lattice.check());
+            }
+            &ComponentEntry::SurfaceEllipsoid(ref object) => {
+                self.component_residue(&object.residue);
+                self.lattice(object.lattice.check());
+            }
+            &ComponentEntry::SurfaceBilayer(ref object) => {
+                if object.upper_leaflet.is_empty() {
+                    self.error("upper_leaflet", "no lipids are set");
+                }
+
+                self.leaflet("upper_leaflet", &object.upper_leaflet);
+                self.leaflet("lower_leaflet", &object.lower_leaflet);
+                self.positive("area_per_lipid", object.area_per_lipid);
+
+                self.non_negative("thickness", object.thickness);
+
+                if object.lipid_axis == Coord::ORIGO {
+                    self.error("lipid_axis", "must not be zero");
+                }
+            }
+            &ComponentEntry::ConfigurationFile(ref object) => {
+                let path = database_dir.join(&object.path);
+
+                if !path.is_file() {
+                    self.error(
+                        "path",
+                        &format!("the configuration file '{}' does not exist", path.display()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coord::Direction,
+        read_conf::{ConfType, ReadConf},
+        surface::{LatticeType, Sheet},
+        system::Atom,
+        volume::Cuboid,
+    };
+
+    fn sheet(name: &str, lattice: LatticeType, residue: Option<Residue>) -> ComponentEntry {
+        ComponentEntry::from(Sheet {
+            name: Some(name.to_string()),
+            residue,
+            lattice,
+            std_z: None,
+            origin: Coord::ORIGO,
+            normal: Direction::Z,
+            orientation: None,
+            length: 0.0,
+            width: 0.0,
+            coords: vec![],
+        })
+    }
+
+    #[test]
+    fn valid_database_has_no_diagnostics() {
+        let residue = resbase!["GRA", ("C", 0.0, 0.0, 0.0)];
+
+        let database = DataBase {
+            residue_defs: vec![residue.clone()],
+            component_defs: vec![sheet(
+                "graphene",
+                LatticeType::Hexagonal { a: 0.142 },
+                Some(residue),
+            )],
+            ..DataBase::new()
+        };
+
+        assert!(database.validate().is_empty());
+    }
+
+    #[test]
+    fn invalid_definitions_are_reported_with_their_path() {
+        let residue = resbase!["GRA", ("C", 0.0, 0.0, 0.0)];
+        let empty_residue = Residue {
+            code: "EMPTY".to_string(),
+            atoms: vec![],
+        };
+
+        let database = DataBase {
+            residue_defs: vec![residue.clone(), empty_residue.clone(), residue.clone()],
+            component_defs: vec![
+                sheet("graphene", LatticeType::Hexagonal { a: 0.0 }, Some(residue)),
+                sheet(
+                    "graphene",
+                    LatticeType::PoissonDisc { density: 10.0 },
+                    Some(empty_residue),
+                ),
+                ComponentEntry::from(Cuboid {
+                    name: Some("box".to_string()),
+                    residue: None,
+                    fill_type: Some(FillType::Density(-1.0)),
+                    ..Cuboid::default()
+                }),
+            ],
+            ..DataBase::new()
+        };
+
+        let diagnostics = database.validate();
+        let paths = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.path.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![
+                (Severity::Error, "residue_definitions[1].atoms"),
+                (Severity::Warning, "residue_definitions[2]"),
+                (
+                    Severity::Error,
+                    "component_definitions[0].SurfaceSheet.lattice"
+                ),
+                (Severity::Warning, "component_definitions[1].SurfaceSheet"),
+                (
+                    Severity::Error,
+                    "component_definitions[1].SurfaceSheet.residue.atoms"
+                ),
+                (
+                    Severity::Error,
+                    "component_definitions[2].VolumeCuboid.residue"
+                ),
+                (
+                    Severity::Error,
+                    "component_definitions[2].VolumeCuboid.fill_type"
+                ),
+            ]
+        );
+
+        assert_eq!(
+            diagnostics[2].describe(),
+            "error: component_definitions[0].SurfaceSheet.lattice ('graphene'): \
+             the spacing must be positive (is 0)"
+        );
+    }
+
+    #[test]
+    fn mass_densities_of_residues_without_masses_are_errors() {
+        let without_mass = resbase!["SOL", ("OW", 0.0, 0.0, 0.0)];
+        let mut with_mass = without_mass.clone();
+        with_mass.atoms[0].metadata.element = Some("O".to_string());
+
+        let volume = |name: &str, residue: &Residue, fill_type: FillType| {
+            ComponentEntry::from(Cuboid {
+                name: Some(name.to_string()),
+                residue: Some(residue.clone()),
+                fill_type: Some(fill_type),
+                ..Cuboid::default()
+            })
+        };
+
+        let database = DataBase {
+            component_defs: vec![
+                volume("water", &without_mass, FillType::MassDensity(1.0)),
+                volume("oxygen", &with_mass, FillType::MassDensity(1.0)),
+                volume("solution", &without_mass, FillType::Molarity(1.0)),
+            ],
+            ..DataBase::new()
+        };
+
+        let diagnostics = database.validate();
+        let paths = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.path.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![(
+                Severity::Error,
+                "component_definitions[0].VolumeCuboid.fill_type"
+            )]
+        );
+
+        assert_eq!(
+            diagnostics[0].describe(),
+            "error: component_definitions[0].VolumeCuboid.fill_type ('water'): \
+             the mass of the residue is not known"
+        );
+    }
+
+    #[test]
+    fn missing_configuration_files_are_errors() {
+        let database = DataBase {
+            component_defs: vec![ComponentEntry::ConfigurationFile(ReadConf {
+                conf: None,
+                backup_conf: None,
+                path: PathBuf::from("this/file/does/not/exist.gro"),
+                description: "conf".to_string(),
+                volume_type: ConfType::Spheroid {
+                    origin: Coord::ORIGO,
+                    radius: 1.0,
+                },
+            })],
+            ..DataBase::new()
+        };
+
+        let diagnostics = database.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].path,
+            "component_definitions[0].ConfigurationFile.path"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Look up properties of chemical elements.
+
+/// Standard atomic weights (g/mol) of common elements in molecular simulations.
+const ATOMIC_MASSES: &[(&str, f64)] = &[
+    ("H", 1.008),
+    ("He", 4.0026),
+    ("Li", 6.94),
+    ("Be", 9.0122),
+    ("B", 10.81),
+    ("C", 12.011),
+    ("N", 14.007),
+    ("O", 15.999),
+    ("F", 18.998),
+    ("Ne", 20.180),
+    ("Na", 22.990),
+    ("Mg", 24.305),
+    ("Al", 26.982),
+    ("Si", 28.085),
+    ("P", 30.974),
+    ("S", 32.06),
+    ("Cl", 35.45),
+    ("Ar", 39.948),
+    ("K", 39.098),
+    ("Ca", 40.078),
+    ("Ti", 47.867),
+    ("Cr", 51.996),
+    ("Mn", 54.938),
+    ("Fe", 55.845),
+    ("Co", 58.933),
+    ("Ni", 58.693),
+    ("Cu", 63.546),
+    ("Zn", 65.38),
+    ("Ga", 69.723),
+    ("Ge", 72.630),
+    ("As", 74.922),
+    ("Se", 78.971),
+    ("Br", 79.904),
+    ("Kr", 83.798),
+    ("Rb", 85.468),
+    ("Sr", 87.62),
+    ("Mo", 95.95),
+    ("Pd", 106.42),
+    ("Ag", 107.87),
+    ("Cd", 112.41),
+    ("Sn", 118.71),
+    ("I", 126.90),
+    ("Xe", 131.29),
+    ("Cs", 132.91),
+    ("Ba", 137.33),
+    ("Pt", 195.08),
+    ("Au", 196.97),
+    ("Hg", 200.59),
+    ("Pb", 207.2),
+];
+
+/// Return the standard atomic weight (g/mol) of an element from its symbol.
+///
+/// The symbol is matched case insensitively.
+pub fn atomic_mass(symbol: &str) -> Option<f64> {
+    let symbol = symbol.trim();
+
+    ATOMIC_MASSES
+        .iter()
+        .find(|(element, _)| element.eq_ignore_ascii_case(symbol))
+        .map(|&(_, mass)| mass)
+}
+
+/// Guess the element of an atom from its name, as is done for configurations
+/// which have no element information.
+///
+/// Leading digits are skipped and the element is taken from the first letter of
+/// the name (`OW` and `HW1` are oxygen and hydrogen, `CA` is carbon). A name is
+/// read as a two-letter element if its second letter is lowercase (`Cl`), or if it
+/// is an ion whose atom and residue names are the same (`NA` in residue `NA`).
+pub fn guess_element(atom_name: &str, residue_name: &str) -> Option<&'static str> {
+    let atom_name = atom_name.trim();
+    let letters = atom_name
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>();
+
+    let mut chars = letters.chars();
+    let first = chars.next()?;
+
+    let is_two_letter = chars.next().map_or(false, |second| {
+        second.is_ascii_lowercase() || atom_name.eq_ignore_ascii_case(residue_name.trim())
+    });
+
+    if is_two_letter {
+        if let Some(element) = find_element(&letters[..2]) {
+            return Some(element);
+        }
+    }
+
+    find_element(&first.to_string())
+}
+
+/// Return the symbol of an element in the table, matched case insensitively.
+fn find_element(symbol: &str) -> Option<&'static str> {
+    ATOMIC_MASSES
+        .iter()
+        .find(|(element, _)| element.eq_ignore_ascii_case(symbol))
+        .map(|&(element, _)| element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_element_uses_the_first_letter_of_atom_names() {
+        assert_eq!(guess_element("OW", "SOL"), Some("O"));
+        assert_eq!(guess_element("HW1", "SOL"), Some("H"));
+        assert_eq!(guess_element("CA", "ALA"), Some("C"));
+        assert_eq!(guess_element("1HB", "ALA"), Some("H"));
+        assert_eq!(guess_element("NA", "HEM"), Some("N"));
+    }
+
+    #[test]
+    fn guess_element_reads_two_letter_elements_of_ions_and_lowercase_names() {
+        assert_eq!(guess_element("NA", "NA"), Some("Na"));
+        assert_eq!(guess_element("CL", "CL"), Some("Cl"));
+        assert_eq!(guess_element("Cl", "MOL"), Some("Cl"));
+    }
+
+    #[test]
+    fn guess_element_returns_none_for_unknown_names() {
+        assert_eq!(guess_element("XW", "SOL"), None);
+        assert_eq!(guess_element("12", "SOL"), None);
+        assert_eq!(guess_element("", "SOL"), None);
+    }
+
+    #[test]
+    fn atomic_mass_is_found_case_insensitively() {
+        assert_eq!(atomic_mass("C"), Some(12.011));
+        assert_eq!(atomic_mass("CL"), Some(35.45));
+        assert_eq!(atomic_mass(" na "), Some(22.990));
+        assert_eq!(atomic_mass("Xx"), None);
+    }
+}
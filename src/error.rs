@@ -1,21 +1,117 @@
 //! Implements the custom `GrafenError` class for the library.
 
-use std::{error, fmt, result};
+use std::{
+    error, fmt, io,
+    path::{Path, PathBuf},
+    result,
+};
 
 #[derive(Debug)]
 /// A class for configuration or runtime errors.
 pub enum GrafenError {
-    /// Something went wrong when creating the system.
-    RunError(String),
+    /// A parameter of a component has an invalid value, eg. a non-positive size.
+    InvalidParameter {
+        /// Name of the parameter.
+        parameter: String,
+        /// Why the value is invalid.
+        message: String,
+    },
+    /// The lattice of a surface has invalid parameters.
+    LatticeError(String),
+    /// A file could not be read or written.
+    IoError {
+        /// Path to the file.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// The contents of a file could not be parsed.
+    ParseError {
+        /// Path to the file, if read from one.
+        path: Option<PathBuf>,
+        /// Line number (starting from 1) of the error, if known.
+        line: Option<usize>,
+        /// What could not be parsed.
+        message: String,
+    },
+    /// A database or one of its definitions is invalid.
+    DataBaseError {
+        /// Path to the database, if read from one.
+        path: Option<PathBuf>,
+        /// Name of the definition, if the error is in one.
+        entry: Option<String>,
+        /// What is wrong.
+        message: String,
+    },
 }
 
 /// Shorthand for our `Result` class.
 pub type Result<T> = result::Result<T, GrafenError>;
 
+impl GrafenError {
+    /// Construct an `InvalidParameter` error.
+    pub fn invalid_parameter(parameter: &str, message: &str) -> GrafenError {
+        GrafenError::InvalidParameter {
+            parameter: parameter.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Construct an `IoError` for a path.
+    pub fn io(path: &Path, error: io::Error) -> GrafenError {
+        GrafenError::IoError {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+}
+
 impl fmt::Display for GrafenError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            GrafenError::RunError(ref err) => write!(f, "{}", err),
+            GrafenError::InvalidParameter {
+                ref parameter,
+                ref message,
+            } => write!(f, "invalid {}: {}", parameter, message),
+            GrafenError::LatticeError(ref err) => write!(f, "invalid lattice: {}", err),
+            GrafenError::IoError {
+                ref path,
+                ref error,
+            } => write!(f, "could not access '{}': {}", path.display(), error),
+            GrafenError::ParseError {
+                ref path,
+                line,
+                ref message,
+            } => {
+                write!(f, "could not parse")?;
+
+                if let Some(ref path) = *path {
+                    write!(f, " '{}'", path.display())?;
+                }
+
+                if let Some(line) = line {
+                    write!(f, " at line {}", line)?;
+                }
+
+                write!(f, ": {}", message)
+            }
+            GrafenError::DataBaseError {
+                ref path,
+                ref entry,
+                ref message,
+            } => {
+                write!(f, "invalid database")?;
+
+                if let Some(ref path) = *path {
+                    write!(f, " '{}'", path.display())?;
+                }
+
+                if let Some(ref entry) = *entry {
+                    write!(f, " (definition '{}')", entry)?;
+                }
+
+                write!(f, ": {}", message)
+            }
         }
     }
 }
@@ -23,13 +119,49 @@ impl fmt::Display for GrafenError {
 impl error::Error for GrafenError {
     fn description(&self) -> &str {
         match *self {
-            GrafenError::RunError(ref err) => &err,
+            GrafenError::InvalidParameter { ref message, .. } => &message,
+            GrafenError::LatticeError(ref err) => &err,
+            GrafenError::IoError { .. } => "could not read or write a file",
+            GrafenError::ParseError { ref message, .. } => &message,
+            GrafenError::DataBaseError { ref message, .. } => &message,
         }
     }
 
     fn cause(&self) -> Option<&error::Error> {
         match *self {
-            GrafenError::RunError(_) => None, // There is no good cause for this currently.
+            GrafenError::IoError { ref error, .. } => Some(error),
+            _ => None, // There is no good cause for the others currently.
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_are_displayed_with_their_context() {
+        let err = GrafenError::ParseError {
+            path: Some(PathBuf::from("conf.pdb")),
+            line: Some(3),
+            message: "bad coordinate".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "could not parse 'conf.pdb' at line 3: bad coordinate"
+        );
+
+        let err = GrafenError::DataBaseError {
+            path: None,
+            entry: Some("Graphene".to_string()),
+            message: "missing lattice".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid database (definition 'Graphene'): missing lattice"
+        );
+
+        let err = GrafenError::invalid_parameter("radius", "it must be positive");
+        assert_eq!(err.to_string(), "invalid radius: it must be positive");
+    }
+}
@@ -1,9 +1,13 @@
 //! Iterate over atoms in components.
 
-use crate::{coord::Coord, system::Residue};
+use crate::{
+    coord::{Coord, Rotation},
+    element::guess_element,
+    system::{AtomMetadata, Residue},
+};
 
 use mdio;
-use std::{cell::RefCell, rc::Rc, slice::Iter};
+use std::{cell::RefCell, rc::Rc, slice::Iter, vec};
 
 /// Iteration object which owns the iterator. Used to ensure that it always exists.
 ///
@@ -25,13 +29,22 @@ impl<'a> ConfIter<'a> {
 pub enum ResidueIter<'a> {
     Conf(ConfIter<'a>),
     Component(&'a Residue, Iter<'a, Coord>),
+    /// Residues which are individually rotated before being placed at their positions.
+    /// The rotation is made around the first atom of the residue, which keeps
+    /// its position relative to the residue position.
+    Rotated(vec::IntoIter<(&'a Residue, Coord, Rotation)>),
     None,
 }
 
 #[derive(Debug, Clone)]
 pub enum ResidueIterOut {
     FromConf(Vec<Rc<RefCell<mdio::Atom>>>),
-    FromComp(Rc<RefCell<String>>, Vec<(Rc<RefCell<String>>, Coord)>),
+    /// Residue name, atom names with their positions and the atom metadata.
+    FromComp(
+        Rc<RefCell<String>>,
+        Vec<(Rc<RefCell<String>>, Coord)>,
+        Vec<AtomMetadata>,
+    ),
 }
 
 impl ResidueIterOut {
@@ -45,7 +58,7 @@ impl ResidueIterOut {
 
                 Rc::clone(&name)
             }
-            &ResidueIterOut::FromComp(ref res, _) => Rc::clone(&res),
+            &ResidueIterOut::FromComp(ref res, _, _) => Rc::clone(&res),
         }
     }
 
@@ -61,7 +74,28 @@ impl ResidueIterOut {
                     )
                 })
                 .collect(),
-            &ResidueIterOut::FromComp(_, ref atoms) => atoms.clone(),
+            &ResidueIterOut::FromComp(_, ref atoms, _) => atoms.clone(),
+        }
+    }
+
+    /// Return the metadata of the atoms in the residue. Atoms from read configurations
+    /// only have their element, which is guessed from their name.
+    pub fn get_metadata(&self) -> Vec<AtomMetadata> {
+        match self {
+            &ResidueIterOut::FromConf(ref atoms) => atoms
+                .iter()
+                .map(|atom| {
+                    let atom = atom.borrow();
+                    let residue = atom.residue.borrow();
+                    let element = guess_element(&atom.name.borrow(), &residue.name.borrow());
+
+                    AtomMetadata {
+                        element: element.map(|element| element.to_string()),
+                        ..AtomMetadata::default()
+                    }
+                })
+                .collect(),
+            &ResidueIterOut::FromComp(_, _, ref metadata) => metadata.clone(),
         }
     }
 }
@@ -93,8 +127,38 @@ impl<'a> Iterator for ResidueIter<'a> {
                             )
                         })
                         .collect::<Vec<_>>(),
+                    metadata(res),
+                )
+            }),
+            &mut ResidueIter::Rotated(ref mut iter) => iter.next().map(|(res, coord, rotation)| {
+                let pivot = res
+                    .atoms
+                    .first()
+                    .map(|atom| atom.position)
+                    .unwrap_or(Coord::ORIGO);
+
+                ResidueIterOut::FromComp(
+                    Rc::new(RefCell::new(res.code.clone())),
+                    res.atoms
+                        .iter()
+                        .map(|atom| {
+                            (
+                                Rc::new(RefCell::new(atom.code.clone())),
+                                coord + pivot + rotation.apply(atom.position - pivot),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                    metadata(res),
                 )
             }),
         }
     }
 }
+
+fn metadata(residue: &Residue) -> Vec<AtomMetadata> {
+    residue
+        .atoms
+        .iter()
+        .map(|atom| atom.metadata.clone())
+        .collect()
+}
@@ -5,10 +5,19 @@ pub mod coord;
 #[macro_use]
 pub mod system;
 
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod database;
 pub mod describe;
+pub mod element;
 pub mod error;
 pub mod iterator;
+pub mod output;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod read_conf;
+pub mod read_residue;
+pub mod read_system;
+pub mod session;
 pub mod surface;
 pub mod volume;
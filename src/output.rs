@@ -0,0 +1,291 @@
+//! Write systems to configuration files.
+//!
+//! Every supported format implements `SystemWriter`, which writes a `System`
+//! to any `std::io::Write` object.
+//!
+//! # Examples
+//! Write a system as a GROMOS87 configuration to a buffer.
+//!
+//! ```
+//! # use grafen::database::DataBase;
+//! # use grafen::output::{Gromos87, SystemWriter};
+//! # use grafen::system::{BoxSetting, System};
+//! # use std::path::PathBuf;
+//! let system = System {
+//!     title: "An empty system".to_string(),
+//!     output_path: PathBuf::from("conf.gro"),
+//!     database: DataBase::new(),
+//!     components: vec![],
+//!     box_setting: BoxSetting::Auto,
+//! };
+//!
+//! let mut buffer = Vec::new();
+//! Gromos87.write(&system, &mut buffer).unwrap();
+//!
+//! assert!(String::from_utf8(buffer).unwrap().starts_with("An empty system\n0\n"));
+//! ```
+
+use crate::{
+    error::{GrafenError, Result},
+    system::{Component, System},
+};
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Write a `System` in a configuration file format.
+pub trait SystemWriter {
+    /// File extension of the format, without a leading dot.
+    fn extension(&self) -> &str;
+
+    /// Write the system to a writer.
+    ///
+    /// # Errors
+    /// Returns an error if the writer could not be written to.
+    fn write(&self, system: &System, writer: &mut dyn Write) -> io::Result<()>;
+}
+
+/// Get the writer for the format of a path from its extension.
+///
+/// Systems are written as PDB files if the extension is .pdb, otherwise
+/// as GROMOS87 files.
+pub fn writer_from_path(path: &Path) -> Box<dyn SystemWriter> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("pdb") => Box::new(Pdb),
+        _ => Box::new(Gromos87),
+    }
+}
+
+/// Write a system to a file using a writer.
+///
+/// The extension of the path is adjusted to that of the format.
+/// Returns the path of the written file.
+///
+/// # Errors
+/// Returns an `IoError` if the file could not be written to.
+pub fn write_system_to_file(
+    system: &System,
+    path: &Path,
+    format: &dyn SystemWriter,
+) -> Result<PathBuf> {
+    let path = path.with_extension(format.extension());
+
+    File::create(&path)
+        .map(BufWriter::new)
+        .and_then(|mut writer| {
+            format.write(system, &mut writer)?;
+            writer.flush()
+        })
+        .map_err(|err| GrafenError::io(&path, err))?;
+
+    Ok(path)
+}
+
+#[derive(Clone, Copy, Debug)]
+/// The GROMOS87 (.gro) format.
+pub struct Gromos87;
+
+impl SystemWriter for Gromos87 {
+    fn extension(&self) -> &str {
+        "gro"
+    }
+
+    fn write(&self, system: &System, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_fmt(format_args!("{}\n", system.title))?;
+        writer.write_fmt(format_args!("{}\n", system.num_atoms()))?;
+
+        let mut res_num_total = 1;
+        let mut atom_num_total = 1;
+
+        for component in &system.components {
+            let (x0, y0, z0) = component.get_origin().to_tuple();
+
+            for residue in component.iter_residues() {
+                let res_name = residue.get_residue();
+
+                for (atom_name, position) in residue.get_atoms() {
+                    // GROMOS loops the atom and residue indices at five digits, and so do we.
+                    let res_num = res_num_total % 100_000;
+                    let atom_num = atom_num_total % 100_000;
+
+                    let (x, y, z) = (x0 + position.x, y0 + position.y, z0 + position.z);
+
+                    write!(
+                        writer,
+                        "{:>5}{:<5}{:>5}{:>5}{:>8.3}{:>8.3}{:>8.3}\n",
+                        res_num,
+                        res_name.borrow(),
+                        atom_name.borrow(),
+                        atom_num,
+                        x,
+                        y,
+                        z
+                    )?;
+
+                    atom_num_total += 1;
+                }
+
+                res_num_total += 1;
+            }
+        }
+
+        let (dx, dy, dz) = system.box_size().to_tuple();
+        writer.write_fmt(format_args!("{:12.8} {:12.8} {:12.8}\n", dx, dy, dz))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// The PDB (.pdb) format. Positions are converted to Ångström and the element
+/// column is set from the atom metadata.
+pub struct Pdb;
+
+impl SystemWriter for Pdb {
+    fn extension(&self) -> &str {
+        "pdb"
+    }
+
+    fn write(&self, system: &System, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_fmt(format_args!("TITLE     {}\n", system.title))?;
+
+        let (dx, dy, dz) = system.box_size().to_tuple();
+        writer.write_fmt(format_args!(
+            "CRYST1{:9.3}{:9.3}{:9.3}{:7.2}{:7.2}{:7.2} P 1           1\n",
+            10.0 * dx,
+            10.0 * dy,
+            10.0 * dz,
+            90.0,
+            90.0,
+            90.0
+        ))?;
+
+        let mut res_num_total = 1;
+        let mut atom_num_total = 1;
+
+        for component in &system.components {
+            let origin = component.get_origin();
+
+            for residue in component.iter_residues() {
+                let res_name = residue.get_residue();
+                let metadata = residue.get_metadata();
+
+                for (i, (atom_name, position)) in residue.get_atoms().into_iter().enumerate() {
+                    // PDB loops the atom indices at five and residue indices at four digits.
+                    let res_num = res_num_total % 10_000;
+                    let atom_num = atom_num_total % 100_000;
+
+                    let (x, y, z) = ((origin + position) * 10.0).to_tuple();
+
+                    // Atom names shorter than four characters start in the second column
+                    let atom_name = atom_name.borrow();
+                    let atom_name = if atom_name.len() < 4 {
+                        format!(" {}", atom_name)
+                    } else {
+                        atom_name.clone()
+                    };
+
+                    let element = metadata
+                        .get(i)
+                        .and_then(|meta| meta.element.as_ref())
+                        .map(|element| element.to_uppercase())
+                        .unwrap_or_default();
+
+                    write!(
+                        writer,
+                        "ATOM  {:>5} {:<4} {:<4} {:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}          {:>2}\n",
+                        atom_num,
+                        atom_name,
+                        res_name.borrow(),
+                        res_num,
+                        x,
+                        y,
+                        z,
+                        1.0,
+                        0.0,
+                        element
+                    )?;
+
+                    atom_num_total += 1;
+                }
+
+                res_num_total += 1;
+            }
+        }
+
+        writer.write_fmt(format_args!("END\n"))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coord::Coord,
+        database::{ComponentEntry, DataBase},
+        system::{Atom, BoxSetting, Residue},
+        volume::Cuboid,
+    };
+
+    fn system_with_two_atoms() -> System {
+        let cuboid = Cuboid {
+            residue: Some(resbase!["RES", ("A", 0.0, 0.0, 0.0), ("B", 0.1, 0.0, 0.0)]),
+            origin: Coord::new(1.0, 2.0, 3.0),
+            size: Coord::new(2.0, 2.0, 2.0),
+            coords: vec![Coord::new(0.5, 0.5, 0.5)],
+            ..Cuboid::default()
+        };
+
+        System {
+            title: "Title".to_string(),
+            output_path: PathBuf::from("conf"),
+            database: DataBase::new(),
+            components: vec![ComponentEntry::from(cuboid)],
+            box_setting: BoxSetting::Auto,
+        }
+    }
+
+    #[test]
+    fn writer_is_selected_from_the_path_extension() {
+        assert_eq!(writer_from_path(Path::new("conf.pdb")).extension(), "pdb");
+        assert_eq!(writer_from_path(Path::new("conf.gro")).extension(), "gro");
+        assert_eq!(writer_from_path(Path::new("conf")).extension(), "gro");
+    }
+
+    #[test]
+    fn gromos87_writer_writes_atoms_with_the_component_origin() {
+        let mut buffer = Vec::new();
+        Gromos87
+            .write(&system_with_two_atoms(), &mut buffer)
+            .unwrap();
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "Title");
+        assert_eq!(lines[1], "2");
+        assert_eq!(lines[2], "    1RES      A    1   1.500   2.500   3.500");
+        assert_eq!(lines[3], "    1RES      B    2   1.600   2.500   3.500");
+    }
+
+    #[test]
+    fn pdb_writer_writes_atoms_in_angstrom() {
+        let mut buffer = Vec::new();
+        Pdb.write(&system_with_two_atoms(), &mut buffer).unwrap();
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("TITLE     Title"));
+        assert!(lines[1].starts_with("CRYST1"));
+        assert!(lines[2].starts_with("ATOM      1  A   RES     1      15.000  25.000  35.000"));
+        assert_eq!(lines[4], "END");
+    }
+}
@@ -0,0 +1,597 @@
+//! Python bindings for the library, built with the `python` feature. The extension
+//! module is built with the `extension-module` feature, eg. by maturin.
+//!
+//! The extension module `grafen` exposes the `DataBase` of definitions, systems
+//! which are constructed from them and the constructed components. Coordinates
+//! are returned as NumPy arrays of shape (N, 3) in nm.
+//!
+//! ```python
+//! import grafen
+//!
+//! system = grafen.System("Water on graphene", grafen.DataBase.library())
+//! system.add_sheet("Graphene", (0.0, 0.0, 0.0), 5.0, 5.0)
+//! system.fill_cuboid("Water (SPC/E)", (0.0, 0.0, 0.5), (5.0, 5.0, 3.0), mass_density=0.997)
+//! system.prune_against(0, 1, 0.3, policy="any")
+//!
+//! xs = system.coordinates()
+//! system.write("conf.gro")
+//! ```
+
+use crate::{
+    coord::{Coord, Translate},
+    database::{library, read_database, write_database, ComponentEntry, DataBase},
+    error::{self, GrafenError},
+    system::{Component as ComponentTrait, System as GrafenSystem},
+    volume::{keep_residues_within_volume, prune_residues_from_volume, FillType, InclusionPolicy},
+};
+
+use numpy::PyArray2;
+use pyo3::{
+    exceptions::{PyIOError, PyIndexError, PyTypeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+use std::path::PathBuf;
+
+/// Convert an error to `IOError` if a file could not be accessed, otherwise `ValueError`.
+fn to_py_err(err: GrafenError) -> PyErr {
+    match err {
+        GrafenError::IoError { .. } => PyIOError::new_err(err.to_string()),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
+
+fn to_coord((x, y, z): (f64, f64, f64)) -> Coord {
+    Coord::new(x, y, z)
+}
+
+/// Get the absolute positions of all atoms in a component as rows of (x, y, z).
+fn atom_positions(component: &ComponentEntry) -> Vec<Vec<f64>> {
+    let origin = component.get_origin();
+
+    component
+        .iter_residues()
+        .flat_map(|residue| residue.get_atoms().into_iter())
+        .map(|(_, position)| {
+            let (x, y, z) = (origin + position).to_tuple();
+            vec![x, y, z]
+        })
+        .collect()
+}
+
+fn to_array<'py>(py: Python<'py>, rows: &[Vec<f64>]) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    if rows.is_empty() {
+        return Ok(PyArray2::zeros(py, [0, 3], false));
+    }
+
+    PyArray2::from_vec2(py, rows).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Get a fill type from at most one of the keyword arguments `number`, `density`,
+/// `mass_density` and `molarity`.
+fn get_fill_type(fill: Option<&Bound<'_, PyDict>>) -> PyResult<Option<FillType>> {
+    let fill = match fill {
+        Some(fill) if !fill.is_empty() => fill,
+        _ => return Ok(None),
+    };
+
+    if fill.len() > 1 {
+        return Err(PyValueError::new_err("give at most one fill type"));
+    }
+
+    let (key, value) = fill.iter().next().unwrap();
+
+    match key.extract::<String>()?.as_str() {
+        "number" => Ok(Some(FillType::NumCoords(value.extract()?))),
+        "density" => Ok(Some(FillType::Density(value.extract()?))),
+        "mass_density" => Ok(Some(FillType::MassDensity(value.extract()?))),
+        "molarity" => Ok(Some(FillType::Molarity(value.extract()?))),
+        other => Err(PyTypeError::new_err(format!(
+            "unknown fill type '{}' (use number, density, mass_density or molarity)",
+            other
+        ))),
+    }
+}
+
+/// Discard the system which is returned by a builder method.
+fn build(result: error::Result<&mut GrafenSystem>) -> PyResult<()> {
+    result.map(|_| ()).map_err(to_py_err)
+}
+
+fn get_policy(policy: &str) -> PyResult<InclusionPolicy> {
+    policy
+        .parse::<InclusionPolicy>()
+        .map_err(PyValueError::new_err)
+}
+
+#[pyclass(name = "DataBase", unsendable, from_py_object)]
+#[derive(Clone)]
+/// A database of residue and component definitions.
+pub struct PyDataBase {
+    inner: DataBase,
+}
+
+#[pymethods]
+impl PyDataBase {
+    #[new]
+    fn new() -> Self {
+        PyDataBase {
+            inner: DataBase::new(),
+        }
+    }
+
+    #[staticmethod]
+    /// Read a database from a file.
+    fn read(path: &str) -> PyResult<Self> {
+        read_database(&PathBuf::from(path))
+            .map(|inner| PyDataBase { inner })
+            .map_err(to_py_err)
+    }
+
+    #[staticmethod]
+    /// Get the built-in library of definitions.
+    fn library() -> Self {
+        PyDataBase { inner: library() }
+    }
+
+    /// Write the database to a file.
+    fn write(&mut self, path: &str) -> PyResult<()> {
+        self.inner
+            .set_path(path)
+            .map_err(|_| PyValueError::new_err(format!("bad database path '{}'", path)))?;
+
+        write_database(&self.inner).map_err(to_py_err)
+    }
+
+    #[getter]
+    /// Codes of the residue definitions.
+    fn residues(&self) -> Vec<String> {
+        self.inner
+            .residue_defs
+            .iter()
+            .map(|residue| residue.code.clone())
+            .collect()
+    }
+
+    #[getter]
+    /// Names of the component definitions.
+    fn components(&self) -> Vec<String> {
+        self.inner
+            .component_defs
+            .iter()
+            .filter_map(|component| component.name())
+            .collect()
+    }
+}
+
+#[pyclass(name = "Component", unsendable)]
+#[derive(Clone)]
+/// A constructed component.
+pub struct PyComponent {
+    inner: ComponentEntry,
+}
+
+#[pymethods]
+impl PyComponent {
+    #[getter]
+    fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    #[getter]
+    fn num_atoms(&self) -> u64 {
+        self.inner.num_atoms()
+    }
+
+    #[getter]
+    fn origin(&self) -> (f64, f64, f64) {
+        self.inner.get_origin().to_tuple()
+    }
+
+    /// Get the positions of all atoms as an array of shape (N, 3).
+    fn coordinates<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        to_array(py, &atom_positions(&self.inner))
+    }
+
+    /// Translate the component.
+    fn translate(&mut self, shift: (f64, f64, f64)) {
+        self.inner.translate_in_place(to_coord(shift));
+    }
+
+    #[pyo3(signature = (volume, margin = 0.0, policy = "any"))]
+    /// Remove residues which overlap a volume component, with a margin around it.
+    /// The policy decides which residues are inside: "any", "all", "geometry", "mass"
+    /// or "first".
+    fn prune(&mut self, volume: PyRef<PyComponent>, margin: f64, policy: &str) -> PyResult<()> {
+        let policy = get_policy(policy)?;
+        let volume = volume
+            .inner
+            .to_volume(margin)
+            .ok_or_else(|| PyValueError::new_err("the component is not a volume"))?;
+
+        let remaining = prune_residues_from_volume(&self.inner, volume.as_ref(), policy);
+        self.inner.assign_residues(&remaining);
+
+        Ok(())
+    }
+
+    #[pyo3(signature = (volume, margin = 0.0, policy = "any"))]
+    /// Remove residues which are outside of a volume component, with a margin around it.
+    /// The policy decides which residues are inside, as for `prune`.
+    fn keep_within(
+        &mut self,
+        volume: PyRef<PyComponent>,
+        margin: f64,
+        policy: &str,
+    ) -> PyResult<()> {
+        let policy = get_policy(policy)?;
+        let volume = volume
+            .inner
+            .to_volume(margin)
+            .ok_or_else(|| PyValueError::new_err("the component is not a volume"))?;
+
+        let remaining = keep_residues_within_volume(&self.inner, volume.as_ref(), policy);
+        self.inner.assign_residues(&remaining);
+
+        Ok(())
+    }
+}
+
+#[pyclass(name = "System", unsendable)]
+/// A system of components constructed from database definitions.
+///
+/// Surfaces are constructed with the `add_*` methods and volumes are filled with
+/// the `fill_*` methods. A volume is filled by at most one of the keyword arguments
+/// `number`, `density`, `mass_density` (g/cm^3) or `molarity` (mol/L), or by the
+/// default fill type of its definition if none is given.
+pub struct PySystem {
+    inner: GrafenSystem,
+}
+
+#[pymethods]
+impl PySystem {
+    #[new]
+    #[pyo3(signature = (title, database = None))]
+    fn new(title: &str, database: Option<PyDataBase>) -> Self {
+        let database = database.map(|db| db.inner).unwrap_or_else(DataBase::new);
+
+        PySystem {
+            inner: GrafenSystem::new(title).with_database(database),
+        }
+    }
+
+    #[getter]
+    fn num_atoms(&self) -> u64 {
+        self.inner.num_atoms()
+    }
+
+    #[getter]
+    fn box_size(&self) -> (f64, f64, f64) {
+        self.inner.box_size().to_tuple()
+    }
+
+    #[getter]
+    /// Copies of the constructed components.
+    fn components(&self) -> Vec<PyComponent> {
+        self.inner
+            .components
+            .iter()
+            .map(|component| PyComponent {
+                inner: component.clone(),
+            })
+            .collect()
+    }
+
+    /// Add a copy of a constructed component.
+    fn add_component(&mut self, component: PyRef<PyComponent>) {
+        self.inner.add_component(component.inner.clone());
+    }
+
+    /// Construct a sheet from a definition and add it.
+    fn add_sheet(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        length: f64,
+        width: f64,
+    ) -> PyResult<()> {
+        build(self.inner.add_sheet(name, to_coord(origin), length, width))
+    }
+
+    /// Construct a sheet with a blue noise lattice of a number of residues from
+    /// a definition and add it.
+    fn add_blue_noise_sheet(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        length: f64,
+        width: f64,
+        number: u64,
+    ) -> PyResult<()> {
+        build(
+            self.inner
+                .add_blue_noise_sheet(name, to_coord(origin), length, width, number),
+        )
+    }
+
+    /// Construct a cuboid surface from a definition and add it.
+    fn add_surface_cuboid(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        size: (f64, f64, f64),
+    ) -> PyResult<()> {
+        build(
+            self.inner
+                .add_surface_cuboid(name, to_coord(origin), to_coord(size)),
+        )
+    }
+
+    /// Construct a cylinder surface from a definition and add it.
+    fn add_cylinder(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        radius: f64,
+        height: f64,
+    ) -> PyResult<()> {
+        build(
+            self.inner
+                .add_cylinder(name, to_coord(origin), radius, height),
+        )
+    }
+
+    /// Construct a spherical shell from a definition and add it.
+    fn add_sphere_shell(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        radius: f64,
+    ) -> PyResult<()> {
+        build(self.inner.add_sphere_shell(name, to_coord(origin), radius))
+    }
+
+    /// Construct an ellipsoidal shell from a definition and add it.
+    fn add_ellipsoid_shell(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        semi_axes: (f64, f64, f64),
+    ) -> PyResult<()> {
+        build(
+            self.inner
+                .add_ellipsoid_shell(name, to_coord(origin), to_coord(semi_axes)),
+        )
+    }
+
+    /// Construct a lipid bilayer from a definition and add it.
+    fn add_bilayer(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        length: f64,
+        width: f64,
+    ) -> PyResult<()> {
+        build(
+            self.inner
+                .add_bilayer(name, to_coord(origin), length, width),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, size, **fill))]
+    /// Fill a cuboid from a definition and add it.
+    fn fill_cuboid(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        size: (f64, f64, f64),
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_cuboid(name, to_coord(origin), to_coord(size), fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, radius, height, **fill))]
+    /// Fill a cylinder from a definition and add it.
+    fn fill_cylinder(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        radius: f64,
+        height: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_cylinder(name, to_coord(origin), radius, height, fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, radius, **fill))]
+    /// Fill a sphere from a definition and add it.
+    fn fill_sphere(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        radius: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_sphere(name, to_coord(origin), radius, fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, semi_axes, **fill))]
+    /// Fill an ellipsoid from a definition and add it.
+    fn fill_ellipsoid(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        semi_axes: (f64, f64, f64),
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_ellipsoid(name, to_coord(origin), to_coord(semi_axes), fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, bottom_radius, top_radius, height, **fill))]
+    /// Fill a cone, which is truncated if it has a top radius, from a definition and add it.
+    fn fill_cone(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        bottom_radius: f64,
+        top_radius: f64,
+        height: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(self.inner.fill_cone(
+            name,
+            to_coord(origin),
+            bottom_radius,
+            top_radius,
+            height,
+            fill_type,
+        ))
+    }
+
+    #[pyo3(signature = (name, origin, major_radius, minor_radius, **fill))]
+    /// Fill a torus from a definition and add it.
+    fn fill_torus(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        major_radius: f64,
+        minor_radius: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(self.inner.fill_torus(
+            name,
+            to_coord(origin),
+            major_radius,
+            minor_radius,
+            fill_type,
+        ))
+    }
+
+    #[pyo3(signature = (name, origin, radius, height, **fill))]
+    /// Fill a capsule from a definition and add it.
+    fn fill_capsule(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        radius: f64,
+        height: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_capsule(name, to_coord(origin), radius, height, fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, thickness, length, width, **fill))]
+    /// Fill a region of a slab from a definition and add it.
+    fn fill_slab(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        thickness: f64,
+        length: f64,
+        width: f64,
+        fill: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let fill_type = get_fill_type(fill)?;
+        build(
+            self.inner
+                .fill_slab(name, to_coord(origin), thickness, length, width, fill_type),
+        )
+    }
+
+    #[pyo3(signature = (name, origin, policy = "any"))]
+    /// Read the configuration file of a definition, reconstruct it into the volume
+    /// of the definition and add it. Residues are kept by the inclusion policy.
+    fn add_configuration(
+        &mut self,
+        name: &str,
+        origin: (f64, f64, f64),
+        policy: &str,
+    ) -> PyResult<()> {
+        let policy = get_policy(policy)?;
+        build(
+            self.inner
+                .add_configuration(name, to_coord(origin), None, policy),
+        )
+    }
+
+    #[pyo3(signature = (index, volume_index, margin = 0.0, policy = "any"))]
+    /// Remove the residues of a component which are inside a volume component by
+    /// the inclusion policy: any, all, geometry, mass or first. Raises an `IndexError`
+    /// if either component does not exist.
+    fn prune_against(
+        &mut self,
+        index: usize,
+        volume_index: usize,
+        margin: f64,
+        policy: &str,
+    ) -> PyResult<()> {
+        let policy = get_policy(policy)?;
+
+        for &i in &[index, volume_index] {
+            if i >= self.inner.components.len() {
+                return Err(PyIndexError::new_err(format!("no component {}", i)));
+            }
+        }
+
+        self.inner
+            .prune_against(index, volume_index, margin, policy)
+            .map(|_| ())
+            .map_err(to_py_err)
+    }
+
+    /// Get the positions of all atoms in the system as an array of shape (N, 3).
+    fn coordinates<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        let rows = self
+            .inner
+            .components
+            .iter()
+            .flat_map(|component| atom_positions(component).into_iter())
+            .collect::<Vec<_>>();
+
+        to_array(py, &rows)
+    }
+
+    /// Write the system to a file, in the format of the path extension.
+    ///
+    /// Returns the path of the written file.
+    fn write(&mut self, path: &str) -> PyResult<String> {
+        self.inner.output_path = PathBuf::from(path);
+
+        self.inner
+            .write()
+            .map(|path| path.to_string_lossy().into_owned())
+            .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+/// Create substrates and other systems for molecular dynamics simulations.
+fn grafen(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyDataBase>()?;
+    module.add_class::<PyComponent>()?;
+    module.add_class::<PySystem>()?;
+
+    Ok(())
+}
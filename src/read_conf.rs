@@ -1,14 +1,16 @@
 use crate::{
     coord::{Coord, Direction, Translate},
     describe::Describe,
+    error::{GrafenError, Result},
     iterator::{ConfIter, ResidueIter, ResidueIterOut},
-    system::Component,
-    volume::{keep_residues_within_volume, Contains},
+    system::{AtomMetadata, Component},
+    volume::{get_center, keep_residues_within_volume, Contains, InclusionPolicy},
 };
 
 use mdio;
 use serde_derive::{Deserialize, Serialize};
 use std::{
+    fs,
     path::{Path, PathBuf},
     rc::Rc,
 };
@@ -18,13 +20,13 @@ use std::{
 pub enum ConfType {
     /// The cuboid uses a specific size.
     Cuboid {
-        #[serde(skip_deserializing)]
+        #[serde(default)]
         origin: Coord,
         size: Coord,
     },
     /// The cylinder requires some data about its construction.
     Cylinder {
-        #[serde(skip_deserializing)]
+        #[serde(default)]
         origin: Coord,
         radius: f64,
         height: f64,
@@ -32,7 +34,7 @@ pub enum ConfType {
     },
     /// The sphere requires some data about its construction.
     Spheroid {
-        #[serde(skip_deserializing)]
+        #[serde(default)]
         origin: Coord,
         radius: f64,
     },
@@ -99,6 +101,41 @@ impl Contains for ConfType {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The position of a residue which decides where it is wrapped into a periodic box.
+pub enum WrapCenter {
+    /// The position of the first atom.
+    FirstAtom,
+    /// The center of geometry of all atoms.
+    Geometry,
+}
+
+impl WrapCenter {
+    fn get_center(&self, positions: &[Coord]) -> Coord {
+        match *self {
+            WrapCenter::FirstAtom => positions.first().cloned().unwrap_or(Coord::ORIGO),
+            WrapCenter::Geometry => get_center(positions, None).unwrap_or(Coord::ORIGO),
+        }
+    }
+}
+
+/// Get the shortest vector from one position to another in a periodic box.
+fn minimum_image(delta: Coord, box_size: Coord) -> Coord {
+    let shift = |d: f64, size: f64| {
+        if size > 0.0 {
+            d - size * (d / size).round()
+        } else {
+            d
+        }
+    };
+
+    Coord::new(
+        shift(delta.x, box_size.x),
+        shift(delta.y, box_size.y),
+        shift(delta.z, box_size.z),
+    )
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 /// Wrap a configuration that is read from disk into an object we can handle.
 pub struct ReadConf {
@@ -122,8 +159,18 @@ pub struct ReadConf {
 impl ReadConf {
     /// Read a configuration from a GROMOS87 formatted file. Set its description to
     /// the title of the configuration file, and the path to that input.
-    pub fn from_gromos87(path: &Path) -> Result<ReadConf, String> {
-        let conf = mdio::Conf::from_gromos87(path).map_err(|err| err.to_string())?;
+    ///
+    /// # Errors
+    /// Returns an `IoError` if the file cannot be accessed or a `ParseError`
+    /// if it could not be read as a configuration.
+    pub fn from_gromos87(path: &Path) -> Result<ReadConf> {
+        fs::metadata(path).map_err(|err| GrafenError::io(path, err))?;
+
+        let conf = mdio::Conf::from_gromos87(path).map_err(|err| GrafenError::ParseError {
+            path: Some(path.to_path_buf()),
+            line: None,
+            message: err.to_string(),
+        })?;
 
         let description = conf.title.clone();
         let origin = Coord::ORIGO;
@@ -170,7 +217,73 @@ impl ReadConf {
         }
     }
 
-    pub fn reconstruct(&mut self, new_conf_type: ConfType) {
+    /// Make residues which are broken over the periodic box of the read configuration
+    /// whole. Every atom is moved to its closest periodic image of the previous atom
+    /// in the residue.
+    pub fn make_whole(&mut self) {
+        let box_size = match self.conf {
+            Some(ref conf) => Coord::from(conf.size),
+            None => return,
+        };
+
+        self.map_residue_positions(|positions| {
+            let mut whole: Vec<Coord> = Vec::with_capacity(positions.len());
+
+            for &position in positions {
+                let next = match whole.last() {
+                    Some(&previous) => previous + minimum_image(position - previous, box_size),
+                    None => position,
+                };
+
+                whole.push(next);
+            }
+
+            whole
+        });
+    }
+
+    /// Wrap residues into the box of the component, which is set by the volume type.
+    /// Residues are kept whole and moved by the periodic image of their center.
+    pub fn wrap_residues(&mut self, center: WrapCenter) {
+        let box_size = self.calc_size();
+
+        self.map_residue_positions(|positions| {
+            let center = center.get_center(positions);
+            let shift = center.with_pbc(box_size) - center;
+
+            positions.iter().map(|&position| position + shift).collect()
+        });
+    }
+
+    /// Replace the atom positions of every residue using a function.
+    fn map_residue_positions<F>(&mut self, f: F)
+    where
+        F: Fn(&[Coord]) -> Vec<Coord>,
+    {
+        let residues = self
+            .iter_residues()
+            .map(|residue| {
+                let (names, positions): (Vec<_>, Vec<_>) = residue.get_atoms().into_iter().unzip();
+                let num_atoms = names.len();
+
+                ResidueIterOut::FromComp(
+                    residue.get_residue(),
+                    names.into_iter().zip(f(&positions).into_iter()).collect(),
+                    vec![AtomMetadata::default(); num_atoms],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.assign_residues(&residues);
+    }
+
+    /// Reconstruct the configuration into a new volume type. The configuration is
+    /// replicated as many times as needed to fill the volume, then cut to it.
+    ///
+    /// Broken residues are made whole before the configuration is replicated,
+    /// to not cut them into pieces. Residues are kept if they are inside of the
+    /// volume by the inclusion policy.
+    pub fn reconstruct(&mut self, new_conf_type: ConfType, policy: InclusionPolicy) {
         // Ensure that the volume we want to create has our origin.
         let new_conf_type = match new_conf_type {
             ConfType::Cuboid { origin: _, size } => ConfType::Cuboid {
@@ -194,6 +307,8 @@ impl ReadConf {
             },
         };
 
+        self.make_whole();
+
         if let Some(conf) = self.conf.as_ref() {
             let mut current_size = self.calc_size();
 
@@ -212,7 +327,7 @@ impl ReadConf {
             self.conf = Some(conf.pbc_multiply(nx, ny, nz));
         }
 
-        let contained_residues = keep_residues_within_volume(self, &new_conf_type);
+        let contained_residues = keep_residues_within_volume(self, &new_conf_type, policy);
         self.assign_residues(&contained_residues);
 
         self.volume_type = new_conf_type;
@@ -277,9 +392,9 @@ impl<'a> Component<'a> for ReadConf {
         self.conf.as_ref().map(|c| c.atoms.len()).unwrap_or(0) as u64
     }
 
-    /// Currently does nothing at all.
-    fn with_pbc(self) -> Self {
-        eprintln!("Warning: PBC adjustment has not been added to read configurations.");
+    /// Wrap residues into the box by their center of geometry, keeping them whole.
+    fn with_pbc(mut self) -> Self {
+        self.wrap_residues(WrapCenter::Geometry);
         self
     }
 }
@@ -375,6 +490,7 @@ impl Translate for ReadConf {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::volume::num_residues_without_mass;
     use mdio::RVec;
     use std::cell::RefCell;
     use std::rc::Rc;
@@ -907,7 +1023,7 @@ pub mod tests {
             size: new_size,
         };
 
-        cuboid.reconstruct(new_volume);
+        cuboid.reconstruct(new_volume, InclusionPolicy::AnyAtom);
 
         eprintln!("{:?}", cuboid.conf.as_ref().unwrap().atoms);
         assert_eq!(cuboid.calc_size(), new_size);
@@ -1195,4 +1311,132 @@ pub mod tests {
         cuboid.translate_in_place(-origin);
         assert_eq!(cuboid.get_origin(), target);
     }
+
+    /// Create a configuration of one residue with two atoms along x in a box of size 2.
+    fn get_conf_with_residue(x0: f64, x1: f64) -> ReadConf {
+        get_conf_with_named_residue(["A1", "A2"], x0, x1)
+    }
+
+    /// Create a configuration of one residue with two named atoms along x in a box
+    /// of size 2.
+    fn get_conf_with_named_residue(names: [&str; 2], x0: f64, x1: f64) -> ReadConf {
+        let residue = Rc::new(RefCell::new(mdio::Residue {
+            name: Rc::new(RefCell::new("RES".to_string())),
+            atoms: names
+                .iter()
+                .map(|name| Rc::new(RefCell::new(name.to_string())))
+                .collect(),
+        }));
+
+        let atom = |index: usize, x: f64| mdio::Atom {
+            name: Rc::clone(&residue.borrow().atoms[index]),
+            residue: Rc::clone(&residue),
+            position: RVec { x, y: 1.0, z: 1.0 },
+            velocity: None,
+        };
+
+        let conf = mdio::Conf {
+            title: "A title".to_string(),
+            origin: RVec {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            size: RVec {
+                x: 2.0,
+                y: 2.0,
+                z: 2.0,
+            },
+            residues: vec![Rc::clone(&residue)],
+            atoms: vec![atom(0, x0), atom(1, x1)],
+        };
+
+        ReadConf {
+            conf: Some(conf),
+            backup_conf: None,
+            path: PathBuf::from(""),
+            description: String::new(),
+            volume_type: ConfType::Cuboid {
+                origin: Coord::ORIGO,
+                size: Coord::new(2.0, 2.0, 2.0),
+            },
+        }
+    }
+
+    fn get_positions(conf: &ReadConf) -> Vec<f64> {
+        conf.iter_residues()
+            .flat_map(|residue| residue.get_atoms().into_iter())
+            .map(|(_, position)| position.x)
+            .collect()
+    }
+
+    fn assert_positions(conf: &ReadConf, expected: &[f64]) {
+        let positions = get_positions(conf);
+        assert_eq!(positions.len(), expected.len());
+
+        for (x, x0) in positions.iter().zip(expected.iter()) {
+            assert!((x - x0).abs() < 1e-9, "{:?} != {:?}", positions, expected);
+        }
+    }
+
+    #[test]
+    fn make_whole_moves_atoms_to_the_closest_image_of_the_previous_atom() {
+        let mut conf = get_conf_with_residue(0.1, 1.9);
+        conf.make_whole();
+        assert_positions(&conf, &[0.1, -0.1]);
+
+        let mut conf = get_conf_with_residue(0.4, 0.8);
+        conf.make_whole();
+        assert_positions(&conf, &[0.4, 0.8]);
+    }
+
+    #[test]
+    fn atoms_of_read_configurations_have_masses_from_their_guessed_elements() {
+        let conf = get_conf_with_named_residue(["OW", "HW1"], 0.2, 1.0);
+        let residue = conf.iter_residues().next().unwrap();
+
+        let masses = residue
+            .get_metadata()
+            .iter()
+            .map(|meta| meta.mass())
+            .collect::<Vec<_>>();
+
+        assert_eq!(masses, vec![Some(15.999), Some(1.008)]);
+        assert_eq!(num_residues_without_mass(&conf), 0);
+
+        let unknown = get_conf_with_named_residue(["OW", "XW"], 0.2, 1.0);
+        assert_eq!(num_residues_without_mass(&unknown), 1);
+    }
+
+    #[test]
+    fn cutting_read_configurations_by_center_of_mass_uses_the_atom_masses() {
+        // The center of mass is close to the heavy oxygen at x = 0.2, at about x = 0.25,
+        // while the center of geometry is at x = 0.6.
+        let conf = get_conf_with_named_residue(["OW", "HW1"], 0.2, 1.0);
+        let volume = ConfType::Cuboid {
+            origin: Coord::ORIGO,
+            size: Coord::new(0.5, 2.0, 2.0),
+        };
+
+        let by_mass = keep_residues_within_volume(&conf, &volume, InclusionPolicy::CenterOfMass);
+        assert_eq!(by_mass.len(), 1);
+
+        let by_geometry =
+            keep_residues_within_volume(&conf, &volume, InclusionPolicy::CenterOfGeometry);
+        assert!(by_geometry.is_empty());
+    }
+
+    #[test]
+    fn wrap_residues_moves_whole_residues_by_their_center() {
+        let mut conf = get_conf_with_residue(1.9, 2.3);
+        conf.wrap_residues(WrapCenter::Geometry);
+        assert_positions(&conf, &[-0.1, 0.3]);
+
+        let mut conf = get_conf_with_residue(1.9, 2.3);
+        conf.wrap_residues(WrapCenter::FirstAtom);
+        assert_positions(&conf, &[1.9, 2.3]);
+
+        let conf = get_conf_with_residue(-1.9, -1.7).with_pbc();
+        assert_positions(&conf, &[0.1, 0.3]);
+    }
 }
@@ -0,0 +1,462 @@
+//! Read residue definitions from structure files.
+//!
+//! Supported formats are GROMOS87 (.gro), PDB (.pdb) and XYZ (.xyz) files
+//! which contain a single residue. Positions in PDB and XYZ files are given
+//! in Ångström and are converted to nm.
+
+use crate::{
+    coord::Coord,
+    error::{GrafenError, Result},
+    system::{Atom, AtomMetadata, Residue},
+};
+
+use mdio;
+use serde_derive::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Conversion factor from Ångström to nm.
+const ANGSTROM_TO_NM: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Translation applied to the atoms of a read residue.
+pub enum Centering {
+    /// Keep the atom positions as they are in the file.
+    None,
+    /// Place the first atom at the origin.
+    FirstAtom,
+    /// Place the center of geometry of all atoms at the origin.
+    CenterOfGeometry,
+}
+
+/// Read a residue from a structure file and center its atoms.
+///
+/// The file format is determined from the file extension.
+///
+/// # Errors
+/// Returns an error if the file could not be read or parsed, if the format
+/// is not supported, or if it does not contain exactly one residue.
+pub fn read_residue(path: &Path, centering: Centering) -> Result<Residue> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    let residue = match extension.as_ref().map(|ext| ext.as_str()) {
+        Some("gro") => read_gromos87(path)?,
+        Some("pdb") => parse_pdb(&read_to_string(path)?).map_err(|err| add_path(err, path))?,
+        Some("xyz") => {
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("RES")
+                .to_uppercase();
+
+            parse_xyz(&read_to_string(path)?, &name).map_err(|err| add_path(err, path))?
+        }
+        _ => {
+            return Err(GrafenError::ParseError {
+                path: Some(path.to_path_buf()),
+                line: None,
+                message: "unsupported file format (supported: .gro, .pdb, .xyz)".to_string(),
+            });
+        }
+    };
+
+    Ok(center_residue(residue, centering))
+}
+
+/// Translate the atoms of a residue according to a centering.
+pub fn center_residue(mut residue: Residue, centering: Centering) -> Residue {
+    let center = match centering {
+        Centering::None => return residue,
+        Centering::FirstAtom => residue
+            .atoms
+            .first()
+            .map(|atom| atom.position)
+            .unwrap_or(Coord::ORIGO),
+        Centering::CenterOfGeometry => {
+            let num_atoms = residue.atoms.len().max(1) as f64;
+            let sum = residue
+                .atoms
+                .iter()
+                .fold(Coord::ORIGO, |acc, atom| acc + atom.position);
+
+            Coord::new(sum.x / num_atoms, sum.y / num_atoms, sum.z / num_atoms)
+        }
+    };
+
+    residue
+        .atoms
+        .iter_mut()
+        .for_each(|atom| atom.position -= center);
+
+    residue
+}
+
+fn read_to_string(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| GrafenError::io(path, err))
+}
+
+/// Set the path of a `ParseError` which was returned from parsing the content of a file.
+fn add_path(err: GrafenError, path: &Path) -> GrafenError {
+    match err {
+        GrafenError::ParseError {
+            path: None,
+            line,
+            message,
+        } => GrafenError::ParseError {
+            path: Some(path.to_path_buf()),
+            line,
+            message,
+        },
+        err => err,
+    }
+}
+
+/// Read a residue from a GROMOS87 formatted file.
+fn read_gromos87(path: &Path) -> Result<Residue> {
+    let conf = mdio::Conf::from_gromos87(path).map_err(|err| GrafenError::ParseError {
+        path: Some(path.to_path_buf()),
+        line: None,
+        message: err.to_string(),
+    })?;
+
+    check_gromos87_residue_numbers(&read_to_string(path)?).map_err(|err| add_path(err, path))?;
+
+    let mut residues = conf.iter_residues();
+
+    let atoms = match residues.next() {
+        Some(Ok(atoms)) => atoms,
+        Some(Err(err)) => {
+            return Err(GrafenError::ParseError {
+                path: Some(path.to_path_buf()),
+                line: None,
+                message: err.to_string(),
+            });
+        }
+        None => return Err(add_path(no_atoms_error(), path)),
+    };
+
+    if residues.next().is_some() {
+        return Err(GrafenError::ParseError {
+            path: Some(path.to_path_buf()),
+            line: None,
+            message: "the file contains more than one residue".to_string(),
+        });
+    }
+
+    let code = atoms[0].residue.borrow().name.borrow().clone();
+    let atoms = atoms
+        .iter()
+        .map(|atom| Atom {
+            code: atom.name.borrow().clone(),
+            position: Coord::from(atom.position),
+            metadata: AtomMetadata::default(),
+        })
+        .collect();
+
+    Ok(Residue { code, atoms })
+}
+
+/// Assert that all atoms of GROMOS87 formatted content have the same residue number
+/// and name. Consecutive residues with the same name are otherwise read as one.
+fn check_gromos87_residue_numbers(content: &str) -> Result<()> {
+    let mut lines = content.lines().skip(1);
+
+    let num_atoms = lines
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok())
+        .ok_or_else(|| parse_error("GROMOS87", 2, "could not parse the number of atoms"))?;
+
+    let mut residue: Option<(&str, &str)> = None;
+
+    for (i, line) in lines.take(num_atoms).enumerate() {
+        let current = (
+            line.get(0..5).unwrap_or("").trim(),
+            line.get(5..10).unwrap_or("").trim(),
+        );
+
+        match residue {
+            None => residue = Some(current),
+            Some(previous) if previous != current => {
+                return Err(parse_error(
+                    "GROMOS87",
+                    i + 3,
+                    "the file contains more than one residue",
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a residue from the `ATOM` and `HETATM` records of PDB formatted content.
+///
+/// Only the first model of the file is read.
+fn parse_pdb(content: &str) -> Result<Residue> {
+    let mut residue: Option<(String, (String, String))> = None;
+    let mut atoms = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        // Both `END` and `ENDMDL` records end the first model
+        if line.starts_with("END") {
+            break;
+        }
+
+        if !(line.starts_with("ATOM") || line.starts_with("HETATM")) {
+            continue;
+        }
+
+        let field = |start: usize, end: usize| -> Result<&str> {
+            line.get(start..end.min(line.len()))
+                .map(|s| s.trim())
+                .ok_or_else(|| parse_error("PDB", i + 1, "the record is too short"))
+        };
+
+        let parse_position = |start: usize, end: usize| -> Result<f64> {
+            field(start, end)?
+                .parse::<f64>()
+                .map(|value| value * ANGSTROM_TO_NM)
+                .map_err(|_| parse_error("PDB", i + 1, "could not parse a position"))
+        };
+
+        let atom_name = field(12, 16)?.to_string();
+        let element = line
+            .get(76..78.min(line.len()))
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        // Residue names of four characters extend into the otherwise blank column 21
+        let residue_name = field(17, 21)?.to_string();
+        // Residues with the same name are told apart by their chain and sequence number
+        let residue_id = (field(21, 22)?.to_string(), field(22, 26)?.to_string());
+        let position = Coord::new(
+            parse_position(30, 38)?,
+            parse_position(38, 46)?,
+            parse_position(46, 54)?,
+        );
+
+        match residue {
+            None => residue = Some((residue_name, residue_id)),
+            Some((ref name, ref id)) if name != &residue_name || id != &residue_id => {
+                return Err(parse_error(
+                    "PDB",
+                    i + 1,
+                    "the file contains more than one residue",
+                ));
+            }
+            _ => (),
+        }
+
+        atoms.push(Atom {
+            code: atom_name,
+            position,
+            metadata: AtomMetadata {
+                element,
+                ..AtomMetadata::default()
+            },
+        });
+    }
+
+    match residue {
+        Some((code, _)) => Ok(Residue { code, atoms }),
+        None => Err(no_atoms_error()),
+    }
+}
+
+/// Parse a residue with the input name from XYZ formatted content.
+fn parse_xyz(content: &str, name: &str) -> Result<Residue> {
+    let mut lines = content.lines();
+
+    let num_atoms = lines
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok())
+        .ok_or_else(|| parse_error("XYZ", 1, "could not parse the number of atoms"))?;
+
+    // The second line is a comment
+    lines.next();
+
+    let atoms = lines
+        .take(num_atoms)
+        .enumerate()
+        .map(|(i, line)| {
+            let values = line.split_whitespace().collect::<Vec<_>>();
+
+            if values.len() < 4 {
+                return Err(parse_error(
+                    "XYZ",
+                    i + 3,
+                    "expected an atom name and position",
+                ));
+            }
+
+            let mut position = values[1..4].iter().map(|value| {
+                value
+                    .parse::<f64>()
+                    .map(|v| v * ANGSTROM_TO_NM)
+                    .map_err(|_| parse_error("XYZ", i + 3, "could not parse a position"))
+            });
+
+            let (x, y, z) = (
+                position.next().unwrap()?,
+                position.next().unwrap()?,
+                position.next().unwrap()?,
+            );
+
+            Ok(Atom {
+                code: values[0].to_string(),
+                position: Coord::new(x, y, z),
+                metadata: AtomMetadata {
+                    element: Some(values[0].to_string()),
+                    ..AtomMetadata::default()
+                },
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if atoms.len() < num_atoms {
+        return Err(parse_error(
+            "XYZ",
+            atoms.len() + 3,
+            "the file has fewer atoms than specified",
+        ));
+    }
+
+    if atoms.is_empty() {
+        return Err(no_atoms_error());
+    }
+
+    Ok(Residue {
+        code: name.to_string(),
+        atoms,
+    })
+}
+
+fn parse_error(format: &str, line: usize, message: &str) -> GrafenError {
+    GrafenError::ParseError {
+        path: None,
+        line: Some(line),
+        message: format!("{} file: {}", format, message),
+    }
+}
+
+fn no_atoms_error() -> GrafenError {
+    GrafenError::ParseError {
+        path: None,
+        line: None,
+        message: "the file contains no atoms".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PDB: &str = "\
+REMARK    a water molecule
+ATOM      1  OW  SOL     1      10.000  20.000  30.000  1.00  0.00           O
+ATOM      2  HW1 SOL     1      11.000  20.000  30.000  1.00  0.00           H
+HETATM    3  HW2 SOL     1      10.000  21.000  30.000  1.00  0.00           H
+END
+";
+
+    #[test]
+    fn parse_pdb_residue_converts_to_nm() {
+        let residue = parse_pdb(PDB).unwrap();
+
+        assert_eq!(residue.code, "SOL");
+        assert_eq!(residue.atoms.len(), 3);
+        assert_eq!(residue.atoms[0].code, "OW");
+        assert_eq!(residue.atoms[0].position, Coord::new(1.0, 2.0, 3.0));
+        assert_eq!(residue.atoms[2].code, "HW2");
+        assert_eq!(residue.atoms[2].position, Coord::new(1.0, 2.1, 3.0));
+        assert_eq!(residue.atoms[2].metadata.element, Some("H".to_string()));
+    }
+
+    #[test]
+    fn parse_pdb_with_several_residues_is_an_error() {
+        let content = "\
+ATOM      1  OW  SOL     1      10.000  20.000  30.000
+ATOM      2  NA  NA      2      11.000  20.000  30.000
+";
+        assert!(parse_pdb(content).is_err());
+        assert!(parse_pdb("REMARK nothing\n").is_err());
+    }
+
+    #[test]
+    fn parse_pdb_with_several_residues_of_the_same_name_is_an_error() {
+        let consecutive = "\
+ATOM      1  OW  SOL     1      10.000  20.000  30.000
+ATOM      2  OW  SOL     2      11.000  20.000  30.000
+";
+        assert!(parse_pdb(consecutive).is_err());
+
+        let chains = "\
+ATOM      1  OW  SOL A   1      10.000  20.000  30.000
+ATOM      2  OW  SOL B   1      11.000  20.000  30.000
+";
+        match parse_pdb(chains) {
+            Err(GrafenError::ParseError { line, .. }) => assert_eq!(line, Some(2)),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn gromos87_with_several_residues_of_the_same_name_is_an_error() {
+        let single = "Water\n    2\n    1SOL     OW    1   0.100   0.100   0.100\n    1SOL    HW1    2   0.200   0.100   0.100\n   1.0 1.0 1.0\n";
+        assert!(check_gromos87_residue_numbers(single).is_ok());
+
+        let consecutive = "Water\n    2\n    1SOL     OW    1   0.100   0.100   0.100\n    2SOL     OW    2   0.200   0.100   0.100\n   1.0 1.0 1.0\n";
+        match check_gromos87_residue_numbers(consecutive) {
+            Err(GrafenError::ParseError { line, .. }) => assert_eq!(line, Some(4)),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_xyz_residue_converts_to_nm() {
+        let content = "3\nwater\nO 0.0 0.0 0.0\nH 1.0 0.0 0.0\nH 0.0 1.0 0.0\n";
+        let residue = parse_xyz(content, "SOL").unwrap();
+
+        assert_eq!(residue.code, "SOL");
+        assert_eq!(residue.atoms.len(), 3);
+        assert_eq!(residue.atoms[1].code, "H");
+        assert_eq!(residue.atoms[1].position, Coord::new(0.1, 0.0, 0.0));
+        assert_eq!(residue.atoms[1].metadata.element, Some("H".to_string()));
+    }
+
+    #[test]
+    fn parse_xyz_with_missing_atoms_or_bad_values_is_an_error() {
+        assert!(parse_xyz("3\nwater\nO 0.0 0.0 0.0\n", "SOL").is_err());
+        assert!(parse_xyz("1\nwater\nO 0.0 a 0.0\n", "SOL").is_err());
+        assert!(parse_xyz("one\nwater\nO 0.0 0.0 0.0\n", "SOL").is_err());
+
+        match parse_xyz("1\nwater\nO 0.0 a 0.0\n", "SOL") {
+            Err(GrafenError::ParseError { line, .. }) => assert_eq!(line, Some(3)),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn center_residue_on_first_atom_or_center_of_geometry() {
+        let residue = resbase!["RES", ("A", 1.0, 1.0, 1.0), ("B", 3.0, 1.0, 1.0)];
+
+        let uncentered = center_residue(residue.clone(), Centering::None);
+        assert_eq!(uncentered, residue);
+
+        let first = center_residue(residue.clone(), Centering::FirstAtom);
+        assert_eq!(first.atoms[0].position, Coord::ORIGO);
+        assert_eq!(first.atoms[1].position, Coord::new(2.0, 0.0, 0.0));
+
+        let geometric = center_residue(residue.clone(), Centering::CenterOfGeometry);
+        assert_eq!(geometric.atoms[0].position, Coord::new(-1.0, 0.0, 0.0));
+        assert_eq!(geometric.atoms[1].position, Coord::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn reading_residue_from_unsupported_format_is_an_error() {
+        assert!(read_residue(Path::new("residue.mol2"), Centering::None).is_err());
+    }
+}
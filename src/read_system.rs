@@ -0,0 +1,332 @@
+//! Read full systems and split them into components.
+//!
+//! A configuration which has been read from disk is a single component. Systems
+//! which were created earlier can instead be split into several components, which
+//! can then be edited, pruned or replaced separately from each other. The residues
+//! of the configuration are split by a `SplitRule`.
+//!
+//! Every part is a `ReadConf` with the residues of its group, named after the group
+//! and using the box of the full configuration.
+
+use crate::{
+    database::ComponentEntry,
+    error::{GrafenError, Result},
+    iterator::ResidueIterOut,
+    read_conf::ReadConf,
+    system::{Component, System},
+};
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the component which contains residues which are not in any group.
+pub const UNGROUPED_NAME: &str = "Other";
+
+#[derive(Clone, Debug, PartialEq)]
+/// A group of atoms in an index file.
+pub struct IndexGroup {
+    /// Name of the group.
+    pub name: String,
+    /// Atom numbers of the group, starting from 1.
+    pub atoms: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A group of residues selected by their names.
+pub struct ResidueGroup {
+    /// Name of the group.
+    pub name: String,
+    /// Names of the residues in the group.
+    pub residues: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// How to split the residues of a configuration into components.
+pub enum SplitRule {
+    /// Every block of consecutive residues with the same name is a component.
+    ResidueBlocks,
+    /// Every index group is a component. Residues belong to the first group which
+    /// contains their first atom.
+    IndexGroups(Vec<IndexGroup>),
+    /// Every group of residue names is a component. Residues belong to the first
+    /// group which contains their name.
+    ResidueGroups(Vec<ResidueGroup>),
+}
+
+impl SplitRule {
+    /// Get the index of the group which a residue belongs to, if any.
+    fn find_group(
+        &self,
+        name: &str,
+        first_atom: usize,
+        index_sets: &[HashSet<usize>],
+    ) -> Option<usize> {
+        match self {
+            &SplitRule::ResidueBlocks => None,
+            &SplitRule::IndexGroups(_) => index_sets
+                .iter()
+                .position(|atoms| atoms.contains(&first_atom)),
+            &SplitRule::ResidueGroups(ref groups) => groups
+                .iter()
+                .position(|group| group.residues.iter().any(|residue| residue == name)),
+        }
+    }
+
+    /// Get the names of the groups. Blocks of residues have no names before they are found.
+    fn group_names(&self) -> Vec<String> {
+        match self {
+            &SplitRule::ResidueBlocks => Vec::new(),
+            &SplitRule::IndexGroups(ref groups) => {
+                groups.iter().map(|group| group.name.clone()).collect()
+            }
+            &SplitRule::ResidueGroups(ref groups) => {
+                groups.iter().map(|group| group.name.clone()).collect()
+            }
+        }
+    }
+}
+
+/// Read a configuration as a system of components, which are split by a rule.
+///
+/// The title of the system is that of the configuration.
+///
+/// # Errors
+/// Returns an error if the configuration could not be read.
+pub fn read_system(path: &Path, rule: &SplitRule) -> Result<System> {
+    let conf = ReadConf::from_gromos87(path)?;
+
+    let mut system = System::new(&conf.description);
+    system.components = split_configuration(&conf, rule);
+
+    Ok(system)
+}
+
+/// Split the residues of a read configuration into components.
+///
+/// Components are ordered as the groups of the rule, with residues which are not
+/// in any group collected in a last component named `UNGROUPED_NAME`. Groups
+/// without residues are left out.
+pub fn split_configuration(conf: &ReadConf, rule: &SplitRule) -> Vec<ComponentEntry> {
+    let index_sets = match rule {
+        &SplitRule::IndexGroups(ref groups) => groups
+            .iter()
+            .map(|group| group.atoms.iter().cloned().collect::<HashSet<_>>())
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+
+    let mut groups: Vec<(String, Vec<ResidueIterOut>)> = rule
+        .group_names()
+        .into_iter()
+        .map(|name| (name, Vec::new()))
+        .collect();
+    let mut ungrouped = Vec::new();
+    let mut first_atom = 1;
+
+    for residue in conf.iter_residues() {
+        let name = residue.get_residue().borrow().clone();
+        let num_atoms = residue.get_atoms().len();
+
+        match *rule {
+            SplitRule::ResidueBlocks => {
+                let is_same_block = groups
+                    .last()
+                    .map(|&(ref block, _)| *block == name)
+                    .unwrap_or(false);
+
+                if !is_same_block {
+                    groups.push((name, Vec::new()));
+                }
+
+                groups.last_mut().unwrap().1.push(residue);
+            }
+            _ => match rule.find_group(&name, first_atom, &index_sets) {
+                Some(i) => groups[i].1.push(residue),
+                None => ungrouped.push(residue),
+            },
+        }
+
+        first_atom += num_atoms;
+    }
+
+    if !ungrouped.is_empty() {
+        groups.push((UNGROUPED_NAME.to_string(), ungrouped));
+    }
+
+    groups
+        .into_iter()
+        .filter(|&(_, ref residues)| !residues.is_empty())
+        .map(|(name, residues)| {
+            let mut part = conf.clone();
+            part.description = name;
+            part.backup_conf = None;
+            part.assign_residues(&residues);
+
+            ComponentEntry::from(part)
+        })
+        .collect()
+}
+
+/// Read the groups of a GROMACS index (.ndx) file.
+///
+/// # Errors
+/// Returns an `IoError` if the file could not be read or a `ParseError` if an atom
+/// number is not a positive integer or is not preceded by a group header.
+pub fn read_index_groups(path: &Path) -> Result<Vec<IndexGroup>> {
+    let contents = fs::read_to_string(path).map_err(|err| GrafenError::io(path, err))?;
+
+    let parse_error = |line: usize, message: String| GrafenError::ParseError {
+        path: Some(PathBuf::from(path)),
+        line: Some(line),
+        message,
+    };
+
+    let mut groups: Vec<IndexGroup> = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.starts_with('[') && line.ends_with(']') {
+            groups.push(IndexGroup {
+                name: line[1..line.len() - 1].trim().to_string(),
+                atoms: Vec::new(),
+            });
+
+            continue;
+        }
+
+        for value in line.split_whitespace() {
+            let atom = value
+                .parse::<usize>()
+                .ok()
+                .filter(|&atom| atom > 0)
+                .ok_or_else(|| parse_error(i + 1, format!("invalid atom number '{}'", value)))?;
+
+            groups
+                .last_mut()
+                .ok_or_else(|| {
+                    parse_error(i + 1, "atom numbers before the first group".to_string())
+                })?
+                .atoms
+                .push(atom);
+        }
+    }
+
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::Describe;
+    use std::env::temp_dir;
+
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+
+        path
+    }
+
+    // Residues: SOL, SOL, NA, CL, SOL
+    const CONF: &str = "Mixed system
+    7
+    1SOL     OW    1   0.100   0.100   0.100
+    1SOL    HW1    2   0.200   0.100   0.100
+    2SOL     OW    3   0.500   0.500   0.500
+    2SOL    HW1    4   0.600   0.500   0.500
+    3NA      NA    5   1.000   1.000   1.000
+    4CL      CL    6   1.500   1.500   1.500
+    5SOL     OW    7   1.800   1.800   1.800
+   2.00000   2.00000   2.00000
+";
+
+    fn get_parts(components: &[ComponentEntry]) -> Vec<(String, u64)> {
+        components
+            .iter()
+            .map(|component| (component.describe_short(), component.num_atoms()))
+            .collect()
+    }
+
+    fn part(name: &str, num_atoms: u64) -> (String, u64) {
+        (format!("{} (Configuration)", name), num_atoms)
+    }
+
+    #[test]
+    fn split_system_by_blocks_of_residue_names() {
+        let path = write_temp_file("grafen-split-blocks.gro", CONF);
+        let system = read_system(&path, &SplitRule::ResidueBlocks).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(system.title, "Mixed system");
+        assert_eq!(system.num_atoms(), 7);
+        assert_eq!(
+            get_parts(&system.components),
+            vec![part("SOL", 4), part("NA", 1), part("CL", 1), part("SOL", 1)]
+        );
+    }
+
+    #[test]
+    fn split_system_by_groups_of_residue_names_collects_the_rest() {
+        let path = write_temp_file("grafen-split-residues.gro", CONF);
+        let rule = SplitRule::ResidueGroups(vec![
+            ResidueGroup {
+                name: "Water".to_string(),
+                residues: vec!["SOL".to_string()],
+            },
+            ResidueGroup {
+                name: "Empty".to_string(),
+                residues: vec!["K".to_string()],
+            },
+        ]);
+        let system = read_system(&path, &rule).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            get_parts(&system.components),
+            vec![part("Water", 5), part(UNGROUPED_NAME, 2)]
+        );
+    }
+
+    #[test]
+    fn split_system_by_index_groups_uses_the_first_atom_of_residues() {
+        let conf_path = write_temp_file("grafen-split-index.gro", CONF);
+        let index_path = write_temp_file(
+            "grafen-split-index.ndx",
+            "[ Ions ]\n5 6\n[ Water ]\n   1   3\n   7\n",
+        );
+
+        let groups = read_index_groups(&index_path).unwrap();
+        assert_eq!(
+            groups[1],
+            IndexGroup {
+                name: "Water".to_string(),
+                atoms: vec![1, 3, 7],
+            }
+        );
+
+        let system = read_system(&conf_path, &SplitRule::IndexGroups(groups)).unwrap();
+        fs::remove_file(&conf_path).unwrap();
+        fs::remove_file(&index_path).unwrap();
+
+        assert_eq!(
+            get_parts(&system.components),
+            vec![part("Ions", 2), part("Water", 5)]
+        );
+    }
+
+    #[test]
+    fn index_files_with_bad_atom_numbers_return_parse_errors() {
+        let path = write_temp_file("grafen-bad-index.ndx", "[ Group ]\n1 2\n3 a\n");
+        let result = read_index_groups(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(GrafenError::ParseError { line, .. }) => assert_eq!(line, Some(3)),
+            _ => panic!("a bad atom number was parsed"),
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Save and resume constructed systems.
+//!
+//! A session stores the title, output path and box setting of a `System` along with
+//! all of its constructed components. Definitions in a `DataBase` only store the parameters
+//! which are needed to construct them, but the generated fields of components
+//! (origins, sizes and coordinates) are written as well when they are set. A session
+//! can thus be read back into the same components as when it was written, including
+//! any edits which were made to them.
+//!
+//! Read configurations do not store their atoms. Instead their residues are written
+//! to the session and assigned to the configuration after it has been read from
+//! its file again, which must still exist when the session is read. The paths to
+//! configuration files are written relative to the session file if they are in its
+//! directory and as absolute paths otherwise, so that sessions can be read from
+//! any working directory.
+//!
+//! # Examples
+//! ```
+//! # use grafen::coord::Coord;
+//! # use grafen::database::library;
+//! # use grafen::session::{read_session, write_session, Session};
+//! # use grafen::system::System;
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! # let path = std::env::temp_dir().join("grafen-session-example.json");
+//! let mut system = System::new("Graphene").with_database(library());
+//! system.add_sheet("Graphene", Coord::ORIGO, 2.0, 2.0)?;
+//!
+//! write_session(&Session::from_system(&system), &path)?;
+//!
+//! let session = read_session(&path)?;
+//! assert_eq!(session.components.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    coord::Coord,
+    database::ComponentEntry,
+    error::{GrafenError, Result},
+    iterator::ResidueIterOut,
+    read_conf::ReadConf,
+    system::{AtomMetadata, BoxSetting, Component, System},
+};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json;
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Current version of the session file layout.
+pub const SESSION_VERSION: u64 = 1;
+
+#[derive(Clone, Debug)]
+/// The state of a system which can be written to and read from disk.
+pub struct Session {
+    /// Title of system.
+    pub title: String,
+    /// Path to which the system will be written.
+    pub output_path: PathBuf,
+    /// List of constructed components.
+    pub components: Vec<ComponentEntry>,
+    /// How the size of the system box is set.
+    pub box_setting: BoxSetting,
+}
+
+impl Session {
+    /// Capture the state of a system.
+    pub fn from_system(system: &System) -> Session {
+        Session {
+            title: system.title.clone(),
+            output_path: system.output_path.clone(),
+            components: system.components.clone(),
+            box_setting: system.box_setting,
+        }
+    }
+
+    /// Replace the title, output path, components and box setting of a system with the session.
+    ///
+    /// The database of the system is kept.
+    pub fn restore(self, system: &mut System) {
+        system.title = self.title;
+        system.output_path = self.output_path;
+        system.components = self.components;
+        system.box_setting = self.box_setting;
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+/// Layout of session files.
+struct SessionFile {
+    version: u64,
+    title: String,
+    output_path: PathBuf,
+    components: Vec<SavedComponent>,
+    #[serde(default)]
+    box_setting: BoxSetting,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SavedComponent {
+    component: ComponentEntry,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Residues of read configurations, which are not stored in the component.
+    residues: Vec<SavedResidue>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SavedResidue {
+    name: String,
+    atoms: Vec<(String, Coord)>,
+}
+
+impl SavedComponent {
+    /// Save a component. The path to a read configuration is made relative to
+    /// the session directory, or absolute if the file is outside of it.
+    fn from_component(component: &ComponentEntry, session_dir: &Path) -> SavedComponent {
+        let residues = match *component {
+            ComponentEntry::ConfigurationFile(ref conf) => conf
+                .iter_residues()
+                .map(|residue| SavedResidue {
+                    name: residue.get_residue().borrow().clone(),
+                    atoms: residue
+                        .get_atoms()
+                        .into_iter()
+                        .map(|(name, position)| (name.borrow().clone(), position))
+                        .collect(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let component = match *component {
+            ComponentEntry::ConfigurationFile(ref conf) => {
+                let mut conf = conf.clone();
+                conf.path = session_path(&conf.path, session_dir);
+
+                ComponentEntry::ConfigurationFile(conf)
+            }
+            ref component => component.clone(),
+        };
+
+        SavedComponent {
+            component,
+            residues,
+        }
+    }
+
+    /// Restore a component. The path to a read configuration is resolved against
+    /// the session directory.
+    fn into_component(self, session_dir: &Path) -> Result<ComponentEntry> {
+        match self.component {
+            ComponentEntry::ConfigurationFile(mut conf) => {
+                conf.path = session_dir.join(&conf.path);
+                conf.conf = ReadConf::from_gromos87(&conf.path)?.conf;
+
+                let residues = self
+                    .residues
+                    .into_iter()
+                    .map(|residue| {
+                        let num_atoms = residue.atoms.len();
+
+                        ResidueIterOut::FromComp(
+                            Rc::new(RefCell::new(residue.name)),
+                            residue
+                                .atoms
+                                .into_iter()
+                                .map(|(name, position)| (Rc::new(RefCell::new(name)), position))
+                                .collect(),
+                            vec![AtomMetadata::default(); num_atoms],
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                conf.assign_residues(&residues);
+
+                Ok(ComponentEntry::ConfigurationFile(conf))
+            }
+            component => Ok(component),
+        }
+    }
+}
+
+/// Return the directory of a session file.
+fn get_session_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if dir != Path::new("") => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Return the path to a file relative to the session directory if it is inside of it,
+/// otherwise its absolute path. The path is returned as is if the file does not exist.
+fn session_path(path: &Path, session_dir: &Path) -> PathBuf {
+    let absolute = match fs::canonicalize(path) {
+        Ok(absolute) => absolute,
+        Err(_) => return path.to_path_buf(),
+    };
+
+    fs::canonicalize(session_dir)
+        .ok()
+        .and_then(|dir| absolute.strip_prefix(dir).ok().map(|relative| relative.to_path_buf()))
+        .unwrap_or(absolute)
+}
+
+/// Write a session to a JSON formatted file.
+///
+/// Paths to configuration files are written relative to the session file if they are
+/// in its directory and as absolute paths otherwise.
+///
+/// # Errors
+/// Returns an `IoError` if the file could not be written.
+pub fn write_session(session: &Session, path: &Path) -> Result<()> {
+    let session_dir = get_session_dir(path);
+
+    let file = SessionFile {
+        version: SESSION_VERSION,
+        title: session.title.clone(),
+        output_path: session.output_path.clone(),
+        components: session
+            .components
+            .iter()
+            .map(|component| SavedComponent::from_component(component, session_dir))
+            .collect(),
+        box_setting: session.box_setting,
+    };
+
+    File::create(path)
+        .map(BufWriter::new)
+        .and_then(|mut writer| {
+            serde_json::to_writer(&mut writer, &file)?;
+            writer.flush()
+        })
+        .map_err(|err| GrafenError::io(path, err))
+}
+
+/// Read a session from a file.
+///
+/// The configuration files of read configurations are read again. Relative paths
+/// to them are resolved against the directory of the session file.
+///
+/// # Errors
+/// Returns an `IoError` if the session or a configuration file could not be read and
+/// a `ParseError` if the session could not be parsed or was written by a newer version.
+pub fn read_session(path: &Path) -> Result<Session> {
+    let parse_error = |line: Option<usize>, message: String| GrafenError::ParseError {
+        path: Some(path.to_path_buf()),
+        line,
+        message,
+    };
+
+    let reader = File::open(path)
+        .map(BufReader::new)
+        .map_err(|err| GrafenError::io(path, err))?;
+
+    let file: SessionFile = serde_json::from_reader(reader)
+        .map_err(|err| parse_error(Some(err.line()), err.to_string()))?;
+
+    if file.version > SESSION_VERSION {
+        return Err(parse_error(
+            None,
+            format!(
+                "the session has version {} but only versions up to {} can be read",
+                file.version, SESSION_VERSION
+            ),
+        ));
+    }
+
+    let session_dir = get_session_dir(path);
+
+    let components = file
+        .components
+        .into_iter()
+        .map(|component| component.into_component(session_dir))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Session {
+        title: file.title,
+        output_path: file.output_path,
+        components,
+        box_setting: file.box_setting,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::library,
+        describe::Describe,
+        volume::{FillType, InclusionPolicy},
+    };
+    use std::{env::temp_dir, fs};
+
+    fn get_positions(component: &ComponentEntry) -> Vec<Coord> {
+        component
+            .iter_residues()
+            .flat_map(|residue| residue.get_atoms().into_iter())
+            .map(|(_, position)| position)
+            .collect()
+    }
+
+    #[test]
+    fn sessions_are_read_back_into_the_written_components() {
+        let mut system = System::new("Water on graphene")
+            .with_database(library())
+            .with_box(BoxSetting::Padded(Coord::new(0.0, 0.0, 2.0)));
+        system
+            .add_sheet("Graphene", Coord::ORIGO, 2.0, 2.0)
+            .unwrap()
+            .fill_cuboid(
+                "Water (SPC/E)",
+                Coord::new(0.0, 0.0, 0.5),
+                Coord::new(2.0, 2.0, 1.0),
+                Some(FillType::NumCoords(20)),
+            )
+            .unwrap()
+            .prune_against(0, 1, 0.2, InclusionPolicy::AnyAtom)
+            .unwrap();
+
+        let path = temp_dir().join("grafen-session-roundtrip.json");
+        write_session(&Session::from_system(&system), &path).unwrap();
+
+        let mut restored = System::new("");
+        read_session(&path).unwrap().restore(&mut restored);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.title, system.title);
+        assert_eq!(restored.box_setting, system.box_setting);
+        assert_eq!(restored.num_atoms(), system.num_atoms());
+        assert_eq!(restored.box_size(), system.box_size());
+
+        for (component, original) in restored.components.iter().zip(system.components.iter()) {
+            assert_eq!(component.describe(), original.describe());
+            assert_eq!(get_positions(component), get_positions(original));
+        }
+
+        // The volume is restored with its size, so it can be pruned against again
+        assert!(restored
+            .prune_against(0, 1, 0.2, InclusionPolicy::AnyAtom)
+            .is_ok());
+    }
+
+    #[test]
+    fn generated_fields_are_only_written_for_constructed_components() {
+        let mut system = System::new("").with_database(library());
+
+        let definition = system.definition("Graphene").unwrap();
+        let json = serde_json::to_string(&definition).unwrap();
+        assert!(!json.contains("origin"));
+        assert!(!json.contains("coords"));
+
+        system
+            .add_sheet("Graphene", Coord::ORIGO, 1.0, 1.0)
+            .unwrap();
+        let json = serde_json::to_string(&system.components[0]).unwrap();
+        assert!(json.contains("length"));
+        assert!(json.contains("coords"));
+    }
+
+    #[test]
+    fn configuration_paths_are_written_to_be_read_from_any_directory() {
+        let session_dir = temp_dir().join("grafen-session-conf-path");
+        fs::create_dir_all(&session_dir).unwrap();
+        let session_path = session_dir.join("session.json");
+
+        // The path is relative to the working directory, which the session is not in
+        let conf = ReadConf::from_gromos87(Path::new("assets/water_block.gro")).unwrap();
+        let session = Session {
+            title: String::new(),
+            output_path: PathBuf::from("conf.gro"),
+            components: vec![ComponentEntry::ConfigurationFile(conf)],
+            box_setting: BoxSetting::Auto,
+        };
+
+        write_session(&session, &session_path).unwrap();
+        let file: SessionFile =
+            serde_json::from_reader(File::open(&session_path).unwrap()).unwrap();
+
+        match file.components[0].component {
+            ComponentEntry::ConfigurationFile(ref conf) => {
+                assert!(conf.path.is_absolute());
+                assert!(conf.path.ends_with("assets/water_block.gro"));
+            }
+            _ => panic!("the configuration was not written"),
+        }
+
+        // A configuration in the session directory is written and read relative to it
+        fs::copy("assets/water_block.gro", session_dir.join("water.gro")).unwrap();
+        fs::write(
+            &session_path,
+            r#"{"version":1,"title":"","output_path":"conf.gro","components":[
+                {"component":{"ConfigurationFile":{"path":"water.gro","description":"",
+                "volume_type":{"Cuboid":{"size":{"x":1.0,"y":1.0,"z":1.0}}}}}}]}"#,
+        )
+        .unwrap();
+
+        let result = read_session(&session_path);
+        fs::remove_dir_all(&session_dir).unwrap();
+
+        let session = result.unwrap();
+
+        match session.components[0] {
+            ComponentEntry::ConfigurationFile(ref conf) => {
+                assert_eq!(conf.path, session_dir.join("water.gro"));
+                assert!(conf.conf.is_some());
+            }
+            _ => panic!("the configuration was not read"),
+        }
+    }
+
+    #[test]
+    fn sessions_from_newer_versions_are_rejected() {
+        let path = temp_dir().join("grafen-session-newer.json");
+        fs::write(
+            &path,
+            r#"{"version":1000,"title":"","output_path":"conf.gro","components":[]}"#,
+        )
+        .unwrap();
+
+        let result = read_session(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(GrafenError::ParseError { .. }) => (),
+            _ => panic!("a session from a newer version was read"),
+        }
+    }
+}
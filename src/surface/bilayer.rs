@@ -0,0 +1,574 @@
+//! Construct lipid bilayers.
+
+use crate::{
+    coord::{Coord, Direction, Rotation, Translate},
+    describe::{unwrap_name, Describe},
+    error::{GrafenError, Result},
+    iterator::{ResidueIter, ResidueIterOut},
+    surface::{LatticeType, Sheet},
+    system::*,
+};
+
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use serde_derive::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+impl_translate![Bilayer];
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A lipid residue and its mole fraction in a leaflet.
+pub struct Lipid {
+    /// Lipid residue.
+    pub residue: Residue,
+    /// Mole fraction of the lipid in the leaflet. The fractions of all lipids
+    /// in a leaflet are normalized to their sum.
+    pub fraction: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// The leaflets of a bilayer.
+pub enum Leaflet {
+    /// The leaflet at the top of the bilayer, with its head groups facing up.
+    Upper,
+    /// The leaflet at the bottom of the bilayer, with its head groups facing down.
+    Lower,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// A lipid placed in a constructed bilayer.
+pub struct PlacedLipid {
+    /// Leaflet which the lipid belongs to.
+    pub leaflet: Leaflet,
+    /// Index of the lipid in the composition of the leaflet.
+    pub index: usize,
+    /// Position of the lipid relative to the bilayer origin.
+    pub position: Coord,
+    /// Rotation of the lipid around its first atom.
+    pub rotation: Rotation,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A lipid bilayer in the x-y plane.
+///
+/// Lipids are placed with their first atom (the head group) on the planes
+/// of the two leaflets. The lower leaflet head groups are placed at the bottom
+/// of the bilayer and the upper leaflet head groups at `thickness` above them.
+/// The lipids of the two leaflets are mirrored to have their tails directed
+/// towards the bilayer center.
+pub struct Bilayer {
+    /// Name of component.
+    pub name: Option<String>,
+    /// Lipid composition of the upper leaflet.
+    pub upper_leaflet: Vec<Lipid>,
+    #[serde(default)]
+    /// Lipid composition of the lower leaflet. If empty the composition
+    /// of the upper leaflet is used for both leaflets.
+    pub lower_leaflet: Vec<Lipid>,
+    /// Area per lipid in each leaflet.
+    pub area_per_lipid: f64,
+    /// Distance between the head group planes of the two leaflets.
+    pub thickness: f64,
+    /// Axis of the lipid residues, directed from the head group towards the tails.
+    pub lipid_axis: Coord,
+    /// Whether to randomly rotate the lipids around the bilayer normal.
+    pub random_rotation: bool,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Origin of the bilayer. Located in the lower-left position of it.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Length of the bilayer along the x axis.
+    pub length: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Length of the bilayer along the y axis.
+    pub width: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Lipids of the constructed bilayer.
+    pub lipids: Vec<PlacedLipid>,
+}
+
+impl Bilayer {
+    /// Construct the lipids of both leaflets and return the object.
+    ///
+    /// # Errors
+    /// Returns an error if either the length or width is non-positive, if the area
+    /// per lipid is non-positive or if a leaflet has no lipids with a positive fraction.
+    pub fn construct(self) -> Result<Bilayer> {
+        if self.area_per_lipid <= 0.0 {
+            return Err(GrafenError::invalid_parameter(
+                "area per lipid",
+                "cannot create a bilayer with a non-positive area per lipid",
+            ));
+        }
+
+        check_composition(&self.upper_leaflet)?;
+        check_composition(&self.lower_leaflet_or_upper())?;
+
+        let mut lipids = Vec::new();
+        let mut size = (self.length, self.width);
+
+        for &leaflet in &[Leaflet::Lower, Leaflet::Upper] {
+            let sheet = Sheet {
+                name: None,
+                residue: None,
+                lattice: LatticeType::PoissonDisc {
+                    density: 1.0 / self.area_per_lipid,
+                },
+                std_z: None,
+                origin: Coord::ORIGO,
+                normal: Direction::Z,
+                orientation: None,
+                length: self.length,
+                width: self.width,
+                coords: vec![],
+            }
+            .construct()?;
+
+            size = (sheet.length, sheet.width);
+            lipids.extend(self.place_leaflet(leaflet, &sheet.coords));
+        }
+
+        Ok(Bilayer {
+            length: size.0,
+            width: size.1,
+            lipids,
+            ..self
+        })
+    }
+
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        Coord::new(self.length, self.width, self.thickness)
+    }
+
+    /// Return the range along the normal which is spanned by the atoms of the lipids,
+    /// relative to the bilayer origin.
+    ///
+    /// The range includes both head group planes and every atom of the lipids
+    /// in the leaflet compositions, eg. those which extend beyond the head groups.
+    pub fn normal_extent(&self) -> (f64, f64) {
+        let axis = self.lipid_axis.normalize();
+
+        let lower = self
+            .lower_leaflet_or_upper()
+            .iter()
+            .map(|lipid| (lipid, 1.0, 0.0));
+        let upper = self
+            .upper_leaflet
+            .iter()
+            .map(|lipid| (lipid, -1.0, self.thickness));
+
+        lower
+            .chain(upper)
+            .flat_map(|(lipid, sign, height)| {
+                let atoms = &lipid.residue.atoms;
+                let head = atoms
+                    .first()
+                    .map(|atom| atom.position)
+                    .unwrap_or(Coord::ORIGO);
+
+                atoms
+                    .iter()
+                    .map(move |atom| height + sign * (atom.position - head).dot(axis))
+            })
+            .fold((0.0, self.thickness), |(min, max), z| {
+                (f64::min(min, z), f64::max(max, z))
+            })
+    }
+
+    /// Return the composition of a leaflet.
+    fn composition(&self, leaflet: Leaflet) -> &[Lipid] {
+        match leaflet {
+            Leaflet::Upper => &self.upper_leaflet,
+            Leaflet::Lower => self.lower_leaflet_or_upper(),
+        }
+    }
+
+    fn lower_leaflet_or_upper(&self) -> &[Lipid] {
+        if self.lower_leaflet.is_empty() {
+            &self.upper_leaflet
+        } else {
+            &self.lower_leaflet
+        }
+    }
+
+    /// Return the residue of a placed lipid.
+    fn residue_of(&self, lipid: &PlacedLipid) -> &Residue {
+        &self.composition(lipid.leaflet)[lipid.index].residue
+    }
+
+    /// Place lipids of a leaflet at the input in-plane positions.
+    fn place_leaflet(&self, leaflet: Leaflet, positions: &[Coord]) -> Vec<PlacedLipid> {
+        let composition = self.composition(leaflet);
+        let fractions = composition
+            .iter()
+            .map(|lipid| lipid.fraction.max(0.0))
+            .collect::<Vec<_>>();
+
+        let mut indices = distribute_counts(&fractions, positions.len())
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, count)| vec![i; count])
+            .collect::<Vec<_>>();
+
+        let mut rng = thread_rng();
+        indices.shuffle(&mut rng);
+
+        // Tails are directed towards the bilayer center
+        let (height, tail_direction) = match leaflet {
+            Leaflet::Upper => (self.thickness, Coord::new(0.0, 0.0, -1.0)),
+            Leaflet::Lower => (0.0, Coord::new(0.0, 0.0, 1.0)),
+        };
+        let alignment = Rotation::between(self.lipid_axis, tail_direction);
+
+        indices
+            .into_iter()
+            .zip(positions.iter())
+            .map(|(index, &coord)| {
+                let head = composition[index]
+                    .residue
+                    .atoms
+                    .first()
+                    .map(|atom| atom.position)
+                    .unwrap_or(Coord::ORIGO);
+
+                let rotation = if self.random_rotation {
+                    let angle = rng.gen_range(0.0, 2.0 * PI);
+                    alignment.then(Rotation::around_axis(Coord::new(0.0, 0.0, 1.0), angle))
+                } else {
+                    alignment
+                };
+
+                PlacedLipid {
+                    leaflet,
+                    index,
+                    position: Coord::new(coord.x, coord.y, height) - head,
+                    rotation,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Assert that a leaflet composition has lipids with a positive total fraction.
+fn check_composition(composition: &[Lipid]) -> Result<()> {
+    let total: f64 = composition
+        .iter()
+        .map(|lipid| lipid.fraction.max(0.0))
+        .sum();
+
+    if composition.is_empty() || total <= 0.0 {
+        return Err(GrafenError::invalid_parameter(
+            "leaflet",
+            "cannot create a bilayer leaflet without lipids",
+        ));
+    }
+
+    if composition
+        .iter()
+        .any(|lipid| lipid.residue.atoms.is_empty())
+    {
+        return Err(GrafenError::invalid_parameter(
+            "leaflet",
+            "cannot create a bilayer with lipids that have no atoms",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Distribute a total number of items into groups according to their fractions.
+///
+/// The largest remainder method is used to make the counts sum to the total.
+fn distribute_counts(fractions: &[f64], total: usize) -> Vec<usize> {
+    let sum: f64 = fractions.iter().sum();
+
+    if sum <= 0.0 {
+        return vec![0; fractions.len()];
+    }
+
+    let quotas = fractions
+        .iter()
+        .map(|&fraction| fraction / sum * total as f64)
+        .collect::<Vec<_>>();
+
+    let mut counts = quotas
+        .iter()
+        .map(|&q| q.floor() as usize)
+        .collect::<Vec<_>>();
+    let assigned: usize = counts.iter().sum();
+
+    let mut remainders = quotas
+        .iter()
+        .enumerate()
+        .map(|(i, &q)| (i, q - q.floor()))
+        .collect::<Vec<_>>();
+    remainders.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+    remainders
+        .iter()
+        .take(total.saturating_sub(assigned))
+        .for_each(|&(i, _)| counts[i] += 1);
+
+    counts
+}
+
+impl<'a> Component<'a> for Bilayer {
+    /// Assign a set of input residues to the bilayer.
+    ///
+    /// The residues are assumed to be an ordered subset of the residues
+    /// from `iter_residues()`, eg. after some have been removed. Lipids
+    /// which are not matched by their residue name and head group position
+    /// are removed from the bilayer.
+    fn assign_residues(&mut self, residues: &[ResidueIterOut]) {
+        let mut remaining = residues.iter().peekable();
+        let mut lipids = Vec::new();
+
+        for lipid in self.lipids.iter() {
+            let is_match = match remaining.peek() {
+                Some(res) => {
+                    let residue = self.residue_of(lipid);
+                    let head = lipid.position + residue.atoms[0].position;
+
+                    *res.get_residue().borrow() == residue.code
+                        && res.get_atoms().first().map(|atom| atom.1) == Some(head)
+                }
+                None => false,
+            };
+
+            if is_match {
+                remaining.next();
+                lipids.push(lipid.clone());
+            }
+        }
+
+        self.lipids = lipids;
+    }
+
+    fn box_size(&self) -> Coord {
+        self.calc_box_size() + self.origin
+    }
+
+    fn get_origin(&self) -> Coord {
+        self.origin
+    }
+
+    fn iter_residues(&self) -> ResidueIter {
+        let placements = self
+            .lipids
+            .iter()
+            .map(|lipid| (self.residue_of(lipid), lipid.position, lipid.rotation))
+            .collect::<Vec<_>>();
+
+        ResidueIter::Rotated(placements.into_iter())
+    }
+
+    fn num_atoms(&self) -> u64 {
+        self.lipids
+            .iter()
+            .map(|lipid| self.residue_of(lipid).atoms.len() as u64)
+            .sum()
+    }
+
+    fn with_pbc(mut self) -> Self {
+        // Only wrap the lipids in the bilayer plane
+        let box_size = Coord::new(self.length, self.width, 0.0);
+
+        self.lipids
+            .iter_mut()
+            .for_each(|lipid| lipid.position = lipid.position.with_pbc(box_size));
+
+        self
+    }
+}
+
+impl Describe for Bilayer {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Lipid bilayer of size ({:.2}, {:.2}) and thickness {:.2} at {})",
+            unwrap_name(&self.name),
+            self.length,
+            self.width,
+            self.thickness,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Bilayer)", unwrap_name(&self.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lipid(code: &str, fraction: f64) -> Lipid {
+        Lipid {
+            residue: resbase![code, ("P", 0.0, 0.0, 0.0), ("C", 0.0, 0.0, -1.0)],
+            fraction,
+        }
+    }
+
+    fn setup_bilayer(upper: Vec<Lipid>, lower: Vec<Lipid>) -> Bilayer {
+        Bilayer {
+            name: None,
+            upper_leaflet: upper,
+            lower_leaflet: lower,
+            area_per_lipid: 0.6,
+            thickness: 4.0,
+            lipid_axis: Coord::new(0.0, 0.0, -1.0),
+            random_rotation: false,
+            origin: Coord::ORIGO,
+            length: 10.0,
+            width: 10.0,
+            lipids: vec![],
+        }
+    }
+
+    #[test]
+    fn distribute_counts_sums_to_total_by_largest_remainder() {
+        assert_eq!(distribute_counts(&[1.0, 1.0], 10), vec![5, 5]);
+        assert_eq!(distribute_counts(&[0.7, 0.3], 11), vec![8, 3]);
+        assert_eq!(
+            distribute_counts(&[1.0, 1.0, 1.0], 10)
+                .iter()
+                .sum::<usize>(),
+            10
+        );
+        assert_eq!(distribute_counts(&[0.0, 0.0], 10), vec![0, 0]);
+    }
+
+    #[test]
+    fn bilayer_normal_extent_includes_atoms_beyond_the_head_groups() {
+        let outer = Lipid {
+            residue: resbase![
+                "LIP",
+                ("P", 0.0, 0.0, 0.0),
+                ("N", 0.0, 0.0, 0.5),
+                ("C", 0.0, 0.0, -1.0)
+            ],
+            fraction: 1.0,
+        };
+
+        assert_eq!(
+            setup_bilayer(vec![lipid("LIP", 1.0)], vec![]).normal_extent(),
+            (0.0, 4.0)
+        );
+        assert_eq!(
+            setup_bilayer(vec![outer.clone()], vec![]).normal_extent(),
+            (-0.5, 4.5)
+        );
+        assert_eq!(
+            setup_bilayer(vec![lipid("LIP", 1.0)], vec![outer]).normal_extent(),
+            (-0.5, 4.0)
+        );
+    }
+
+    #[test]
+    fn bilayer_leaflets_have_head_groups_at_their_planes_with_tails_inwards() {
+        let bilayer = setup_bilayer(vec![lipid("LIP", 1.0)], vec![])
+            .construct()
+            .unwrap();
+
+        assert!(!bilayer.lipids.is_empty());
+
+        for res in bilayer.iter_residues() {
+            let atoms = res.get_atoms();
+            let (head, tail) = (atoms[0].1, atoms[1].1);
+
+            if head.z > 2.0 {
+                assert_eq!(head.z, 4.0);
+                assert_eq!(tail, head + Coord::new(0.0, 0.0, -1.0));
+            } else {
+                assert_eq!(head.z, 0.0);
+                assert_eq!(tail, head + Coord::new(0.0, 0.0, 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn bilayer_has_roughly_the_number_of_lipids_given_by_the_area_per_lipid() {
+        let bilayer = setup_bilayer(vec![lipid("LIP", 1.0)], vec![])
+            .construct()
+            .unwrap();
+
+        let expected = 2.0 * 100.0 / 0.6;
+        let num_lipids = bilayer.lipids.len() as f64;
+
+        assert!(num_lipids > 0.8 * expected && num_lipids < 1.2 * expected);
+        assert_eq!(bilayer.num_atoms(), 2 * bilayer.lipids.len() as u64);
+    }
+
+    #[test]
+    fn asymmetric_bilayer_composition_follows_the_fractions() {
+        let upper = vec![lipid("POPC", 3.0), lipid("POPE", 1.0)];
+        let lower = vec![lipid("POPS", 1.0)];
+
+        let bilayer = setup_bilayer(upper, lower).construct().unwrap();
+
+        let count = |leaflet, index| {
+            bilayer
+                .lipids
+                .iter()
+                .filter(|lipid| lipid.leaflet == leaflet && lipid.index == index)
+                .count()
+        };
+
+        let num_upper = count(Leaflet::Upper, 0) + count(Leaflet::Upper, 1);
+        let expected = distribute_counts(&[3.0, 1.0], num_upper);
+
+        assert_eq!(count(Leaflet::Upper, 0), expected[0]);
+        assert_eq!(count(Leaflet::Upper, 1), expected[1]);
+        assert!(count(Leaflet::Lower, 0) > 0);
+
+        let codes = bilayer
+            .iter_residues()
+            .map(|res| res.get_residue().borrow().clone())
+            .collect::<Vec<_>>();
+
+        assert!(codes.iter().any(|code| code == "POPS"));
+        assert!(codes.iter().any(|code| code == "POPE"));
+    }
+
+    #[test]
+    fn constructing_bilayer_without_lipids_or_with_bad_area_is_an_error() {
+        assert!(setup_bilayer(vec![], vec![]).construct().is_err());
+        assert!(setup_bilayer(vec![lipid("LIP", 0.0)], vec![])
+            .construct()
+            .is_err());
+
+        let mut bilayer = setup_bilayer(vec![lipid("LIP", 1.0)], vec![]);
+        bilayer.area_per_lipid = 0.0;
+        assert!(bilayer.construct().is_err());
+    }
+
+    #[test]
+    fn assigning_a_subset_of_residues_keeps_the_matching_lipids() {
+        let mut bilayer = setup_bilayer(vec![lipid("LIP", 1.0)], vec![])
+            .construct()
+            .unwrap();
+
+        let residues = bilayer
+            .iter_residues()
+            .enumerate()
+            .filter(|(i, _)| i % 2 == 0)
+            .map(|(_, res)| res)
+            .collect::<Vec<_>>();
+
+        let heads = residues
+            .iter()
+            .map(|res| res.get_atoms()[0].1)
+            .collect::<Vec<_>>();
+
+        bilayer.assign_residues(&residues);
+
+        assert_eq!(bilayer.lipids.len(), residues.len());
+        assert_eq!(
+            bilayer
+                .iter_residues()
+                .map(|res| res.get_atoms()[0].1)
+                .collect::<Vec<_>>(),
+            heads
+        );
+    }
+}
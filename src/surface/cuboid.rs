@@ -5,15 +5,18 @@ use crate::{
     describe::{unwrap_name, Describe},
     error::Result,
     iterator::{ResidueIter, ResidueIterOut},
-    surface::{LatticeType, Sheet},
+    surface::{LatticeType, Orientation, Sheet, SurfaceNormal},
     system::*,
 };
 
 use bitflags::bitflags;
 use serde_derive::{Deserialize, Serialize};
-use std::fmt::{self, Display, Formatter};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+};
 
-impl_component![Cuboid];
+impl_component![@oriented Cuboid];
 impl_translate![Cuboid];
 
 bitflags! {
@@ -67,15 +70,17 @@ pub struct Cuboid {
     /// Standard deviation along z of coordinates. Added to the coordinates when `construct`
     /// is called.
     pub std_z: Option<f64>,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Origin of the sheet. Located in the lower-left position of it.
     pub origin: Coord,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Size of cuboid box.
     pub size: Coord,
     /// Sides that are added for the box. Is a `bitflag` struct.
     pub sides: Sides,
-    #[serde(skip)]
+    /// Optional orientation of the residues along the surface normal.
+    pub orientation: Option<Orientation>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// List of coordinates belonging to the sheet. Relative to the `origin`.
     pub coords: Vec<Coord>,
 }
@@ -97,6 +102,7 @@ impl Cuboid {
             std_z: self.std_z,
             origin: Coord::ORIGO,
             normal: Direction::X,
+            orientation: None,
             length: 0.0,
             width: 0.0,
             coords: Vec::new(),
@@ -174,6 +180,28 @@ impl Cuboid {
     }
 }
 
+impl SurfaceNormal for Cuboid {
+    /// The normal points out of the closest constructed side of the box.
+    fn normal_at(&self, coord: Coord) -> Coord {
+        let faces = [
+            (Sides::X0, coord.x, Coord::new(-1.0, 0.0, 0.0)),
+            (Sides::X1, self.size.x - coord.x, Coord::new(1.0, 0.0, 0.0)),
+            (Sides::Y0, coord.y, Coord::new(0.0, -1.0, 0.0)),
+            (Sides::Y1, self.size.y - coord.y, Coord::new(0.0, 1.0, 0.0)),
+            (Sides::Z0, coord.z, Coord::new(0.0, 0.0, -1.0)),
+            (Sides::Z1, self.size.z - coord.z, Coord::new(0.0, 0.0, 1.0)),
+        ];
+
+        // If no sides are set we still want a valid normal, so use all of them
+        faces
+            .iter()
+            .filter(|&&(side, _, _)| self.sides.is_empty() || self.sides.contains(side))
+            .min_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap_or(Ordering::Equal))
+            .map(|&(_, _, normal)| normal)
+            .unwrap_or(Coord::new(0.0, 0.0, 1.0))
+    }
+}
+
 impl Describe for Cuboid {
     fn describe(&self) -> String {
         format!(
@@ -209,6 +237,7 @@ mod tests {
             origin: Coord::ORIGO,
             lattice: lattice.clone(),
             normal: Direction::X,
+            orientation: None,
             length: 0.0,
             width: 0.0,
             coords: Vec::new(),
@@ -257,6 +286,7 @@ mod tests {
             origin: Coord::ORIGO,
             size: size,
             sides: Sides::empty(),
+            orientation: None,
             coords: Vec::new(),
         };
 
@@ -399,4 +429,30 @@ mod tests {
             .zip(sheet_xy.coords.iter())
             .all(|(&c0, &c1)| c0.z == c1.z));
     }
+
+    #[test]
+    fn cuboid_normals_point_out_of_the_closest_constructed_side() {
+        let (_, _, _, cuboid) =
+            setup_sheets_and_cuboid_base(2.0, 2.0, 2.0, LatticeType::Hexagonal { a: 0.57 });
+
+        let cuboid = Cuboid {
+            sides: Sides::X0 | Sides::Z1,
+            ..cuboid
+        };
+
+        assert_eq!(
+            cuboid.normal_at(Coord::new(0.0, 1.0, 1.0)),
+            Coord::new(-1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            cuboid.normal_at(Coord::new(1.0, 1.0, 2.0)),
+            Coord::new(0.0, 0.0, 1.0)
+        );
+
+        // The Y0 side is closer but not constructed
+        assert_eq!(
+            cuboid.normal_at(Coord::new(0.5, 0.0, 1.0)),
+            Coord::new(-1.0, 0.0, 0.0)
+        );
+    }
 }
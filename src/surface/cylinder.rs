@@ -5,7 +5,7 @@ use crate::{
     describe::{unwrap_name, Describe},
     error::Result,
     iterator::{ResidueIter, ResidueIterOut},
-    surface::{LatticeType, Sheet},
+    surface::{LatticeType, Orientation, Sheet, SurfaceNormal},
     system::*,
 };
 
@@ -15,7 +15,7 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
-impl_component![Cylinder];
+impl_component![@oriented Cylinder];
 impl_translate![Cylinder];
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
@@ -50,16 +50,18 @@ pub struct Cylinder {
     pub alignment: Direction,
     /// Cylinders can be capped at its ends.
     pub cap: Option<CylinderCap>,
-    #[serde(skip)]
+    /// Optional orientation of the residues along the surface normal.
+    pub orientation: Option<Orientation>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Origin of the cylinder. Located in the center of the bottom.
     pub origin: Coord,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Radius of cylinder.
     pub radius: f64,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Height of cylinder.
     pub height: f64,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// List of coordinates belonging to the cylinder. Relative to the `origin.
     pub coords: Vec<Coord>,
 }
@@ -81,6 +83,7 @@ impl Cylinder {
             std_z: None,
             origin: Coord::default(),
             normal: Direction::Z,
+            orientation: None,
             length,
             width,
             coords: vec![],
@@ -153,6 +156,32 @@ impl Cylinder {
     }
 }
 
+impl SurfaceNormal for Cylinder {
+    /// The normal points away from the cylinder axis on its wall and along the axis
+    /// on its caps. Coordinates are assigned to the closest of these surfaces.
+    fn normal_at(&self, coord: Coord) -> Coord {
+        let (dr, dh) = Coord::ORIGO.distance_cylindrical(coord, self.alignment);
+        let axis = self.alignment.unit_vector();
+
+        let (bottom, top) = match self.cap {
+            None => (false, false),
+            Some(CylinderCap::Bottom) => (true, false),
+            Some(CylinderCap::Top) => (false, true),
+            Some(CylinderCap::Both) => (true, true),
+        };
+
+        let dist_wall = (dr - self.radius).abs();
+
+        if bottom && dh.abs() < dist_wall {
+            -axis
+        } else if top && (dh - self.height).abs() < dist_wall {
+            axis
+        } else {
+            (coord - axis * dh).normalize()
+        }
+    }
+}
+
 impl Describe for Cylinder {
     fn describe(&self) -> String {
         format!(
@@ -181,6 +210,7 @@ mod tests {
             lattice: lattice.clone(),
             alignment: Direction::Z,
             cap: None,
+            orientation: None,
             origin: Coord::default(),
             radius,
             height,
@@ -334,4 +364,39 @@ mod tests {
             cylinder.calc_box_size()
         );
     }
+
+    #[test]
+    fn cylinder_normals_point_out_of_the_wall_and_caps() {
+        let mut cylinder = setup_cylinder(2.0, 5.0, &Hexagonal { a: 0.1 });
+
+        assert_eq!(
+            cylinder.normal_at(Coord::new(2.0, 0.0, 2.5)),
+            Coord::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            cylinder.normal_at(Coord::new(0.0, -2.0, 0.1)),
+            Coord::new(0.0, -1.0, 0.0)
+        );
+
+        // Without caps all coordinates are on the wall
+        assert_eq!(
+            cylinder.normal_at(Coord::new(1.0, 0.0, 0.0)),
+            Coord::new(1.0, 0.0, 0.0)
+        );
+
+        cylinder.cap = Some(CylinderCap::Both);
+
+        assert_eq!(
+            cylinder.normal_at(Coord::new(1.0, 0.0, 0.0)),
+            Coord::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            cylinder.normal_at(Coord::new(0.0, 1.0, 5.0)),
+            Coord::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            cylinder.normal_at(Coord::new(2.0, 0.0, 2.5)),
+            Coord::new(1.0, 0.0, 0.0)
+        );
+    }
 }
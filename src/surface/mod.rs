@@ -1,19 +1,28 @@
 //! Define and construct 2D surface objects.
 
+mod bilayer;
 mod cuboid;
 mod cylinder;
 mod distribution;
 mod lattice;
 mod points;
 mod sheet;
+mod sphere;
+
+use crate::{
+    coord::{Coord, Rotation},
+    error::{GrafenError, Result},
+};
 
 use serde_derive::{Deserialize, Serialize};
 
 // Export components
 pub use self::{
+    bilayer::{Bilayer, Leaflet, Lipid, PlacedLipid},
     cuboid::{Cuboid, Sides},
     cylinder::{Cylinder, CylinderCap},
     sheet::{Circle, Sheet},
+    sphere::{Ellipsoid, Sphere, SphereLattice},
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
@@ -45,3 +54,75 @@ pub enum LatticeType {
         number: u64,
     },
 }
+
+impl LatticeType {
+    /// Assert that the lattice can be constructed.
+    ///
+    /// # Errors
+    /// Returns a `LatticeError` if a spacing or density is not positive and finite,
+    /// or if the angle of a triclinic lattice is not between 0 and 180 degrees.
+    pub fn check(&self) -> Result<()> {
+        let error = |message: String| Err(GrafenError::LatticeError(message));
+
+        match *self {
+            LatticeType::Hexagonal { a } if !is_positive(a) => {
+                error(format!("the spacing must be positive (is {})", a))
+            }
+            LatticeType::Triclinic { a, b, .. } if !(is_positive(a) && is_positive(b)) => error(
+                format!("the spacings must be positive (are {} and {})", a, b),
+            ),
+            LatticeType::Triclinic { gamma, .. } if !(gamma > 0.0 && gamma < 180.0) => error(
+                format!("the angle must be between 0 and 180 degrees (is {})", gamma),
+            ),
+            LatticeType::PoissonDisc { density } if !is_positive(density) => {
+                error(format!("the density must be positive (is {})", density))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether a lattice spacing or density is positive and finite.
+fn is_positive(value: f64) -> bool {
+    value > 0.0 && value.is_finite()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Which side of a surface a residue is oriented towards.
+pub enum NormalDirection {
+    /// Along the surface normal, ie. away from the enclosed side.
+    Outward,
+    /// Against the surface normal.
+    Inward,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Orientation of residues on a surface.
+///
+/// The residue is rotated around its first atom to align an `axis` of it with
+/// the surface normal at its position.
+pub struct Orientation {
+    /// Axis of the residue, relative to its own atom positions, which is aligned
+    /// with the surface normal.
+    pub axis: Coord,
+    /// Whether the axis points outwards or inwards.
+    pub direction: NormalDirection,
+}
+
+impl Orientation {
+    /// Return the rotation of a residue at a position with the input surface normal.
+    pub fn rotation(&self, normal: Coord) -> Rotation {
+        let target = match self.direction {
+            NormalDirection::Outward => normal,
+            NormalDirection::Inward => -normal,
+        };
+
+        Rotation::between(self.axis, target)
+    }
+}
+
+/// Surfaces which have a well defined normal vector at every point.
+pub trait SurfaceNormal {
+    /// Return the outward unit normal of the surface at a coordinate relative to its origin.
+    fn normal_at(&self, coord: Coord) -> Coord;
+}
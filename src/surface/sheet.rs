@@ -9,6 +9,7 @@ use crate::{
         distribution::Distribution,
         lattice::Lattice,
         LatticeType::{self, *},
+        Orientation, SurfaceNormal,
     },
     system::*,
     volume::pbc_multiply_volume,
@@ -16,7 +17,7 @@ use crate::{
 
 use serde_derive::{Deserialize, Serialize};
 
-impl_component![Sheet];
+impl_component![@oriented Sheet];
 impl_translate![Circle, Sheet];
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -32,18 +33,20 @@ pub struct Sheet {
     /// Standard deviation along z of coordinates. Added to the coordinates when `construct`
     /// is called.
     pub std_z: Option<f64>,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Origin of the sheet. Located in the lower-left position of it.
     pub origin: Coord,
     /// Normal vector of the sheet.
     pub normal: Direction,
-    #[serde(skip)]
+    /// Optional orientation of the residues along the sheet normal.
+    pub orientation: Option<Orientation>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Length of the sheet along the x axis.
     pub length: f64,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Length of the sheet along the y axis.
     pub width: f64,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// List of coordinates belonging to the sheet. Relative to the `origin`.
     pub coords: Vec<Coord>,
 }
@@ -55,11 +58,14 @@ impl Sheet {
     /// Returns an error if either the length or width is non-positive.
     pub fn construct(self) -> Result<Sheet> {
         if self.length <= 0.0 || self.width <= 0.0 {
-            return Err(GrafenError::RunError(
-                "cannot create a substrate of negative size".to_string(),
+            return Err(GrafenError::invalid_parameter(
+                "size",
+                "cannot create a substrate of non-positive size",
             ));
         }
 
+        self.lattice.check()?;
+
         let mut coords_lattice = match self.lattice {
             Hexagonal { a } => Lattice::hexagonal(a)
                 .with_size(self.length, self.width)
@@ -141,6 +147,13 @@ impl Sheet {
     }
 }
 
+impl SurfaceNormal for Sheet {
+    /// The normal of a sheet points along the positive direction of its normal axis.
+    fn normal_at(&self, _: Coord) -> Coord {
+        self.normal.unit_vector()
+    }
+}
+
 impl Describe for Sheet {
     fn describe(&self) -> String {
         format!(
@@ -206,6 +219,7 @@ mod tests {
             std_z: None,
             origin: Coord::default(),
             normal: Direction::Z,
+            orientation: None,
             length,
             width,
             coords: vec![],
@@ -254,6 +268,35 @@ mod tests {
         assert!(setup_sheet(1.0, 1.0, &lattice).construct().is_ok());
     }
 
+    #[test]
+    fn create_sheets_with_invalid_lattices_returns_lattice_errors() {
+        let lattices = vec![
+            Hexagonal { a: 0.0 },
+            Triclinic {
+                a: 1.0,
+                b: -1.0,
+                gamma: 90.0,
+            },
+            Triclinic {
+                a: 1.0,
+                b: 1.0,
+                gamma: 180.0,
+            },
+            PoissonDisc { density: 0.0 },
+            Hexagonal { a: f64::NAN },
+            PoissonDisc {
+                density: f64::INFINITY,
+            },
+        ];
+
+        for lattice in lattices {
+            match setup_sheet(1.0, 1.0, &lattice).construct() {
+                Err(GrafenError::LatticeError(_)) => (),
+                result => panic!("expected a lattice error, got {:?}", result.map(|_| ())),
+            }
+        }
+    }
+
     #[test]
     fn variance_is_added_if_requested() {
         let length = 10.0;
@@ -406,4 +449,32 @@ mod tests {
             assert_eq!(coord.z, 0.0);
         }
     }
+
+    #[test]
+    fn oriented_residues_on_sheet_are_aligned_with_its_normal() {
+        use crate::surface::NormalDirection;
+
+        let residue = resbase!["RES", ("A", 0.0, 0.0, 0.0), ("B", 1.0, 0.0, 0.0)];
+
+        let sheet = Sheet {
+            residue: Some(residue),
+            normal: Direction::Y,
+            orientation: Some(Orientation {
+                axis: Coord::new(1.0, 0.0, 0.0),
+                direction: NormalDirection::Inward,
+            }),
+            ..setup_sheet(1.0, 1.0, &Hexagonal { a: 0.1 })
+        }
+        .construct()
+        .unwrap();
+
+        assert!(!sheet.coords.is_empty());
+
+        for (res, &coord) in sheet.iter_residues().zip(sheet.coords.iter()) {
+            let atoms = res.get_atoms();
+
+            assert_eq!(atoms[0].1, coord);
+            assert_eq!(atoms[1].1 - atoms[0].1, Coord::new(0.0, -1.0, 0.0));
+        }
+    }
 }
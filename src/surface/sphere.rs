@@ -0,0 +1,492 @@
+//! Construct closed spherical and ellipsoidal shells.
+
+use crate::{
+    coord::{Coord, Translate},
+    describe::{unwrap_name, Describe},
+    error::{GrafenError, Result},
+    iterator::{ResidueIter, ResidueIterOut},
+    surface::{Orientation, SurfaceNormal},
+    system::*,
+};
+
+use rand::{
+    distributions::{Distribution as _, Uniform},
+    thread_rng, Rng,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashMap, f64::consts::PI};
+
+impl_component![@oriented Ellipsoid, Sphere];
+impl_translate![Ellipsoid, Sphere];
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Distributions of points on a closed shell.
+pub enum SphereLattice {
+    /// Points on a Fibonacci (golden spiral) lattice with an input `density` in number
+    /// of points per unit area. The points are deterministic and evenly spaced.
+    /// For ellipsoids the points are stretched from a sphere along the semi-axes,
+    /// which makes the spacing less even for elongated shapes.
+    Fibonacci { density: f64 },
+    /// A Poisson disc distribution of points with an input `density` in number
+    /// of points per unit area. Points are randomly placed on the surface and kept
+    /// if they are not within sqrt(2 / (pi * density)) of an earlier point, which
+    /// matches the spacing used for planar sheets.
+    PoissonDisc { density: f64 },
+}
+
+impl SphereLattice {
+    fn density(&self) -> f64 {
+        match *self {
+            SphereLattice::Fibonacci { density } => density,
+            SphereLattice::PoissonDisc { density } => density,
+        }
+    }
+
+    /// Assert that points can be generated with the lattice.
+    ///
+    /// # Errors
+    /// Returns a `LatticeError` if the density is not positive and finite.
+    pub fn check(&self) -> Result<()> {
+        let density = self.density();
+
+        if density > 0.0 && density.is_finite() {
+            Ok(())
+        } else {
+            Err(GrafenError::LatticeError(format!(
+                "the density must be positive (is {})",
+                density
+            )))
+        }
+    }
+
+    /// Generate points on an ellipsoid with the input semi-axes, centered at origo.
+    fn generate(&self, semi_axes: Coord) -> Result<Vec<Coord>> {
+        let (a, b, c) = semi_axes.to_tuple();
+
+        if a <= 0.0 || b <= 0.0 || c <= 0.0 {
+            return Err(GrafenError::invalid_parameter(
+                "size",
+                "cannot create a shell of non-positive size",
+            ));
+        }
+
+        self.check()?;
+
+        match *self {
+            SphereLattice::Fibonacci { density } => {
+                let num_points = (density * ellipsoid_area(semi_axes)).round().max(1.0) as usize;
+
+                Ok(fibonacci_sphere(num_points)
+                    .into_iter()
+                    .map(|coord| Coord::new(a * coord.x, b * coord.y, c * coord.z))
+                    .collect())
+            }
+            SphereLattice::PoissonDisc { density } => {
+                let rmin = (2.0 / (PI * density)).sqrt();
+                Ok(poisson_disc_on_ellipsoid(semi_axes, rmin))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A hollow spherical shell.
+pub struct Sphere {
+    /// Name of component.
+    pub name: Option<String>,
+    /// Optional residue placed at each coordinate. If not set the sphere describes
+    /// a general collection of coordinates.
+    pub residue: Option<Residue>,
+    /// Distribution of points on the shell.
+    pub lattice: SphereLattice,
+    /// Optional orientation of the residues along the surface normal.
+    pub orientation: Option<Orientation>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Origin of the sphere. Located in its center.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Radius of the sphere.
+    pub radius: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// List of coordinates belonging to the sphere. Relative to the `origin`.
+    pub coords: Vec<Coord>,
+}
+
+impl Sphere {
+    /// Construct the sphere coordinates and return the object.
+    ///
+    /// # Errors
+    /// Returns an error if the radius or lattice density is non-positive.
+    pub fn construct(self) -> Result<Sphere> {
+        let semi_axes = Coord::new(self.radius, self.radius, self.radius);
+        let coords = self.lattice.generate(semi_axes)?;
+
+        Ok(Sphere { coords, ..self })
+    }
+
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        let diameter = 2.0 * self.radius;
+        Coord::new(diameter, diameter, diameter)
+    }
+}
+
+impl SurfaceNormal for Sphere {
+    fn normal_at(&self, coord: Coord) -> Coord {
+        coord.normalize()
+    }
+}
+
+impl Describe for Sphere {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Spherical surface of radius {:.2} at {})",
+            unwrap_name(&self.name),
+            self.radius,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Spherical surface)", unwrap_name(&self.name))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A hollow ellipsoidal shell, with its semi-axes along the x, y and z axes.
+pub struct Ellipsoid {
+    /// Name of component.
+    pub name: Option<String>,
+    /// Optional residue placed at each coordinate. If not set the ellipsoid describes
+    /// a general collection of coordinates.
+    pub residue: Option<Residue>,
+    /// Distribution of points on the shell.
+    pub lattice: SphereLattice,
+    /// Optional orientation of the residues along the surface normal.
+    pub orientation: Option<Orientation>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Origin of the ellipsoid. Located in its center.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Lengths of the semi-axes along x, y and z.
+    pub semi_axes: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// List of coordinates belonging to the ellipsoid. Relative to the `origin`.
+    pub coords: Vec<Coord>,
+}
+
+impl Ellipsoid {
+    /// Construct the ellipsoid coordinates and return the object.
+    ///
+    /// # Errors
+    /// Returns an error if any semi-axis or the lattice density is non-positive.
+    pub fn construct(self) -> Result<Ellipsoid> {
+        let coords = self.lattice.generate(self.semi_axes)?;
+
+        Ok(Ellipsoid { coords, ..self })
+    }
+
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        self.semi_axes * 2.0
+    }
+}
+
+impl SurfaceNormal for Ellipsoid {
+    fn normal_at(&self, coord: Coord) -> Coord {
+        let (a, b, c) = self.semi_axes.to_tuple();
+        Coord::new(coord.x / (a * a), coord.y / (b * b), coord.z / (c * c)).normalize()
+    }
+}
+
+impl Describe for Ellipsoid {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Ellipsoidal surface with semi-axes {} at {})",
+            unwrap_name(&self.name),
+            self.semi_axes,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Ellipsoidal surface)", unwrap_name(&self.name))
+    }
+}
+
+/// Approximate the surface area of an ellipsoid using Knud Thomsen's formula.
+/// The relative error is at most about 1%, and the area of a sphere is exact.
+fn ellipsoid_area(semi_axes: Coord) -> f64 {
+    let p = 1.6075;
+    let (a, b, c) = (
+        semi_axes.x.powf(p),
+        semi_axes.y.powf(p),
+        semi_axes.z.powf(p),
+    );
+
+    4.0 * PI * ((a * b + a * c + b * c) / 3.0).powf(1.0 / p)
+}
+
+/// Generate a number of points on a unit sphere along a golden spiral.
+fn fibonacci_sphere(num_points: usize) -> Vec<Coord> {
+    let golden_angle = PI * (3.0 - 5.0f64.sqrt());
+
+    (0..num_points)
+        .map(|i| {
+            let z = 1.0 - (2.0 * i as f64 + 1.0) / num_points as f64;
+            let radius = (1.0 - z * z).sqrt();
+            let angle = golden_angle * i as f64;
+
+            Coord::new(radius * angle.cos(), radius * angle.sin(), z)
+        })
+        .collect()
+}
+
+/// Sample a point uniformly by area on the surface of an ellipsoid centered at origo.
+///
+/// A point on the unit sphere is stretched onto the ellipsoid and kept with a probability
+/// proportional to how much the area is stretched at it.
+fn sample_on_ellipsoid<R: Rng>(semi_axes: Coord, rng: &mut R) -> Coord {
+    let (a, b, c) = semi_axes.to_tuple();
+    let max_weight = (b * c).max(a * c).max(a * b);
+
+    let range_z = Uniform::new_inclusive(-1.0, 1.0);
+    let range_angle = Uniform::new(0.0, 2.0 * PI);
+    let range_accept = Uniform::new(0.0, max_weight);
+
+    loop {
+        let z: f64 = range_z.sample(rng);
+        let angle = range_angle.sample(rng);
+        let radius = (1.0 - z * z).sqrt();
+        let (x, y) = (radius * angle.cos(), radius * angle.sin());
+
+        let weight = ((b * c * x).powi(2) + (a * c * y).powi(2) + (a * b * z).powi(2)).sqrt();
+
+        if range_accept.sample(rng) <= weight {
+            return Coord::new(a * x, b * y, c * z);
+        }
+    }
+}
+
+/// Randomly place points on an ellipsoid surface such that no points are closer than `rmin`
+/// to each other. Stops when a large number of consecutive candidates have been rejected,
+/// at which point the surface is close to saturated.
+fn poisson_disc_on_ellipsoid(semi_axes: Coord, rmin: f64) -> Vec<Coord> {
+    const MAX_REJECTIONS: usize = 1000;
+
+    let cell_of = |coord: Coord| {
+        (
+            (coord.x / rmin).floor() as i64,
+            (coord.y / rmin).floor() as i64,
+            (coord.z / rmin).floor() as i64,
+        )
+    };
+
+    let mut rng = thread_rng();
+    let mut grid: HashMap<(i64, i64, i64), Vec<Coord>> = HashMap::new();
+    let mut coords = Vec::new();
+    let mut num_rejections = 0;
+
+    while num_rejections < MAX_REJECTIONS {
+        let candidate = sample_on_ellipsoid(semi_axes, &mut rng);
+        let (i, j, k) = cell_of(candidate);
+
+        let collision = (i - 1..=i + 1)
+            .flat_map(|ci| (j - 1..=j + 1).map(move |cj| (ci, cj)))
+            .flat_map(|(ci, cj)| (k - 1..=k + 1).map(move |ck| (ci, cj, ck)))
+            .filter_map(|cell| grid.get(&cell))
+            .any(|cell_coords| {
+                cell_coords
+                    .iter()
+                    .any(|&coord| coord.distance(candidate) < rmin)
+            });
+
+        if collision {
+            num_rejections += 1;
+        } else {
+            grid.entry((i, j, k)).or_default().push(candidate);
+            coords.push(candidate);
+            num_rejections = 0;
+        }
+    }
+
+    coords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surface::NormalDirection;
+
+    fn setup_sphere(radius: f64, lattice: SphereLattice) -> Sphere {
+        Sphere {
+            name: None,
+            residue: None,
+            lattice,
+            orientation: None,
+            origin: Coord::ORIGO,
+            radius,
+            coords: vec![],
+        }
+    }
+
+    fn setup_ellipsoid(semi_axes: Coord, lattice: SphereLattice) -> Ellipsoid {
+        Ellipsoid {
+            name: None,
+            residue: None,
+            lattice,
+            orientation: None,
+            origin: Coord::ORIGO,
+            semi_axes,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn fibonacci_sphere_has_points_on_its_surface_with_the_input_density() {
+        let (radius, density) = (2.0, 10.0);
+        let sphere = setup_sphere(radius, SphereLattice::Fibonacci { density })
+            .construct()
+            .unwrap();
+
+        let expected = (4.0 * PI * radius.powi(2) * density).round() as usize;
+        assert_eq!(sphere.coords.len(), expected);
+
+        for coord in sphere.coords {
+            assert!((coord.norm() - radius).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn poisson_disc_sphere_has_points_on_its_surface_roughly_at_the_input_density() {
+        let (radius, density) = (2.0, 10.0);
+        let sphere = setup_sphere(radius, SphereLattice::PoissonDisc { density })
+            .construct()
+            .unwrap();
+
+        let expected = 4.0 * PI * radius.powi(2) * density;
+        let ratio = sphere.coords.len() as f64 / expected;
+        assert!(ratio >= 0.8 && ratio <= 1.2);
+
+        let rmin = (2.0 / (PI * density)).sqrt();
+
+        for (i, &coord) in sphere.coords.iter().enumerate() {
+            assert!((coord.norm() - radius).abs() < 1e-9);
+
+            for &other in &sphere.coords[i + 1..] {
+                assert!(coord.distance(other) >= rmin);
+            }
+        }
+    }
+
+    #[test]
+    fn ellipsoid_points_are_on_its_surface() {
+        let semi_axes = Coord::new(1.0, 2.0, 3.0);
+        let lattices = vec![
+            SphereLattice::Fibonacci { density: 5.0 },
+            SphereLattice::PoissonDisc { density: 5.0 },
+        ];
+
+        for lattice in lattices {
+            let ellipsoid = setup_ellipsoid(semi_axes, lattice).construct().unwrap();
+            assert!(!ellipsoid.coords.is_empty());
+
+            for coord in ellipsoid.coords {
+                let (x, y, z) = coord.to_tuple();
+                let r = (x / 1.0).powi(2) + (y / 2.0).powi(2) + (z / 3.0).powi(2);
+
+                assert!((r - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn ellipsoid_area_of_sphere_is_exact() {
+        let radius = 1.5;
+        let area = ellipsoid_area(Coord::new(radius, radius, radius));
+
+        assert!((area - 4.0 * PI * radius.powi(2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shells_with_bad_sizes_or_densities_are_errors() {
+        let lattice = SphereLattice::Fibonacci { density: 1.0 };
+        assert!(setup_sphere(0.0, lattice).construct().is_err());
+        assert!(setup_sphere(-1.0, lattice).construct().is_err());
+        assert!(setup_ellipsoid(Coord::new(1.0, 0.0, 1.0), lattice)
+            .construct()
+            .is_err());
+
+        let bad_lattice = SphereLattice::PoissonDisc { density: 0.0 };
+        assert!(setup_sphere(1.0, bad_lattice).construct().is_err());
+
+        let bad_lattice = SphereLattice::Fibonacci { density: f64::NAN };
+        assert!(setup_sphere(1.0, bad_lattice).construct().is_err());
+    }
+
+    #[test]
+    fn ellipsoid_normals_are_perpendicular_to_its_surface() {
+        let ellipsoid = setup_ellipsoid(
+            Coord::new(1.0, 2.0, 3.0),
+            SphereLattice::Fibonacci { density: 1.0 },
+        );
+
+        assert_eq!(
+            ellipsoid.normal_at(Coord::new(1.0, 0.0, 0.0)),
+            Coord::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            ellipsoid.normal_at(Coord::new(0.0, 0.0, -3.0)),
+            Coord::new(0.0, 0.0, -1.0)
+        );
+
+        // At (x, y, 0) on the ellipse x^2 + y^2 / 4 = 1 the tangent is along (-y, 4x, 0)
+        let (x, y) = (0.6, 1.6);
+        let normal = ellipsoid.normal_at(Coord::new(x, y, 0.0));
+
+        assert!(normal.dot(Coord::new(-y, 4.0 * x, 0.0)).abs() < 1e-9);
+        assert!((normal.norm() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn oriented_residues_are_rotated_along_the_surface_normal() {
+        let residue = resbase!["RES", ("A", 0.0, 0.0, 0.0), ("B", 0.0, 0.0, 1.0)];
+
+        let mut sphere = Sphere {
+            residue: Some(residue),
+            orientation: Some(Orientation {
+                axis: Coord::new(0.0, 0.0, 1.0),
+                direction: NormalDirection::Outward,
+            }),
+            ..setup_sphere(2.0, SphereLattice::Fibonacci { density: 1.0 })
+        }
+        .construct()
+        .unwrap();
+
+        for (res, &coord) in sphere.iter_residues().zip(sphere.coords.iter()) {
+            let atoms = res.get_atoms();
+            let (first, second) = (atoms[0].1, atoms[1].1);
+
+            assert_eq!(first, coord);
+            assert_eq!(second - first, coord.normalize());
+        }
+
+        // Inward orientation flips the residues
+        sphere.orientation = Some(Orientation {
+            axis: Coord::new(0.0, 0.0, 1.0),
+            direction: NormalDirection::Inward,
+        });
+
+        for (res, &coord) in sphere.iter_residues().zip(sphere.coords.iter()) {
+            let atoms = res.get_atoms();
+            assert_eq!(atoms[1].1 - atoms[0].1, -coord.normalize());
+        }
+
+        // The residue positions are recovered when assigned back to the component
+        let residues = sphere.iter_residues().collect::<Vec<_>>();
+        let coords = sphere.coords.clone();
+        sphere.assign_residues(&residues);
+
+        assert_eq!(sphere.coords, coords);
+    }
+}
@@ -12,15 +12,24 @@
 //! similarly grouped into molecules.
 
 use crate::{
-    coord::Coord,
+    coord::{Coord, Translate},
     database::{ComponentEntry, DataBase},
     describe::{describe_list, Describe},
+    element::atomic_mass,
+    error::{GrafenError, Result},
     iterator::{ResidueIter, ResidueIterOut},
+    output::{write_system_to_file, writer_from_path},
+    read_conf::{ConfType, ReadConf},
+    surface::{self, LatticeType},
+    volume::{self, prune_residues_from_volume, FillType, InclusionPolicy, Volume},
 };
 
 use colored::*;
 use serde_derive::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
 
 /// Main structure of a constructed system with several components.
 pub struct System {
@@ -32,12 +41,51 @@ pub struct System {
     pub database: DataBase,
     /// List of constructed components.
     pub components: Vec<ComponentEntry>,
+    /// How the size of the system box is set.
+    pub box_setting: BoxSetting,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Variants for how the size of a system box is set.
+pub enum BoxSetting {
+    /// The maximum size along each axis of all components.
+    Auto,
+    /// A fixed size.
+    Fixed(Coord),
+    /// The maximum size of all components with vacuum padding added along each axis.
+    Padded(Coord),
+}
+
+impl Default for BoxSetting {
+    fn default() -> BoxSetting {
+        BoxSetting::Auto
+    }
+}
+
+impl fmt::Display for BoxSetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BoxSetting::Auto => write!(f, "from components"),
+            BoxSetting::Fixed(_) => write!(f, "fixed"),
+            BoxSetting::Padded(padding) => write!(f, "from components with padding {}", padding),
+        }
+    }
 }
 
 impl<'a> System {
-    /// Calculate the total box size of the system as the maximum size along each axis
-    /// from all components.
+    /// Calculate the box size of the system from its setting.
+    ///
+    /// The size is otherwise the maximum size along each axis from all components.
     pub fn box_size(&self) -> Coord {
+        match self.box_setting {
+            BoxSetting::Auto => self.components_box_size(),
+            BoxSetting::Fixed(size) => size,
+            BoxSetting::Padded(padding) => self.components_box_size() + padding,
+        }
+    }
+
+    /// Calculate the maximum size along each axis from all components.
+    fn components_box_size(&self) -> Coord {
         self.components.iter().map(|object| object.box_size()).fold(
             Coord::new(0.0, 0.0, 0.0),
             |max_size, current| Coord {
@@ -48,6 +96,32 @@ impl<'a> System {
         )
     }
 
+    /// Count the atoms which lie outside of the system box.
+    pub fn num_atoms_outside_box(&self) -> u64 {
+        let box_size = self.box_size();
+        let is_outside = |coord: Coord| {
+            coord.x < 0.0
+                || coord.y < 0.0
+                || coord.z < 0.0
+                || coord.x > box_size.x
+                || coord.y > box_size.y
+                || coord.z > box_size.z
+        };
+
+        self.components
+            .iter()
+            .map(|component| {
+                let origin = component.get_origin();
+
+                component
+                    .iter_residues()
+                    .flat_map(|residue| residue.get_atoms().into_iter())
+                    .filter(|&(_, position)| is_outside(position + origin))
+                    .count() as u64
+            })
+            .sum()
+    }
+
     /// Print the system state to standard error.
     pub fn print_state(&self) {
         let (dx, dy, dz) = self.box_size().to_tuple();
@@ -58,7 +132,16 @@ impl<'a> System {
             "Output path  {}",
             self.output_path.to_str().unwrap_or("(Not set)")
         );
-        eprintln!("Box size     ({:.8}, {:.8}, {:.8})", dx, dy, dz);
+        eprintln!(
+            "Box size     ({:.8}, {:.8}, {:.8}) [{}]",
+            dx, dy, dz, self.box_setting
+        );
+
+        let net_charge = self.net_charge();
+        if net_charge != 0.0 {
+            eprintln!("Net charge   {:.4}", net_charge);
+        }
+
         eprintln!("");
 
         if self.components.len() > 0 {
@@ -75,6 +158,713 @@ impl<'a> System {
             .map(|object| object.num_atoms())
             .sum()
     }
+
+    /// Calculate the total mass of the system, or `None` if the mass of any atom is unknown.
+    pub fn mass(&self) -> Option<f64> {
+        self.components.iter().map(|object| object.mass()).sum()
+    }
+
+    /// Calculate the net charge of the system.
+    pub fn net_charge(&self) -> f64 {
+        self.components
+            .iter()
+            .map(|object| object.net_charge())
+            .sum()
+    }
+}
+
+/// Construct systems from code in the same way as from the interactive menu.
+///
+/// Components are constructed from the definitions in the database by their name,
+/// with the position and size which are given to the builder methods. The `add_*`
+/// methods construct surfaces and the `fill_*` methods fill volumes with the residue
+/// of their definition. If no fill type is given to a volume the default of its
+/// definition is used. Configuration files are read and reconstructed into a volume
+/// with `add_configuration`.
+///
+/// The builder methods return an error if no definition has the name or if it is
+/// of another kind, if no fill type is given or set, or if the component could
+/// not be constructed, eg. for a negative size.
+///
+/// # Examples
+/// ```
+/// # use grafen::coord::Coord;
+/// # use grafen::database::{library, DataBase};
+/// # use grafen::system::System;
+/// # use grafen::volume::InclusionPolicy;
+/// # use std::error::Error;
+/// # fn build(database: DataBase) -> Result<(), Box<dyn Error>> {
+/// # let path = std::env::temp_dir().join("grafen-system-example.gro");
+/// let mut system = System::new("Water on graphene")
+///     .with_database(database)
+///     .with_output_path(&path);
+///
+/// system
+///     .add_sheet("Graphene", Coord::ORIGO, 5.0, 5.0)?
+///     .fill_cuboid("Water (SPC/E)", Coord::new(0.0, 0.0, 0.5), Coord::new(5.0, 5.0, 3.0), None)?
+///     // Remove graphene residues with any atom within 0.3 nm of the water box
+///     .prune_against(0, 1, 0.3, InclusionPolicy::AnyAtom)?;
+///
+/// system.write()?;
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// # build(library()).unwrap();
+/// ```
+impl System {
+    /// Construct an empty system with a title and an empty database.
+    ///
+    /// The system is written to `conf.gro` unless another path is set.
+    pub fn new(title: &str) -> System {
+        System {
+            title: title.to_string(),
+            output_path: PathBuf::from("conf.gro"),
+            database: DataBase::new(),
+            components: vec![],
+            box_setting: BoxSetting::Auto,
+        }
+    }
+
+    /// Set the database of definitions to construct components from.
+    pub fn with_database(self, database: DataBase) -> System {
+        System { database, ..self }
+    }
+
+    /// Set how the size of the system box is set.
+    pub fn with_box(self, box_setting: BoxSetting) -> System {
+        System {
+            box_setting,
+            ..self
+        }
+    }
+
+    /// Set the path to write the system to.
+    pub fn with_output_path<P: Into<PathBuf>>(self, path: P) -> System {
+        System {
+            output_path: path.into(),
+            ..self
+        }
+    }
+
+    /// Get a copy of a component definition from the database by its name.
+    ///
+    /// # Errors
+    /// Returns an error if no definition has the name.
+    pub fn definition(&self, name: &str) -> Result<ComponentEntry> {
+        self.scoped_definition(name)
+            .map(|(definition, _)| definition)
+    }
+
+    /// Get a copy of a component definition along with the path of the database
+    /// which it is defined in.
+    fn scoped_definition(&self, name: &str) -> Result<(ComponentEntry, Option<PathBuf>)> {
+        self.database
+            .components()
+            .into_iter()
+            .find(|scoped| scoped.definition.name().as_deref() == Some(name))
+            .map(|scoped| (scoped.definition.clone(), scoped.path.cloned()))
+            .ok_or_else(|| GrafenError::DataBaseError {
+                path: None,
+                entry: Some(name.to_string()),
+                message: "no component definition has the name".to_string(),
+            })
+    }
+
+    /// Add a constructed component to the system.
+    pub fn add_component(&mut self, component: ComponentEntry) -> &mut System {
+        self.components.push(component);
+        self
+    }
+
+    /// Construct a sheet from a definition and add it to the system.
+    ///
+    /// # Errors
+    /// Returns an error for sheets with a blue noise lattice, which need a number
+    /// of residues and are added with `add_blue_noise_sheet`.
+    pub fn add_sheet(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        length: f64,
+        width: f64,
+    ) -> Result<&mut System> {
+        let component = self.construct_sheet(name, origin, length, width, None)?;
+        Ok(self.add_component(component))
+    }
+
+    /// Construct a sheet with a blue noise lattice of a number of residues from
+    /// a definition and add it to the system.
+    ///
+    /// # Errors
+    /// Returns an error if the definition does not have a blue noise lattice.
+    pub fn add_blue_noise_sheet(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        length: f64,
+        width: f64,
+        number: u64,
+    ) -> Result<&mut System> {
+        let component = self.construct_sheet(name, origin, length, width, Some(number))?;
+        Ok(self.add_component(component))
+    }
+
+    /// Construct a cuboid surface from a definition and add it to the system.
+    pub fn add_surface_cuboid(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        size: Coord,
+    ) -> Result<&mut System> {
+        let component = self.construct_definition(name, |cuboid: &mut surface::Cuboid| {
+            cuboid.origin = origin;
+            cuboid.size = size;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Construct a cylinder surface from a definition and add it to the system.
+    pub fn add_cylinder(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        radius: f64,
+        height: f64,
+    ) -> Result<&mut System> {
+        let component = self.construct_definition(name, |cylinder: &mut surface::Cylinder| {
+            cylinder.origin = origin;
+            cylinder.radius = radius;
+            cylinder.height = height;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Construct a spherical shell from a definition and add it to the system.
+    pub fn add_sphere_shell(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        radius: f64,
+    ) -> Result<&mut System> {
+        let component = self.construct_definition(name, |sphere: &mut surface::Sphere| {
+            sphere.origin = origin;
+            sphere.radius = radius;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Construct an ellipsoidal shell from a definition and add it to the system.
+    pub fn add_ellipsoid_shell(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        semi_axes: Coord,
+    ) -> Result<&mut System> {
+        let component = self.construct_definition(name, |ellipsoid: &mut surface::Ellipsoid| {
+            ellipsoid.origin = origin;
+            ellipsoid.semi_axes = semi_axes;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Construct a lipid bilayer from a definition and add it to the system.
+    pub fn add_bilayer(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        length: f64,
+        width: f64,
+    ) -> Result<&mut System> {
+        let component = self.construct_definition(name, |bilayer: &mut surface::Bilayer| {
+            bilayer.origin = origin;
+            bilayer.length = length;
+            bilayer.width = width;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a cuboid from a definition and add it to the system.
+    pub fn fill_cuboid(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        size: Coord,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component = self.fill_definition(name, fill_type, |cuboid: &mut volume::Cuboid| {
+            cuboid.origin = origin;
+            cuboid.size = size;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a cylinder from a definition and add it to the system.
+    pub fn fill_cylinder(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        radius: f64,
+        height: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component =
+            self.fill_definition(name, fill_type, |cylinder: &mut volume::Cylinder| {
+                cylinder.origin = origin;
+                cylinder.radius = radius;
+                cylinder.height = height;
+            })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a sphere from a definition and add it to the system.
+    pub fn fill_sphere(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        radius: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component =
+            self.fill_definition(name, fill_type, |sphere: &mut volume::Spheroid| {
+                sphere.origin = origin;
+                sphere.radius = radius;
+            })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill an ellipsoid from a definition and add it to the system.
+    pub fn fill_ellipsoid(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        semi_axes: Coord,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component =
+            self.fill_definition(name, fill_type, |ellipsoid: &mut volume::Ellipsoid| {
+                ellipsoid.origin = origin;
+                ellipsoid.semi_axes = semi_axes;
+            })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a cone from a definition and add it to the system. A cone with a top
+    /// radius is truncated into a frustum.
+    pub fn fill_cone(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        bottom_radius: f64,
+        top_radius: f64,
+        height: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component = self.fill_definition(name, fill_type, |cone: &mut volume::Cone| {
+            cone.origin = origin;
+            cone.bottom_radius = bottom_radius;
+            cone.top_radius = top_radius;
+            cone.height = height;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a torus from a definition and add it to the system.
+    pub fn fill_torus(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        major_radius: f64,
+        minor_radius: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component = self.fill_definition(name, fill_type, |torus: &mut volume::Torus| {
+            torus.origin = origin;
+            torus.major_radius = major_radius;
+            torus.minor_radius = minor_radius;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a capsule from a definition and add it to the system.
+    pub fn fill_capsule(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        radius: f64,
+        height: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component =
+            self.fill_definition(name, fill_type, |capsule: &mut volume::Capsule| {
+                capsule.origin = origin;
+                capsule.radius = radius;
+                capsule.height = height;
+            })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Fill a region of a slab from a definition and add it to the system.
+    pub fn fill_slab(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        thickness: f64,
+        length: f64,
+        width: f64,
+        fill_type: Option<FillType>,
+    ) -> Result<&mut System> {
+        let component = self.fill_definition(name, fill_type, |slab: &mut volume::Slab| {
+            slab.origin = origin;
+            slab.thickness = thickness;
+            slab.length = length;
+            slab.width = width;
+        })?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Read the configuration file of a definition, reconstruct it into a volume
+    /// and add it to the system with its displayed origin at the input origin.
+    ///
+    /// The volume of the definition is used if none is given. Residues are kept
+    /// in the volume by the inclusion policy.
+    ///
+    /// # Errors
+    /// Returns an error if the definition is not a configuration file or if the
+    /// file could not be read.
+    pub fn add_configuration(
+        &mut self,
+        name: &str,
+        origin: Coord,
+        volume: Option<ConfType>,
+        policy: InclusionPolicy,
+    ) -> Result<&mut System> {
+        let (definition, database_path) = self.scoped_definition(name)?;
+        let conf = ReadConf::from_entry(definition).ok_or_else(|| not_a(name, ReadConf::KIND))?;
+
+        let component =
+            construct_configuration(conf, database_path.as_deref(), origin, volume, policy)?;
+
+        Ok(self.add_component(component))
+    }
+
+    /// Get a sheet definition from the database, set its geometry and construct it.
+    ///
+    /// The number of residues must be given for, and only for, blue noise lattices.
+    fn construct_sheet(
+        &self,
+        name: &str,
+        origin: Coord,
+        length: f64,
+        width: f64,
+        number: Option<u64>,
+    ) -> Result<ComponentEntry> {
+        let mut sheet = self.get_definition::<surface::Sheet>(name)?;
+
+        sheet.origin = origin;
+        sheet.length = length;
+        sheet.width = width;
+
+        match (&mut sheet.lattice, number) {
+            (&mut LatticeType::BlueNoise { ref mut number }, Some(value)) => *number = value,
+            (&mut LatticeType::BlueNoise { .. }, None) => {
+                return Err(GrafenError::invalid_parameter(
+                    "lattice",
+                    "a blue noise sheet needs a number of residues, use `add_blue_noise_sheet`",
+                ));
+            }
+            (_, Some(_)) => return Err(not_a(name, "blue noise sheet")),
+            (_, None) => (),
+        }
+
+        construct_component(sheet.into(), None)
+    }
+
+    /// Get a definition of a kind from the database, set its geometry and construct it.
+    fn construct_definition<S, F>(&self, name: &str, set: F) -> Result<ComponentEntry>
+    where
+        S: SurfaceDefinition,
+        F: FnOnce(&mut S),
+    {
+        let mut surface = self.get_definition::<S>(name)?;
+        set(&mut surface);
+
+        construct_component(surface.into(), None)
+    }
+
+    /// Get a volume definition from the database, set its geometry and fill it with
+    /// the fill type or the default of the definition.
+    fn fill_definition<V, F>(
+        &self,
+        name: &str,
+        fill_type: Option<FillType>,
+        set: F,
+    ) -> Result<ComponentEntry>
+    where
+        V: VolumeDefinition,
+        F: FnOnce(&mut V),
+    {
+        let mut volume = self.get_definition::<V>(name)?;
+        set(&mut volume);
+
+        construct_component(volume.into(), fill_type)
+    }
+
+    fn get_definition<D: Definition>(&self, name: &str) -> Result<D> {
+        D::from_entry(self.definition(name)?).ok_or_else(|| not_a(name, D::KIND))
+    }
+
+    /// Remove the residues of a component which are inside a volume component by
+    /// the inclusion policy, with a margin added around the volume. The components
+    /// are given by their index in the system.
+    ///
+    /// # Errors
+    /// Returns an error if a component does not exist or if the second is not a volume.
+    pub fn prune_against(
+        &mut self,
+        index: usize,
+        volume_index: usize,
+        margin: f64,
+        policy: InclusionPolicy,
+    ) -> Result<&mut System> {
+        let volume = self
+            .components
+            .get(volume_index)
+            .ok_or_else(|| no_component(volume_index))?
+            .to_volume(margin)
+            .ok_or_else(|| {
+                GrafenError::invalid_parameter(
+                    "volume",
+                    &format!("component {} is not a volume", volume_index),
+                )
+            })?;
+
+        let component = self
+            .components
+            .get_mut(index)
+            .ok_or_else(|| no_component(index))?;
+
+        let remaining = prune_residues_from_volume(&*component, volume.as_ref(), policy);
+        component.assign_residues(&remaining);
+
+        Ok(self)
+    }
+
+    /// Write the system to its output path, in the format of the path extension.
+    ///
+    /// Returns the path of the written file.
+    pub fn write(&self) -> Result<PathBuf> {
+        let format = writer_from_path(&self.output_path);
+        write_system_to_file(self, &self.output_path, format.as_ref())
+    }
+}
+
+/// Used to skip generated fields of components which have not been set, ie. in definitions.
+pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Construct a component from a definition with its geometry set.
+///
+/// Surfaces are constructed and volumes are filled with the fill type or the default
+/// of their definition. This is how components are constructed both by the `System`
+/// builder and from the menus.
+///
+/// # Errors
+/// Returns an error if the component could not be constructed or if no fill type
+/// is given or set for a volume. Configuration files are read from disk and have
+/// to be constructed with `construct_configuration`.
+pub fn construct_component(
+    definition: ComponentEntry,
+    fill_type: Option<FillType>,
+) -> Result<ComponentEntry> {
+    match definition {
+        ComponentEntry::SurfaceSheet(object) => construct_surface(object).map(|c| c.with_pbc()),
+        ComponentEntry::SurfaceCuboid(object) => construct_surface(object),
+        ComponentEntry::SurfaceCylinder(object) => construct_surface(object),
+        ComponentEntry::SurfaceSphere(object) => construct_surface(object),
+        ComponentEntry::SurfaceEllipsoid(object) => construct_surface(object),
+        ComponentEntry::SurfaceBilayer(object) => construct_surface(object).map(|c| c.with_pbc()),
+        ComponentEntry::VolumeCuboid(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeCylinder(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeSpheroid(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeEllipsoid(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeCone(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeTorus(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeCapsule(object) => fill_volume(object, fill_type),
+        ComponentEntry::VolumeSlab(object) => fill_volume(object, fill_type),
+        ComponentEntry::ConfigurationFile(_) => Err(GrafenError::invalid_parameter(
+            "definition",
+            "configuration files are read with `construct_configuration`",
+        )),
+    }
+}
+
+/// Read the configuration file of a definition and reconstruct it into a volume,
+/// with its displayed origin at the input origin.
+///
+/// The volume of the definition is used if none is given. Residues are kept
+/// in the volume by the inclusion policy. Relative paths to the file are relative
+/// to the directory of the database path.
+///
+/// # Errors
+/// Returns an error if the file could not be read.
+pub fn construct_configuration(
+    definition: ReadConf,
+    database_path: Option<&Path>,
+    origin: Coord,
+    volume: Option<ConfType>,
+    policy: InclusionPolicy,
+) -> Result<ComponentEntry> {
+    let path = database_path
+        .and_then(|path| path.parent())
+        .map(|dir| dir.join(&definition.path))
+        .unwrap_or(definition.path);
+
+    let mut conf = ReadConf::from_gromos87(&path)?;
+
+    conf.description = definition.description;
+    conf.reconstruct(volume.unwrap_or(definition.volume_type), policy);
+
+    // Make sure that the origin is adjusted to that which was asked for
+    let displayed_origin = conf.get_displayed_origin();
+    conf.translate_in_place(origin - displayed_origin);
+
+    Ok(ComponentEntry::from(conf))
+}
+
+fn construct_surface<S: SurfaceDefinition>(surface: S) -> Result<ComponentEntry> {
+    surface.construct_surface().map(Into::into)
+}
+
+fn fill_volume<V: VolumeDefinition>(
+    volume: V,
+    fill_type: Option<FillType>,
+) -> Result<ComponentEntry> {
+    let fill_type = fill_type_or_default(fill_type, volume.default_fill_type(), volume.residue())?;
+
+    volume.fill(fill_type).map(Into::into)
+}
+
+/// Get the fill type to fill a volume definition with, which defaults to that of
+/// the definition.
+fn fill_type_or_default(
+    fill_type: Option<FillType>,
+    default: Option<FillType>,
+    residue: Option<&Residue>,
+) -> Result<FillType> {
+    fill_type
+        .or(default)
+        .ok_or_else(|| {
+            GrafenError::invalid_parameter(
+                "fill type",
+                "no fill type was given and the definition has no default",
+            )
+        })?
+        .check(residue)
+}
+
+/// Component definitions which are constructed by the `System` builder.
+trait Definition: Sized + Into<ComponentEntry> {
+    /// Kind of definition, used in errors.
+    const KIND: &'static str;
+
+    /// Get the definition from an entry of its kind.
+    fn from_entry(entry: ComponentEntry) -> Option<Self>;
+}
+
+/// Surface definitions, which are constructed.
+trait SurfaceDefinition: Definition {
+    fn construct_surface(self) -> Result<Self>;
+}
+
+/// Volume definitions, which are filled with their residue.
+trait VolumeDefinition: Definition + Volume {
+    fn default_fill_type(&self) -> Option<FillType>;
+    fn residue(&self) -> Option<&Residue>;
+}
+
+/// Implement `Definition` for component entries along with the trait of their family.
+macro_rules! impl_definition {
+    (@definition $class:ty, $entry:ident, $kind:expr) => {
+        impl Definition for $class {
+            const KIND: &'static str = $kind;
+
+            fn from_entry(entry: ComponentEntry) -> Option<Self> {
+                match entry {
+                    ComponentEntry::$entry(object) => Some(object),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    (surface $class:ty => $entry:ident, $kind:expr) => {
+        impl_definition!(@definition $class, $entry, $kind);
+
+        impl SurfaceDefinition for $class {
+            fn construct_surface(self) -> Result<Self> {
+                self.construct()
+            }
+        }
+    };
+
+    (volume $class:ty => $entry:ident, $kind:expr) => {
+        impl_definition!(@definition $class, $entry, $kind);
+
+        impl VolumeDefinition for $class {
+            fn default_fill_type(&self) -> Option<FillType> {
+                self.fill_type
+            }
+
+            fn residue(&self) -> Option<&Residue> {
+                self.residue.as_ref()
+            }
+        }
+    };
+}
+
+impl_definition!(surface surface::Sheet => SurfaceSheet, "sheet");
+impl_definition!(surface surface::Cuboid => SurfaceCuboid, "cuboid surface");
+impl_definition!(surface surface::Cylinder => SurfaceCylinder, "cylinder surface");
+impl_definition!(surface surface::Sphere => SurfaceSphere, "spherical shell");
+impl_definition!(surface surface::Ellipsoid => SurfaceEllipsoid, "ellipsoidal shell");
+impl_definition!(surface surface::Bilayer => SurfaceBilayer, "bilayer");
+impl_definition!(volume volume::Cuboid => VolumeCuboid, "cuboid volume");
+impl_definition!(volume volume::Cylinder => VolumeCylinder, "cylinder volume");
+impl_definition!(volume volume::Spheroid => VolumeSpheroid, "spherical volume");
+impl_definition!(volume volume::Ellipsoid => VolumeEllipsoid, "ellipsoidal volume");
+impl_definition!(volume volume::Cone => VolumeCone, "conical volume");
+impl_definition!(volume volume::Torus => VolumeTorus, "toroidal volume");
+impl_definition!(volume volume::Capsule => VolumeCapsule, "capsule volume");
+impl_definition!(volume volume::Slab => VolumeSlab, "slab volume");
+impl_definition!(@definition ReadConf, ConfigurationFile, "configuration file");
+
+fn not_a(name: &str, kind: &str) -> GrafenError {
+    GrafenError::DataBaseError {
+        path: None,
+        entry: Some(name.to_string()),
+        message: format!("the definition is not a {}", kind),
+    }
+}
+
+fn no_component(index: usize) -> GrafenError {
+    GrafenError::invalid_parameter(
+        "component index",
+        &format!("the system has no component {}", index),
+    )
 }
 
 /// Methods for yielding atoms and output information from constructed objects.
@@ -104,6 +894,30 @@ pub trait Component<'a> {
 
     /// Return the component with its coordinates adjusted to lie within its box.
     fn with_pbc(self) -> Self;
+
+    /// Return the total mass of the object, or `None` if the mass of any atom is unknown.
+    fn mass(&self) -> Option<f64> {
+        self.iter_residues()
+            .map(|res| {
+                res.get_metadata()
+                    .iter()
+                    .map(|meta| meta.mass())
+                    .sum::<Option<f64>>()
+            })
+            .sum()
+    }
+
+    /// Return the net charge of the object. Atoms without a set charge are neutral.
+    fn net_charge(&self) -> f64 {
+        self.iter_residues()
+            .map(|res| {
+                res.get_metadata()
+                    .iter()
+                    .map(|meta| meta.charge.unwrap_or(0.0))
+                    .sum::<f64>()
+            })
+            .sum()
+    }
 }
 
 #[macro_export]
@@ -116,84 +930,115 @@ pub trait Component<'a> {
 ///     coords: [Coord]
 /// }
 /// and the method `calc_box_size`.
+///
+/// Surface objects whose residues can be oriented along their normal are marked
+/// with `@oriented`. They additionally have to contain the field
+/// `orientation: Option<Orientation>` and implement `SurfaceNormal`.
 macro_rules! impl_component {
-    ( $( $class:path ),+ ) => {
+    ( @oriented $( $class:path ),+ ) => {
         $(
-            impl<'a> Component<'a> for $class {
-                /// Assign a set of input residues to the component.
-                ///
-                /// # Downcasting information
-                /// Note that some information may be downcast in the particular implementation
-                /// for this `Component`. This is due to the base of it saving only as single
-                /// coordinate for every `Residue` position: the residue atoms are exactly
-                /// relative to this position, not set explicitly.
-                ///
-                /// However, the residues which result from calling eg. `iter_residues()`
-                /// on components results in objects which carry information about atoms
-                /// with all of their positions set explicitly (but relative to the containing
-                /// `Component`). This explicit information will be lost as it is downcast
-                /// to a single position per residue, with residue-relative positions that are
-                /// shared by all atoms.
-                ///
-                /// The component-relative position for the iterated residues is taken as
-                /// the first atom of every object. That position is subtracted by
-                /// the residue-relative position of the atom of the `Residue` that is set
-                /// to the `Component`, to find the component-relative position of the residue.
-                ///
-                /// Furthermore, note that any information about several different residue
-                /// types in the iterating object is lost. This object assumes that the
-                /// current set `Residue` is the only existing residue in the iterator.
-                ///
-                /// # Panics
-                /// Panics if no `Residue` is set to the `Component`, if the `Residue`
-                /// contains no `Atom`s or if any residue in the iterating object contains
-                /// no atoms. This *should* never happen since we should always assign
-                /// residues to complete objects of the same type, but we could consider
-                /// this to returning a `Result` to guard against it.
-                fn assign_residues(&mut self, residues: &[ResidueIterOut]) {
-                    let residue = self.residue.clone().unwrap();
-
-                    self.coords = residues.iter()
-                        .map(|res| res.get_atoms()[0].1 - residue.atoms[0].position)
-                        .collect::<Vec<_>>();
+            $crate::impl_component!(@impl $class, {
+                fn iter_residues(&self) -> ResidueIter {
+                    match (&self.residue, self.orientation) {
+                        (&None, _) => ResidueIter::None,
+                        (&Some(ref code), None) => ResidueIter::Component(code, self.coords.iter()),
+                        (&Some(ref code), Some(orientation)) => {
+                            let placements = self.coords
+                                .iter()
+                                .map(|&coord| {
+                                    (code, coord, orientation.rotation(self.normal_at(coord)))
+                                })
+                                .collect::<Vec<_>>();
+
+                            ResidueIter::Rotated(placements.into_iter())
+                        }
+                    }
                 }
+            });
+        )*
+    };
+    ( @impl $class:path, { $( $iter_residues:tt )* } ) => {
+        impl<'a> Component<'a> for $class {
+            /// Assign a set of input residues to the component.
+            ///
+            /// # Downcasting information
+            /// Note that some information may be downcast in the particular implementation
+            /// for this `Component`. This is due to the base of it saving only as single
+            /// coordinate for every `Residue` position: the residue atoms are exactly
+            /// relative to this position, not set explicitly.
+            ///
+            /// However, the residues which result from calling eg. `iter_residues()`
+            /// on components results in objects which carry information about atoms
+            /// with all of their positions set explicitly (but relative to the containing
+            /// `Component`). This explicit information will be lost as it is downcast
+            /// to a single position per residue, with residue-relative positions that are
+            /// shared by all atoms.
+            ///
+            /// The component-relative position for the iterated residues is taken as
+            /// the first atom of every object. That position is subtracted by
+            /// the residue-relative position of the atom of the `Residue` that is set
+            /// to the `Component`, to find the component-relative position of the residue.
+            ///
+            /// Furthermore, note that any information about several different residue
+            /// types in the iterating object is lost. This object assumes that the
+            /// current set `Residue` is the only existing residue in the iterator.
+            ///
+            /// # Panics
+            /// Panics if no `Residue` is set to the `Component`, if the `Residue`
+            /// contains no `Atom`s or if any residue in the iterating object contains
+            /// no atoms. This *should* never happen since we should always assign
+            /// residues to complete objects of the same type, but we could consider
+            /// this to returning a `Result` to guard against it.
+            fn assign_residues(&mut self, residues: &[ResidueIterOut]) {
+                let residue = self.residue.clone().unwrap();
+
+                self.coords = residues.iter()
+                    .map(|res| res.get_atoms()[0].1 - residue.atoms[0].position)
+                    .collect::<Vec<_>>();
+            }
 
-                fn box_size(&self) -> Coord {
-                    self.calc_box_size() + self.origin
-                }
+            fn box_size(&self) -> Coord {
+                self.calc_box_size() + self.origin
+            }
 
-                fn get_origin(&self) -> Coord {
-                    self.origin
-                }
+            fn get_origin(&self) -> Coord {
+                self.origin
+            }
 
-                fn iter_residues(&self) -> ResidueIter {
-                    match self.residue {
-                        None => ResidueIter::None,
-                        Some(ref code) => ResidueIter::Component(code, self.coords.iter()),
-                    }
-                }
+            $( $iter_residues )*
 
-                fn num_atoms(&self) -> u64 {
-                    let residue_len = self.residue
-                        .as_ref()
-                        .map(|res| res.atoms.len())
-                        .unwrap_or(0);
+            fn num_atoms(&self) -> u64 {
+                let residue_len = self.residue
+                    .as_ref()
+                    .map(|res| res.atoms.len())
+                    .unwrap_or(0);
 
-                    (residue_len * self.coords.len()) as u64
-                }
+                (residue_len * self.coords.len()) as u64
+            }
 
-                fn with_pbc(mut self) -> Self {
-                    let box_size = self.calc_box_size();
+            fn with_pbc(mut self) -> Self {
+                let box_size = self.calc_box_size();
 
-                    self.coords
-                        .iter_mut()
-                        .for_each(|c| *c = c.with_pbc(box_size));
+                self.coords
+                    .iter_mut()
+                    .for_each(|c| *c = c.with_pbc(box_size));
 
-                    self
-                }
+                self
             }
+        }
+    };
+    ( $( $class:path ),+ ) => {
+        $(
+            $crate::impl_component!(@impl $class, {
+                fn iter_residues(&self) -> ResidueIter {
+                    match self.residue {
+                        None => ResidueIter::None,
+                        Some(ref code) => ResidueIter::Component(code, self.coords.iter()),
+                    }
+                }
+            });
         )*
-    }
+    };
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -204,11 +1049,62 @@ pub struct Atom {
     pub code: String,
     /// Relative position.
     pub position: Coord,
+    #[serde(flatten)]
+    /// Optional physical and force field information about the atom.
+    pub metadata: AtomMetadata,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+/// Optional information about an atom. Unset values are not written to the `DataBase`.
+pub struct AtomMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Chemical element symbol.
+    pub element: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Mass in atomic mass units (g/mol).
+    pub mass: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Partial charge in units of the elementary charge.
+    pub charge: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Force field atom type.
+    pub atom_type: Option<String>,
+}
+
+impl AtomMetadata {
+    /// Return the mass of the atom. If no mass is set it is looked up from the element.
+    pub fn mass(&self) -> Option<f64> {
+        self.mass.or_else(|| {
+            self.element
+                .as_ref()
+                .and_then(|element| atomic_mass(element))
+        })
+    }
 }
 
 impl Describe for Atom {
     fn describe(&self) -> String {
-        format!("{} {}", self.code, self.position)
+        let meta = &self.metadata;
+        let mut info = Vec::new();
+
+        if let Some(ref element) = meta.element {
+            info.push(element.clone());
+        }
+        if let Some(mass) = meta.mass {
+            info.push(format!("{} g/mol", mass));
+        }
+        if let Some(charge) = meta.charge {
+            info.push(format!("{} e", charge));
+        }
+        if let Some(ref atom_type) = meta.atom_type {
+            info.push(format!("type {}", atom_type));
+        }
+
+        if info.is_empty() {
+            format!("{} {}", self.code, self.position)
+        } else {
+            format!("{} {} [{}]", self.code, self.position, info.join(", "))
+        }
     }
 
     fn describe_short(&self) -> String {
@@ -223,6 +1119,21 @@ pub struct Residue {
     pub atoms: Vec<Atom>,
 }
 
+impl Residue {
+    /// Return the mass of the residue, or `None` if the mass of any atom is unknown.
+    pub fn mass(&self) -> Option<f64> {
+        self.atoms.iter().map(|atom| atom.metadata.mass()).sum()
+    }
+
+    /// Return the net charge of the residue. Atoms without a set charge are neutral.
+    pub fn charge(&self) -> f64 {
+        self.atoms
+            .iter()
+            .map(|atom| atom.metadata.charge.unwrap_or(0.0))
+            .sum()
+    }
+}
+
 impl Describe for Residue {
     fn describe(&self) -> String {
         format!("{} ({} atoms)", self.code, self.atoms.len())
@@ -244,13 +1155,21 @@ impl Describe for Residue {
 /// ```
 /// # #[macro_use] extern crate grafen;
 /// # use grafen::coord::Coord;
-/// # use grafen::system::{Atom, Residue};
+/// # use grafen::system::{Atom, AtomMetadata, Residue};
 /// # fn main() {
 /// let expect = Residue {
 ///     code: "RES".to_string(),
 ///     atoms: vec![
-///         Atom { code: "A".to_string(), position: Coord::new(0.0, 0.0, 0.0) },
-///         Atom { code: "B".to_string(), position: Coord::new(1.0, 2.0, 3.0) }
+///         Atom {
+///             code: "A".to_string(),
+///             position: Coord::new(0.0, 0.0, 0.0),
+///             metadata: AtomMetadata::default(),
+///         },
+///         Atom {
+///             code: "B".to_string(),
+///             position: Coord::new(1.0, 2.0, 3.0),
+///             metadata: AtomMetadata::default(),
+///         }
 ///     ],
 /// };
 ///
@@ -275,6 +1194,7 @@ macro_rules! resbase {
                     Atom {
                         code: $atname.to_string(),
                         position: Coord::new($x, $y, $z),
+                        metadata: $crate::system::AtomMetadata::default(),
                     }
                 );
             )*
@@ -290,7 +1210,6 @@ macro_rules! resbase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::coord::Translate;
     use crate::volume::Cuboid;
 
     #[test]
@@ -301,10 +1220,12 @@ mod tests {
                 Atom {
                     code: "A1".to_string(),
                     position: Coord::new(0.0, 0.0, 0.0),
+                    metadata: AtomMetadata::default(),
                 },
                 Atom {
                     code: "A2".to_string(),
                     position: Coord::new(0.0, 1.0, 2.0),
+                    metadata: AtomMetadata::default(),
                 },
             ],
         };
@@ -382,6 +1303,52 @@ mod tests {
         assert!(without_residue.iter_residues().next().is_none());
     }
 
+    #[test]
+    fn atom_metadata_is_optional_in_serialized_atoms() {
+        let atom: Atom = serde_json::from_str(
+            r#"{"code": "OW", "position": {"x": 0.0, "y": 0.0, "z": 0.0}, "element": "O", "charge": -0.8}"#,
+        )
+        .unwrap();
+
+        assert_eq!(atom.metadata.element, Some("O".to_string()));
+        assert_eq!(atom.metadata.charge, Some(-0.8));
+        assert_eq!(atom.metadata.mass, None);
+
+        let serialized = serde_json::to_string(&atom).unwrap();
+        assert!(!serialized.contains("mass"));
+        assert!(!serialized.contains("atom_type"));
+        assert_eq!(serde_json::from_str::<Atom>(&serialized).unwrap(), atom);
+
+        let atom: Atom =
+            serde_json::from_str(r#"{"code": "OW", "position": {"x": 0.0, "y": 0.0, "z": 0.0}}"#)
+                .unwrap();
+        assert_eq!(atom.metadata, AtomMetadata::default());
+    }
+
+    #[test]
+    fn mass_and_charge_of_residues_and_components() {
+        let mut residue = resbase!["SOL", ("OW", 0.0, 0.0, 0.0), ("HW", 0.1, 0.0, 0.0)];
+        assert_eq!(residue.mass(), None);
+        assert_eq!(residue.charge(), 0.0);
+
+        residue.atoms[0].metadata.element = Some("O".to_string());
+        residue.atoms[0].metadata.charge = Some(-1.0);
+        residue.atoms[1].metadata.mass = Some(1.0);
+        residue.atoms[1].metadata.charge = Some(0.5);
+
+        assert!((residue.mass().unwrap() - 16.999).abs() < 1e-9);
+        assert_eq!(residue.charge(), -0.5);
+
+        let cuboid = Cuboid {
+            residue: Some(residue),
+            coords: vec![Coord::ORIGO; 4],
+            ..Cuboid::default()
+        };
+
+        assert!((cuboid.mass().unwrap() - 4.0 * 16.999).abs() < 1e-9);
+        assert_eq!(cuboid.net_charge(), -2.0);
+    }
+
     #[test]
     fn num_atoms_in_macro_generated_impl_objects() {
         // An object with 2 residues * 2 atoms / residue = 4 atoms
@@ -448,6 +1415,7 @@ mod tests {
                     (Rc::clone(&atom1_name), res1_position + atom1_relative),
                     (Rc::clone(&atom2_name), res1_position + atom2_relative),
                 ],
+                vec![AtomMetadata::default(); 2],
             ),
             ResidueIterOut::FromComp(
                 Rc::clone(&res_name),
@@ -455,6 +1423,7 @@ mod tests {
                     (Rc::clone(&atom1_name), res2_position + atom1_relative),
                     (Rc::clone(&atom2_name), res2_position + atom2_relative),
                 ],
+                vec![AtomMetadata::default(); 2],
             ),
         ];
 
@@ -478,7 +1447,7 @@ mod tests {
             residue: None,
             size,
             origin,
-            density: None,
+            fill_type: None,
             coords: vec![],
         };
 
@@ -520,7 +1489,7 @@ mod tests {
     //         residue: Some(residue1.clone()),
     //         origin: Coord::default(),
     //         size: Coord::default(),
-    //         density: None,
+    //         fill_type: None,
     //         coords: vec![Coord::default(), Coord::default(), Coord::default()],
     //     };
     //
@@ -534,7 +1503,7 @@ mod tests {
     //         residue: Some(residue2.clone()),
     //         origin: origin,
     //         size: Coord::default(),
-    //         density: None,
+    //         fill_type: None,
     //         coords: vec![position, Coord::default()],
     //     };
     //
@@ -569,7 +1538,7 @@ mod tests {
             residue: Some(residue.clone()),
             origin: Coord::default(),
             size: Coord::default(),
-            density: None,
+            fill_type: None,
             coords: vec![Coord::default(), Coord::default(), Coord::default()],
         });
 
@@ -578,6 +1547,7 @@ mod tests {
             output_path: PathBuf::new(),
             database: DataBase::new(),
             components: vec![component.clone(), component.clone()],
+            box_setting: BoxSetting::Auto,
         };
 
         assert_eq!(12, system.num_atoms());
@@ -590,7 +1560,7 @@ mod tests {
             residue: None,
             origin: Coord::new(0.0, 0.0, 0.0),
             size: Coord::new(5.0, 5.0, 5.0),
-            density: None,
+            fill_type: None,
             coords: vec![],
         });
 
@@ -599,7 +1569,7 @@ mod tests {
             residue: None,
             origin: Coord::new(3.0, 3.0, 3.0),
             size: Coord::new(3.0, 2.0, 1.0),
-            density: None,
+            fill_type: None,
             coords: vec![],
         });
 
@@ -608,8 +1578,329 @@ mod tests {
             output_path: PathBuf::new(),
             database: DataBase::new(),
             components: vec![component1.clone(), component2.clone()],
+            box_setting: BoxSetting::Auto,
         };
 
         assert_eq!(Coord::new(6.0, 5.0, 5.0), system.box_size());
     }
+
+    #[test]
+    fn box_size_of_system_is_fixed_or_padded_by_the_setting() {
+        let component = ComponentEntry::VolumeCuboid(Cuboid {
+            residue: Some(resbase!["RES", ("A", 0.0, 0.0, 0.0)]),
+            size: Coord::new(2.0, 2.0, 2.0),
+            coords: vec![Coord::new(0.5, 0.5, 0.5), Coord::new(1.5, 1.5, 1.5)],
+            ..Cuboid::default()
+        });
+
+        let mut system = System::new("").with_box(BoxSetting::Padded(Coord::new(0.0, 0.0, 3.0)));
+        system.add_component(component);
+
+        assert_eq!(Coord::new(2.0, 2.0, 5.0), system.box_size());
+        assert_eq!(0, system.num_atoms_outside_box());
+
+        system.box_setting = BoxSetting::Fixed(Coord::new(1.0, 1.0, 1.0));
+        assert_eq!(Coord::new(1.0, 1.0, 1.0), system.box_size());
+        assert_eq!(1, system.num_atoms_outside_box());
+    }
+
+    #[test]
+    fn system_builder_constructs_fills_and_prunes_components_from_definitions() {
+        let mut system = System::new("System").with_database(crate::database::library());
+
+        system
+            .add_sheet("Graphene", Coord::ORIGO, 2.0, 2.0)
+            .unwrap()
+            .fill_cuboid(
+                "Water (SPC/E)",
+                Coord::new(0.0, 0.0, 1.0),
+                Coord::new(2.0, 2.0, 2.0),
+                Some(FillType::NumCoords(10)),
+            )
+            .unwrap();
+
+        assert_eq!(system.components.len(), 2);
+        assert!(system.components[0].num_atoms() > 0);
+        assert_eq!(system.components[1].num_atoms(), 30);
+
+        // The sheet at z = 0 is 1 nm below the water cuboid. A margin of 1.5 nm extends
+        // the pruning volume past the sheet, so that its atoms are not on the boundary
+        system
+            .prune_against(0, 1, 1.5, InclusionPolicy::AnyAtom)
+            .unwrap();
+        assert_eq!(system.components[0].num_atoms(), 0);
+    }
+
+    #[test]
+    fn system_builder_returns_errors_for_bad_definitions() {
+        let mut system = System::new("System").with_database(crate::database::library());
+
+        assert!(system
+            .add_sheet("Not a definition", Coord::ORIGO, 1.0, 1.0)
+            .is_err());
+        assert!(system
+            .add_sheet("Water (SPC/E)", Coord::ORIGO, 1.0, 1.0)
+            .is_err());
+        assert!(system
+            .fill_cuboid("Graphene", Coord::ORIGO, Coord::new(1.0, 1.0, 1.0), None)
+            .is_err());
+
+        system
+            .add_sheet("Graphene", Coord::ORIGO, 1.0, 1.0)
+            .unwrap();
+        assert!(system
+            .prune_against(0, 0, 0.0, InclusionPolicy::AnyAtom)
+            .is_err());
+        assert!(system
+            .prune_against(0, 1, 0.0, InclusionPolicy::AnyAtom)
+            .is_err());
+    }
+
+    const WATER: &str = r#"{
+        "code": "SOL",
+        "atoms": [
+            { "code": "OW", "position": { "x": 0.0, "y": 0.0, "z": 0.0 } },
+            { "code": "HW1", "position": { "x": 0.1, "y": 0.0, "z": 0.0 } }
+        ]
+    }"#;
+
+    /// Add a definition from its JSON form to the database of a system.
+    fn add_definition(system: &mut System, kind: &str, fields: &str) {
+        let json = format!(
+            r#"{{ "{}": {{ "name": "{}", "residue": {}, {} }} }}"#,
+            kind, kind, WATER, fields
+        );
+
+        let definition = serde_json::from_str::<ComponentEntry>(&json).unwrap();
+        system.database.component_defs.push(definition);
+    }
+
+    #[test]
+    fn system_builder_fills_every_kind_of_volume() {
+        let mut system = System::new("System");
+        let fill_type = r#""fill_type": { "NumCoords": 10 }"#;
+
+        for &kind in &["VolumeSpheroid", "VolumeEllipsoid"] {
+            add_definition(&mut system, kind, fill_type);
+        }
+
+        for &kind in &[
+            "VolumeCylinder",
+            "VolumeCone",
+            "VolumeTorus",
+            "VolumeCapsule",
+        ] {
+            add_definition(
+                &mut system,
+                kind,
+                &format!(r#""alignment": "Z", {}"#, fill_type),
+            );
+        }
+
+        add_definition(
+            &mut system,
+            "VolumeSlab",
+            &format!(r#""normal": "Z", {}"#, fill_type),
+        );
+
+        let origin = Coord::new(1.0, 2.0, 3.0);
+
+        system
+            .fill_cylinder("VolumeCylinder", origin, 1.0, 2.0, None)
+            .unwrap()
+            .fill_sphere("VolumeSpheroid", origin, 1.0, None)
+            .unwrap()
+            .fill_ellipsoid("VolumeEllipsoid", origin, Coord::new(1.0, 2.0, 3.0), None)
+            .unwrap()
+            .fill_cone("VolumeCone", origin, 1.0, 0.5, 2.0, None)
+            .unwrap()
+            .fill_torus("VolumeTorus", origin, 2.0, 0.5, None)
+            .unwrap()
+            .fill_capsule("VolumeCapsule", origin, 1.0, 2.0, None)
+            .unwrap()
+            .fill_slab("VolumeSlab", origin, 1.0, 2.0, 3.0, None)
+            .unwrap();
+
+        assert_eq!(system.components.len(), 7);
+
+        for component in &system.components {
+            assert_eq!(component.num_atoms(), 20);
+        }
+
+        // A given fill type is used over the default
+        system
+            .fill_sphere("VolumeSpheroid", origin, 1.0, Some(FillType::NumCoords(5)))
+            .unwrap();
+        assert_eq!(system.components[7].num_atoms(), 10);
+
+        assert!(system
+            .fill_cylinder("VolumeSpheroid", origin, 1.0, 1.0, None)
+            .is_err());
+        assert!(system
+            .fill_torus("VolumeTorus", origin, 2.0, -0.5, None)
+            .is_err());
+    }
+
+    #[test]
+    fn system_builder_constructs_every_kind_of_surface() {
+        let mut system = System::new("System").with_database(crate::database::library());
+        let shell = r#""lattice": { "Fibonacci": { "density": 10.0 } }"#;
+
+        add_definition(
+            &mut system,
+            "SurfaceCuboid",
+            r#""lattice": { "Hexagonal": { "a": 0.142 } }, "sides": { "bits": 63 }"#,
+        );
+        add_definition(&mut system, "SurfaceSphere", shell);
+        add_definition(&mut system, "SurfaceEllipsoid", shell);
+
+        let bilayer = format!(
+            r#"{{ "SurfaceBilayer": {{
+                "name": "Bilayer",
+                "upper_leaflet": [{{ "residue": {}, "fraction": 1.0 }}],
+                "area_per_lipid": 0.6,
+                "thickness": 4.0,
+                "lipid_axis": {{ "x": 1.0, "y": 0.0, "z": 0.0 }},
+                "random_rotation": false
+            }} }}"#,
+            WATER
+        );
+        system
+            .database
+            .component_defs
+            .push(serde_json::from_str(&bilayer).unwrap());
+
+        let origin = Coord::new(1.0, 2.0, 3.0);
+
+        system
+            .add_surface_cuboid("SurfaceCuboid", origin, Coord::new(2.0, 2.0, 2.0))
+            .unwrap()
+            .add_cylinder("Graphene Nanotube", origin, 1.0, 2.0)
+            .unwrap()
+            .add_sphere_shell("SurfaceSphere", origin, 1.0)
+            .unwrap()
+            .add_ellipsoid_shell("SurfaceEllipsoid", origin, Coord::new(1.0, 2.0, 3.0))
+            .unwrap()
+            .add_bilayer("Bilayer", origin, 3.0, 3.0)
+            .unwrap();
+
+        assert_eq!(system.components.len(), 5);
+
+        for component in &system.components {
+            assert!(component.num_atoms() > 0);
+        }
+
+        assert!(system
+            .add_sphere_shell("SurfaceEllipsoid", origin, 1.0)
+            .is_err());
+        assert!(system.add_bilayer("Bilayer", origin, -1.0, 3.0).is_err());
+    }
+
+    #[test]
+    fn system_builder_sets_the_number_of_residues_of_blue_noise_sheets() {
+        let mut system = System::new("System").with_database(crate::database::library());
+        add_definition(
+            &mut system,
+            "SurfaceSheet",
+            r#""lattice": { "BlueNoise": {} }, "normal": "Z""#,
+        );
+
+        assert!(system
+            .add_sheet("SurfaceSheet", Coord::ORIGO, 2.0, 2.0)
+            .is_err());
+        assert!(system
+            .add_blue_noise_sheet("Graphene", Coord::ORIGO, 2.0, 2.0, 5)
+            .is_err());
+
+        system
+            .add_blue_noise_sheet("SurfaceSheet", Coord::ORIGO, 2.0, 2.0, 5)
+            .unwrap();
+        assert_eq!(system.components.len(), 1);
+        assert_eq!(system.components[0].num_atoms(), 10);
+    }
+
+    #[test]
+    fn system_builder_reads_configurations_relative_to_the_database() {
+        let dir = std::env::temp_dir();
+        let conf = "Two waters
+    3
+    1SOL     OW    1   0.100   0.100   0.100
+    1SOL    HW1    2   0.200   0.100   0.100
+    2SOL     OW    3   0.600   0.600   0.600
+   1.00000   1.00000   1.00000
+";
+        let conf_path = dir.join("grafen-system-builder-conf.gro");
+        std::fs::write(&conf_path, conf).unwrap();
+
+        let definition = ComponentEntry::ConfigurationFile(ReadConf {
+            conf: None,
+            backup_conf: None,
+            path: PathBuf::from("grafen-system-builder-conf.gro"),
+            description: "Two waters".to_string(),
+            volume_type: ConfType::Cuboid {
+                origin: Coord::ORIGO,
+                size: Coord::new(1.0, 1.0, 1.0),
+            },
+        });
+
+        let mut database = crate::database::library();
+        database.path = Some(dir.join("grafen-system-builder-database.json"));
+        database.component_defs.push(definition);
+
+        let mut system = System::new("System").with_database(database);
+        let origin = Coord::new(1.0, 2.0, 3.0);
+
+        system
+            .add_configuration("Two waters", origin, None, InclusionPolicy::AnyAtom)
+            .unwrap()
+            .add_configuration(
+                "Two waters",
+                origin,
+                Some(ConfType::Cuboid {
+                    origin: Coord::ORIGO,
+                    size: Coord::new(2.0, 1.0, 1.0),
+                }),
+                InclusionPolicy::AnyAtom,
+            )
+            .unwrap();
+
+        std::fs::remove_file(&conf_path).unwrap();
+
+        assert_eq!(system.components[0].num_atoms(), 3);
+        assert_eq!(system.components[0].get_origin(), origin);
+        assert_eq!(system.components[1].num_atoms(), 6);
+
+        assert!(system
+            .add_configuration("Graphene", origin, None, InclusionPolicy::AnyAtom)
+            .is_err());
+        assert!(construct_component(system.definition("Two waters").unwrap(), None).is_err());
+    }
+
+    #[test]
+    fn system_builder_prunes_components_against_a_surface_cylinder() {
+        let mut system = System::new("System").with_database(crate::database::library());
+
+        // The water box is filled through the nanotube, which is pruned against
+        // the space enclosed by the tube
+        system
+            .add_cylinder("Graphene Nanotube", Coord::new(2.0, 2.0, 0.0), 1.0, 3.0)
+            .unwrap()
+            .fill_cuboid(
+                "Water (SPC/E)",
+                Coord::ORIGO,
+                Coord::new(4.0, 4.0, 3.0),
+                Some(FillType::NumCoords(500)),
+            )
+            .unwrap();
+
+        let num_before = system.components[1].num_atoms();
+
+        system
+            .prune_against(1, 0, 0.0, InclusionPolicy::FirstAtom)
+            .unwrap();
+
+        let num_after = system.components[1].num_atoms();
+        assert!(num_after < num_before);
+        assert!(num_after > 0);
+    }
 }
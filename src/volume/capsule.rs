@@ -0,0 +1,172 @@
+//! Capsule objects.
+
+use crate::{
+    coord::{Coord, Direction, Translate},
+    describe::{unwrap_name, Describe},
+    iterator::{ResidueIter, ResidueIterOut},
+    system::{Component, Residue},
+    volume::*,
+};
+
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A capsule volume: a cylinder which is capped by hemispheres at both ends.
+pub struct Capsule {
+    pub name: Option<String>,
+    pub residue: Option<Residue>,
+    pub alignment: Direction,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Center of the bottom hemisphere.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Radius of the cylinder and hemispheres (nm).
+    pub radius: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Height of the cylinder between the hemisphere centers (nm).
+    pub height: f64,
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub coords: Vec<Coord>,
+}
+
+impl_component![Capsule];
+impl_translate![Capsule];
+
+impl Capsule {
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        let diameter = 2.0 * self.radius;
+        align_size(diameter, self.height + diameter, self.alignment)
+    }
+}
+
+impl Contains for Capsule {
+    fn contains(&self, coord: Coord) -> bool {
+        let (dr, dh) = self.origin.distance_cylindrical(coord, self.alignment);
+
+        // Distance to the closest point on the axis between the hemisphere centers
+        let dh_axis = dh - dh.max(0.0).min(self.height);
+
+        dr.powi(2) + dh_axis.powi(2) <= self.radius.powi(2)
+    }
+}
+
+impl Describe for Capsule {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Capsule volume of radius {:.2} and height {:.2} at {})",
+            unwrap_name(&self.name),
+            self.radius,
+            self.height,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Capsule volume)", unwrap_name(&self.name))
+    }
+}
+
+impl Volume for Capsule {
+    fn fill(self, fill_type: FillType) -> error::Result<Capsule> {
+        check_positive("radius", self.radius)?;
+        check_non_negative("height", self.height)?;
+
+        let lower = align_size(-self.radius, -self.radius, self.alignment);
+
+        let coords = fill_bounding_box(
+            &self,
+            self.origin,
+            lower,
+            self.calc_box_size(),
+            fill_type.resolve(self.residue.as_ref())?,
+        )?;
+
+        Ok(Capsule { coords, ..self })
+    }
+
+    fn volume(&self) -> f64 {
+        PI * self.radius.powi(2) * self.height + 4.0 * PI * self.radius.powi(3) / 3.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_capsule(radius: f64, height: f64) -> Capsule {
+        Capsule {
+            name: None,
+            residue: None,
+            alignment: Direction::Z,
+            origin: Coord::ORIGO,
+            radius,
+            height,
+            fill_type: None,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn capsule_contains_coordinates_within_cylinder_and_caps() {
+        let capsule = setup_capsule(1.0, 2.0);
+        let err = 1e-9;
+
+        // Cylinder part
+        assert!(capsule.contains(Coord::new(1.0 - err, 0.0, 1.0)));
+        assert!(!capsule.contains(Coord::new(1.0 + err, 0.0, 1.0)));
+
+        // Caps
+        assert!(capsule.contains(Coord::new(0.0, 0.0, -1.0 + err)));
+        assert!(capsule.contains(Coord::new(0.0, 0.0, 3.0 - err)));
+        assert!(!capsule.contains(Coord::new(0.0, 0.0, -1.0 - err)));
+        assert!(!capsule.contains(Coord::new(0.0, 0.0, 3.0 + err)));
+
+        // The cap corners are rounded
+        assert!(!capsule.contains(Coord::new(0.9, 0.0, -0.9)));
+    }
+
+    #[test]
+    fn capsule_with_zero_height_is_a_sphere() {
+        let capsule = setup_capsule(1.0, 0.0);
+        let sphere_volume = 4.0 * PI / 3.0;
+
+        assert!((capsule.volume() - sphere_volume).abs() < 1e-9);
+        assert_eq!(capsule.calc_box_size(), Coord::new(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn fill_capsule_with_number_of_coordinates_places_them_inside() {
+        let num_coords = 100;
+        let capsule = setup_capsule(1.0, 2.0)
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap();
+
+        assert_eq!(capsule.coords.len(), num_coords as usize);
+
+        for &coord in &capsule.coords {
+            assert!(capsule.contains(capsule.origin + coord));
+        }
+    }
+
+    #[test]
+    fn capsule_radius_and_height_are_checked_independently() {
+        match setup_capsule(0.0, 2.0).fill(FillType::NumCoords(10)) {
+            Err(err) => assert_eq!(err.to_string(), "invalid radius: it must be positive"),
+            _ => panic!("a capsule without a radius was filled"),
+        }
+
+        match setup_capsule(1.0, -1.0).fill(FillType::NumCoords(10)) {
+            Err(err) => assert_eq!(err.to_string(), "invalid height: it must not be negative"),
+            _ => panic!("a capsule with a negative height was filled"),
+        }
+
+        // The radius may be larger than the height between the hemisphere centers
+        assert!(setup_capsule(2.0, 0.5)
+            .fill(FillType::NumCoords(10))
+            .is_ok());
+    }
+}
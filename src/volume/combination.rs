@@ -0,0 +1,173 @@
+//! Combine volumes using boolean operations.
+
+use crate::{coord::Coord, volume::Contains};
+
+/// A combination of volumes which itself contains coordinates.
+///
+/// Combinations can be nested to create arbitrarily complex expressions, eg.
+/// the space inside a box but outside of a cylinder is the `Difference`
+/// of the box and the cylinder.
+///
+/// # Examples
+/// ```
+/// # use grafen::coord::Coord;
+/// # use grafen::volume::{Combination, Contains, Cuboid};
+/// let cuboid = Cuboid {
+///     size: Coord::new(2.0, 2.0, 2.0),
+///     ..Cuboid::default()
+/// };
+///
+/// let hole = Cuboid {
+///     origin: Coord::new(0.5, 0.5, 0.5),
+///     size: Coord::new(1.0, 1.0, 1.0),
+///     ..Cuboid::default()
+/// };
+///
+/// let volume = Combination::difference(cuboid, hole);
+///
+/// assert!(volume.contains(Coord::new(0.1, 0.1, 0.1)));
+/// assert!(!volume.contains(Coord::new(1.0, 1.0, 1.0)));
+/// ```
+pub enum Combination {
+    /// Contains coordinates which are inside of either volume.
+    Union(Box<dyn Contains>, Box<dyn Contains>),
+    /// Contains coordinates which are inside of both volumes.
+    Intersection(Box<dyn Contains>, Box<dyn Contains>),
+    /// Contains coordinates which are inside of the first volume but not the second.
+    Difference(Box<dyn Contains>, Box<dyn Contains>),
+    /// Contains coordinates which are not inside of the volume.
+    Complement(Box<dyn Contains>),
+}
+
+impl Combination {
+    /// Construct the union of two volumes.
+    pub fn union<T, U>(first: T, second: U) -> Combination
+    where
+        T: Contains + 'static,
+        U: Contains + 'static,
+    {
+        Combination::Union(Box::new(first), Box::new(second))
+    }
+
+    /// Construct the intersection of two volumes.
+    pub fn intersection<T, U>(first: T, second: U) -> Combination
+    where
+        T: Contains + 'static,
+        U: Contains + 'static,
+    {
+        Combination::Intersection(Box::new(first), Box::new(second))
+    }
+
+    /// Construct the difference of two volumes, ie. the space of the first volume
+    /// with that of the second removed.
+    pub fn difference<T, U>(first: T, second: U) -> Combination
+    where
+        T: Contains + 'static,
+        U: Contains + 'static,
+    {
+        Combination::Difference(Box::new(first), Box::new(second))
+    }
+
+    /// Construct the complement of a volume, ie. all space outside of it.
+    pub fn complement<T>(volume: T) -> Combination
+    where
+        T: Contains + 'static,
+    {
+        Combination::Complement(Box::new(volume))
+    }
+}
+
+impl Contains for Combination {
+    fn contains(&self, coord: Coord) -> bool {
+        match self {
+            Combination::Union(first, second) => first.contains(coord) || second.contains(coord),
+            Combination::Intersection(first, second) => {
+                first.contains(coord) && second.contains(coord)
+            }
+            Combination::Difference(first, second) => {
+                first.contains(coord) && !second.contains(coord)
+            }
+            Combination::Complement(volume) => !volume.contains(coord),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::volume::Cuboid;
+
+    // Two cubes of side 2 which overlap in the region 1 <= x <= 2.
+    fn setup_cuboids() -> (Cuboid, Cuboid) {
+        let first = Cuboid {
+            size: Coord::new(2.0, 2.0, 2.0),
+            ..Cuboid::default()
+        };
+
+        let second = Cuboid {
+            origin: Coord::new(1.0, 0.0, 0.0),
+            size: Coord::new(2.0, 2.0, 2.0),
+            ..Cuboid::default()
+        };
+
+        (first, second)
+    }
+
+    #[test]
+    fn union_of_volumes_contains_coordinates_in_either() {
+        let (first, second) = setup_cuboids();
+        let union = Combination::union(first, second);
+
+        assert!(union.contains(Coord::new(0.5, 1.0, 1.0))); // only first
+        assert!(union.contains(Coord::new(1.5, 1.0, 1.0))); // both
+        assert!(union.contains(Coord::new(2.5, 1.0, 1.0))); // only second
+        assert!(!union.contains(Coord::new(3.5, 1.0, 1.0))); // neither
+    }
+
+    #[test]
+    fn intersection_of_volumes_contains_coordinates_in_both() {
+        let (first, second) = setup_cuboids();
+        let intersection = Combination::intersection(first, second);
+
+        assert!(!intersection.contains(Coord::new(0.5, 1.0, 1.0)));
+        assert!(intersection.contains(Coord::new(1.5, 1.0, 1.0)));
+        assert!(!intersection.contains(Coord::new(2.5, 1.0, 1.0)));
+        assert!(!intersection.contains(Coord::new(3.5, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn difference_of_volumes_contains_coordinates_only_in_the_first() {
+        let (first, second) = setup_cuboids();
+        let difference = Combination::difference(first, second);
+
+        assert!(difference.contains(Coord::new(0.5, 1.0, 1.0)));
+        assert!(!difference.contains(Coord::new(1.5, 1.0, 1.0)));
+        assert!(!difference.contains(Coord::new(2.5, 1.0, 1.0)));
+        assert!(!difference.contains(Coord::new(3.5, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn complement_of_volume_contains_coordinates_outside_of_it() {
+        let (first, _) = setup_cuboids();
+        let complement = Combination::complement(first);
+
+        assert!(!complement.contains(Coord::new(0.5, 1.0, 1.0)));
+        assert!(complement.contains(Coord::new(2.5, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn combinations_can_be_nested() {
+        let (first, second) = setup_cuboids();
+
+        // The space in either cube, but not where they overlap.
+        let (first_copy, second_copy) = setup_cuboids();
+        let exclusive = Combination::difference(
+            Combination::union(first, second),
+            Combination::intersection(first_copy, second_copy),
+        );
+
+        assert!(exclusive.contains(Coord::new(0.5, 1.0, 1.0)));
+        assert!(!exclusive.contains(Coord::new(1.5, 1.0, 1.0)));
+        assert!(exclusive.contains(Coord::new(2.5, 1.0, 1.0)));
+    }
+}
@@ -0,0 +1,208 @@
+//! Conical objects.
+
+use crate::{
+    coord::{Coord, Direction, Translate},
+    describe::{unwrap_name, Describe},
+    iterator::{ResidueIter, ResidueIterOut},
+    system::{Component, Residue},
+    volume::*,
+};
+
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A conical volume. With a non-zero top radius the cone is truncated into a frustum.
+pub struct Cone {
+    pub name: Option<String>,
+    pub residue: Option<Residue>,
+    pub alignment: Direction,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Center of the cone bottom.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Radius at the bottom of the cone (nm).
+    pub bottom_radius: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Radius at the top of the cone (nm).
+    pub top_radius: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub height: f64,
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub coords: Vec<Coord>,
+}
+
+impl_component![Cone];
+impl_translate![Cone];
+
+impl Cone {
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        align_size(2.0 * self.max_radius(), self.height, self.alignment)
+    }
+
+    /// Return the largest radius of the cone.
+    fn max_radius(&self) -> f64 {
+        self.bottom_radius.max(self.top_radius)
+    }
+
+    /// Return the radius of the cone at a height above its bottom.
+    fn radius_at(&self, height: f64) -> f64 {
+        if self.height > 0.0 {
+            self.bottom_radius + (self.top_radius - self.bottom_radius) * height / self.height
+        } else {
+            self.bottom_radius
+        }
+    }
+}
+
+impl Contains for Cone {
+    fn contains(&self, coord: Coord) -> bool {
+        let (dr, dh) = self.origin.distance_cylindrical(coord, self.alignment);
+
+        dh >= 0.0 && dh <= self.height && dr <= self.radius_at(dh)
+    }
+}
+
+impl Describe for Cone {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Conical volume of radii {:.2} to {:.2} and height {:.2} at {})",
+            unwrap_name(&self.name),
+            self.bottom_radius,
+            self.top_radius,
+            self.height,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Conical volume)", unwrap_name(&self.name))
+    }
+}
+
+impl Volume for Cone {
+    fn fill(self, fill_type: FillType) -> error::Result<Cone> {
+        check_non_negative("bottom radius", self.bottom_radius)?;
+        check_non_negative("top radius", self.top_radius)?;
+        check_relation(
+            "bottom radius",
+            self.max_radius() > 0.0,
+            "be positive for a zero top radius",
+        )?;
+        check_positive("height", self.height)?;
+
+        let radius = self.max_radius();
+        let lower = align_size(-radius, 0.0, self.alignment);
+
+        let coords = fill_bounding_box(
+            &self,
+            self.origin,
+            lower,
+            self.calc_box_size(),
+            fill_type.resolve(self.residue.as_ref())?,
+        )?;
+
+        Ok(Cone { coords, ..self })
+    }
+
+    fn volume(&self) -> f64 {
+        let (r0, r1) = (self.bottom_radius, self.top_radius);
+        PI * self.height * (r0.powi(2) + r0 * r1 + r1.powi(2)) / 3.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_cone(bottom_radius: f64, top_radius: f64, height: f64) -> Cone {
+        Cone {
+            name: None,
+            residue: None,
+            alignment: Direction::Z,
+            origin: Coord::ORIGO,
+            bottom_radius,
+            top_radius,
+            height,
+            fill_type: None,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn cone_contains_coordinates_within_its_narrowing_radius() {
+        let cone = setup_cone(2.0, 0.0, 4.0);
+        let err = 1e-9;
+
+        assert!(cone.contains(Coord::new(2.0 - err, 0.0, 0.0)));
+        assert!(cone.contains(Coord::new(1.0 - err, 0.0, 2.0)));
+        assert!(cone.contains(Coord::new(0.0, 0.0, 4.0 - err)));
+
+        assert!(!cone.contains(Coord::new(2.0 + err, 0.0, 0.0)));
+        assert!(!cone.contains(Coord::new(1.0 + err, 0.0, 2.0)));
+        assert!(!cone.contains(Coord::new(0.0, 0.0, 4.0 + err)));
+        assert!(!cone.contains(Coord::new(0.0, 0.0, -err)));
+    }
+
+    #[test]
+    fn frustum_has_a_top_radius() {
+        let frustum = setup_cone(1.0, 3.0, 2.0);
+        let err = 1e-9;
+
+        assert!(frustum.contains(Coord::new(3.0 - err, 0.0, 2.0)));
+        assert!(!frustum.contains(Coord::new(1.0 + err, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn cone_volume_is_correct() {
+        let (radius, height) = (2.0, 3.0);
+        let cone = setup_cone(radius, 0.0, height);
+        assert!((cone.volume() - PI * radius.powi(2) * height / 3.0).abs() < 1e-9);
+
+        // A frustum with equal radii is a cylinder
+        let cylinder = setup_cone(radius, radius, height);
+        assert!((cylinder.volume() - PI * radius.powi(2) * height).abs() < 1e-9);
+    }
+
+    #[test]
+    fn calc_box_size_of_cone_depends_on_alignment_and_largest_radius() {
+        let cone = Cone {
+            alignment: Direction::X,
+            ..setup_cone(1.0, 2.0, 5.0)
+        };
+
+        assert_eq!(cone.calc_box_size(), Coord::new(5.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn fill_cone_with_number_of_coordinates_places_them_inside() {
+        let num_coords = 100;
+        let cone = setup_cone(2.0, 1.0, 3.0)
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap();
+
+        assert_eq!(cone.coords.len(), num_coords as usize);
+
+        for &coord in &cone.coords {
+            assert!(cone.contains(cone.origin + coord));
+        }
+    }
+
+    #[test]
+    fn filling_a_cone_without_a_positive_radius_is_an_error() {
+        match setup_cone(0.0, 0.0, 2.0).fill(FillType::NumCoords(10)) {
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "invalid bottom radius: it must be positive for a zero top radius"
+            ),
+            _ => panic!("a cone without a radius was filled"),
+        }
+
+        assert!(setup_cone(0.0, 1.0, 2.0)
+            .fill(FillType::NumCoords(10))
+            .is_ok());
+    }
+}
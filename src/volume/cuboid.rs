@@ -18,15 +18,16 @@ pub struct Cuboid {
     pub name: Option<String>,
     /// Component residue.
     pub residue: Option<Residue>,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Origin position of component.
     pub origin: Coord,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Size of component (nm).
     pub size: Coord,
-    /// A density may be set for the component.
-    pub density: Option<f64>,
-    #[serde(skip)]
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     /// Residue coordinates of component, relative to its `origin`.
     pub coords: Vec<Coord>,
 }
@@ -107,7 +108,7 @@ impl Cuboid {
             origin: self.origin,
             radius,
             height,
-            density: self.density,
+            fill_type: self.fill_type,
             alignment,
             coords,
         }
@@ -136,7 +137,7 @@ impl Cuboid {
             residue: self.residue.clone(),
             origin: self.origin,
             radius,
-            density: self.density,
+            fill_type: self.fill_type,
             coords,
         }
     }
@@ -159,7 +160,7 @@ impl Default for Cuboid {
             residue: None,
             origin: Coord::ORIGO,
             size: Coord::ORIGO,
-            density: None,
+            fill_type: None,
             coords: vec![],
         }
     }
@@ -196,8 +197,12 @@ impl Periodic for Cuboid {
 }
 
 impl Volume for Cuboid {
-    fn fill(self, fill_type: FillType) -> Cuboid {
-        let num_coords = fill_type.to_num_coords(&self);
+    fn fill(self, fill_type: FillType) -> error::Result<Cuboid> {
+        check_positive("length", self.size.x)?;
+        check_positive("width", self.size.y)?;
+        check_positive("height", self.size.z)?;
+
+        let num_coords = fill_type.to_num_coords(&self, self.residue.as_ref())?;
 
         // To fill the cuboid in a uniform manner, construct a lattice grid which can contain
         // the desired number of atoms. Then, select the desired number of cells from this
@@ -233,13 +238,13 @@ impl Volume for Cuboid {
             })
             .collect::<Vec<_>>();
 
-        let density = Some((num_coords as f64) / self.volume());
+        let fill_type = Some(FillType::Density((num_coords as f64) / self.volume()));
 
-        Cuboid {
-            density,
+        Ok(Cuboid {
+            fill_type,
             coords,
             ..self
-        }
+        })
     }
 
     fn volume(&self) -> f64 {
@@ -340,7 +345,8 @@ mod tests {
             size: Coord::new(radius, radius, radius),
             ..Cuboid::default()
         }
-        .fill(FillType::Density(density));
+        .fill(FillType::Density(density))
+        .unwrap();
 
         let cylinder = cuboid.to_cylinder(radius, diameter, Direction::X);
         let expected_coords = (cylinder.volume() * density).round() as usize;
@@ -463,13 +469,17 @@ mod tests {
             size,
             ..Cuboid::default()
         }
-        .fill(FillType::NumCoords(num_atoms));
+        .fill(FillType::NumCoords(num_atoms))
+        .unwrap();
 
         assert_eq!(cuboid.coords.len(), num_atoms as usize);
 
         let volume = size.x * size.y * size.z;
         let expected_density = num_atoms as f64 / volume;
-        let density = cuboid.density.unwrap();
+        let density = cuboid
+            .fill_type
+            .and_then(|fill_type| fill_type.number_density(None))
+            .unwrap();
         let ratio = density / expected_density;
 
         assert!(ratio >= 0.9 && ratio <= 1.1);
@@ -20,15 +20,16 @@ pub struct Cylinder {
     pub name: Option<String>,
     pub residue: Option<Residue>,
     pub alignment: Direction,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub origin: Coord,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub radius: f64,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub height: f64,
-    /// A density may be set for the component.
-    pub density: Option<f64>,
-    #[serde(skip)]
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub coords: Vec<Coord>,
 }
 
@@ -73,9 +74,12 @@ impl Describe for Cylinder {
 }
 
 impl Volume for Cylinder {
-    fn fill(self, fill_type: FillType) -> Cylinder {
+    fn fill(self, fill_type: FillType) -> error::Result<Cylinder> {
+        check_positive("radius", self.radius)?;
+        check_positive("height", self.height)?;
+
         match fill_type {
-            FillType::Density(_) => {
+            FillType::Density(_) | FillType::MassDensity(_) | FillType::Molarity(_) => {
                 // Use the filling function from `Cuboid` to generate coordinates to cut from.
                 // This is slightly inefficient, but for now it is easy to keep the generation
                 // in a single function.
@@ -88,15 +92,15 @@ impl Volume for Cylinder {
                     Direction::Z => Coord::new(box_side, box_side, box_height),
                 };
 
-                Cuboid {
+                Ok(Cuboid {
                     name: self.name,
                     residue: self.residue,
                     origin: self.origin,
                     size,
                     ..Cuboid::default()
                 }
-                .fill(fill_type)
-                .to_cylinder(self.radius, self.height, self.alignment)
+                .fill(fill_type)?
+                .to_cylinder(self.radius, self.height, self.alignment))
             }
             FillType::NumCoords(num_coords) => {
                 // To fill with an exact number of coordinates, generate them explictly.
@@ -127,10 +131,10 @@ impl Volume for Cylinder {
 
                 let coords = (0..num_coords).map(|_| gen_coord()).collect::<Vec<_>>();
 
-                Cylinder {
+                Ok(Cylinder {
                     coords,
                     ..self.clone()
-                }
+                })
             }
         }
     }
@@ -156,13 +160,13 @@ mod tests {
             origin: Coord::default(),
             radius,
             height,
-            density: None,
+            fill_type: None,
             alignment: Direction::Z,
             coords: vec![],
         };
 
         // Default alignment: Z
-        let cylinder = conf.clone().fill(FillType::NumCoords(num_coords));
+        let cylinder = conf.clone().fill(FillType::NumCoords(num_coords)).unwrap();
         assert_eq!(num_coords as usize, cylinder.coords.len());
 
         for coord in cylinder.coords {
@@ -173,7 +177,12 @@ mod tests {
 
         // Along the other axes
         conf.alignment = Direction::X;
-        for coord in conf.clone().fill(FillType::NumCoords(num_coords)).coords {
+        for coord in conf
+            .clone()
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap()
+            .coords
+        {
             let (r, h) = Coord::ORIGO.distance_cylindrical(coord, Direction::X);
             assert!(r <= cylinder.radius);
             assert!(h >= 0.0 && h <= cylinder.height);
@@ -181,7 +190,12 @@ mod tests {
 
         // Along the other axes
         conf.alignment = Direction::Y;
-        for coord in conf.clone().fill(FillType::NumCoords(num_coords)).coords {
+        for coord in conf
+            .clone()
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap()
+            .coords
+        {
             let (r, h) = Coord::ORIGO.distance_cylindrical(coord, Direction::Y);
             assert!(r <= cylinder.radius);
             assert!(h >= 0.0 && h <= cylinder.height);
@@ -199,7 +213,7 @@ mod tests {
             origin: Coord::default(),
             radius,
             height,
-            density: None,
+            fill_type: None,
             alignment: Direction::X,
             coords: vec![],
         };
@@ -232,7 +246,7 @@ mod tests {
             origin: Coord::new(1.0, 1.0, 1.0),
             radius: 1.0,
             height: 2.0,
-            density: None,
+            fill_type: None,
             alignment: Direction::X,
             coords: vec![],
         };
@@ -291,7 +305,7 @@ mod tests {
             origin: Coord::ORIGO,
             radius,
             height,
-            density: None,
+            fill_type: None,
             alignment: Direction::X,
             coords: vec![],
         };
@@ -313,11 +327,12 @@ mod tests {
             origin: Coord::ORIGO,
             radius,
             height,
-            density: None,
+            fill_type: None,
             alignment: Direction::Y,
             coords: vec![],
         }
-        .fill(FillType::Density(density));
+        .fill(FillType::Density(density))
+        .unwrap();
 
         let expected_coords = (cylinder.volume() * density).round() as usize;
         let ratio = cylinder.coords.len() as f64 / expected_coords as f64;
@@ -0,0 +1,145 @@
+//! Ellipsoidal objects.
+
+use crate::{
+    coord::{Coord, Translate},
+    describe::{unwrap_name, Describe},
+    iterator::{ResidueIter, ResidueIterOut},
+    system::{Component, Residue},
+    volume::*,
+};
+
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// An ellipsoidal volume, with its semi-axes along the x, y and z axes.
+pub struct Ellipsoid {
+    pub name: Option<String>,
+    pub residue: Option<Residue>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Center of the ellipsoid.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Lengths of the semi-axes along x, y and z (nm).
+    pub semi_axes: Coord,
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub coords: Vec<Coord>,
+}
+
+impl_component![Ellipsoid];
+impl_translate![Ellipsoid];
+
+impl Ellipsoid {
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        self.semi_axes * 2.0
+    }
+}
+
+impl Contains for Ellipsoid {
+    fn contains(&self, coord: Coord) -> bool {
+        let (dx, dy, dz) = (coord - self.origin).to_tuple();
+        let (a, b, c) = self.semi_axes.to_tuple();
+
+        (dx / a).powi(2) + (dy / b).powi(2) + (dz / c).powi(2) <= 1.0
+    }
+}
+
+impl Describe for Ellipsoid {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Ellipsoidal volume with semi-axes {} at {})",
+            unwrap_name(&self.name),
+            self.semi_axes,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Ellipsoidal volume)", unwrap_name(&self.name))
+    }
+}
+
+impl Volume for Ellipsoid {
+    fn fill(self, fill_type: FillType) -> error::Result<Ellipsoid> {
+        check_positive("semi-axis a", self.semi_axes.x)?;
+        check_positive("semi-axis b", self.semi_axes.y)?;
+        check_positive("semi-axis c", self.semi_axes.z)?;
+
+        let lower = Coord::ORIGO - self.semi_axes;
+        let coords = fill_bounding_box(
+            &self,
+            self.origin,
+            lower,
+            self.calc_box_size(),
+            fill_type.resolve(self.residue.as_ref())?,
+        )?;
+
+        Ok(Ellipsoid { coords, ..self })
+    }
+
+    fn volume(&self) -> f64 {
+        4.0 * PI * self.semi_axes.x * self.semi_axes.y * self.semi_axes.z / 3.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_ellipsoid(semi_axes: Coord) -> Ellipsoid {
+        Ellipsoid {
+            name: None,
+            residue: None,
+            origin: Coord::new(1.0, 1.0, 1.0),
+            semi_axes,
+            fill_type: None,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn ellipsoid_contains_coordinates_within_its_semi_axes() {
+        let ellipsoid = setup_ellipsoid(Coord::new(1.0, 2.0, 3.0));
+        let err = 1e-9;
+
+        assert!(ellipsoid.contains(Coord::new(1.0, 1.0, 1.0)));
+        assert!(ellipsoid.contains(Coord::new(2.0 - err, 1.0, 1.0)));
+        assert!(ellipsoid.contains(Coord::new(1.0, 3.0 - err, 1.0)));
+        assert!(ellipsoid.contains(Coord::new(1.0, 1.0, -2.0 + err)));
+
+        assert!(!ellipsoid.contains(Coord::new(2.0 + err, 1.0, 1.0)));
+        assert!(!ellipsoid.contains(Coord::new(1.0, 3.0 + err, 1.0)));
+        assert!(!ellipsoid.contains(Coord::new(1.0, 1.0, -2.0 - err)));
+        assert!(!ellipsoid.contains(Coord::new(1.9, 2.9, 1.0)));
+    }
+
+    #[test]
+    fn fill_ellipsoid_with_number_of_coordinates_places_them_inside() {
+        let num_coords = 100;
+        let ellipsoid = setup_ellipsoid(Coord::new(1.0, 2.0, 3.0))
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap();
+
+        assert_eq!(ellipsoid.coords.len(), num_coords as usize);
+
+        for &coord in &ellipsoid.coords {
+            assert!(ellipsoid.contains(ellipsoid.origin + coord));
+        }
+    }
+
+    #[test]
+    fn ellipsoid_from_density_makes_an_expected_number_of_coordinates() {
+        let density = 100.0;
+        let ellipsoid = setup_ellipsoid(Coord::new(1.0, 1.5, 2.0))
+            .fill(FillType::Density(density))
+            .unwrap();
+
+        let expected_coords = (ellipsoid.volume() * density).round() as usize;
+        let ratio = ellipsoid.coords.len() as f64 / expected_coords as f64;
+
+        assert!(ratio >= 0.95 && ratio <= 1.05);
+    }
+}
@@ -1,18 +1,40 @@
 //!  Define and construct 3D volume objects.
 
+mod capsule;
+mod combination;
+mod cone;
 mod cuboid;
 mod cylinder;
+mod ellipsoid;
+mod slab;
 mod sphere;
+mod torus;
 
 use crate::{
     coord::{Coord, Direction, Periodic},
+    error::{self, GrafenError},
     iterator::ResidueIterOut,
-    system::Component,
+    system::{Component, Residue},
 };
 
-pub use self::{cuboid::Cuboid, cylinder::Cylinder, sphere::Spheroid};
+pub use self::{
+    capsule::Capsule, combination::Combination, cone::Cone, cuboid::Cuboid, cylinder::Cylinder,
+    ellipsoid::Ellipsoid, slab::Slab, sphere::Spheroid, torus::Torus,
+};
 
+use rand::{
+    distributions::{Distribution as _, Uniform},
+    thread_rng,
+};
 use serde_derive::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Avogadro's constant (1/mol).
+const AVOGADRO: f64 = 6.02214076e23;
+
+/// Number of times more coordinates than expected which are generated inside of
+/// a bounding box before giving up on filling a volume with a number of coordinates.
+const MAX_FILL_ATTEMPTS_FACTOR: f64 = 100.0;
 
 /// Volumes can contain coordinates.
 pub trait Contains {
@@ -23,28 +45,119 @@ pub trait Contains {
 /// Traits for volume objects.
 pub trait Volume: Contains {
     /// Fill the object with (roughly) uniformly distributed coordinates and return it.
-    fn fill(self, fill_type: FillType) -> Self;
+    ///
+    /// # Errors
+    /// Returns an `InvalidParameter` error if a dimension of the object is invalid,
+    /// eg. a negative radius.
+    fn fill(self, fill_type: FillType) -> error::Result<Self>
+    where
+        Self: Sized;
 
     /// Return the object volume in units cubed.
     fn volume(&self) -> f64;
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 /// Variants for how a volume can be filled.
 pub enum FillType {
-    /// An input density from which a number of coordinates to fill with is calculated.
+    /// An input number density (1/nm^3) from which a number of coordinates to fill
+    /// with is calculated.
     Density(f64),
     /// An absolute number of coordinates.
     NumCoords(u64),
+    /// An input mass density (g/cm^3) which is converted to a number density
+    /// using the mass of the filling residue.
+    MassDensity(f64),
+    /// An input molar concentration (mol/L) of the filling residue.
+    Molarity(f64),
 }
 
 impl FillType {
+    /// Return the number density (1/nm^3) of residues for the fill type.
+    ///
+    /// Returns `None` for an absolute number of coordinates, or for a mass density
+    /// if the residue or its mass is not known.
+    pub fn number_density(&self, residue: Option<&Residue>) -> Option<f64> {
+        match *self {
+            FillType::Density(density) => Some(density),
+            FillType::NumCoords(_) => None,
+            // 1 g/cm^3 = 1e-21 g/nm^3
+            FillType::MassDensity(density) => residue
+                .and_then(|res| res.mass())
+                .filter(|&mass| mass > 0.0)
+                .map(|mass| density * AVOGADRO / mass * 1e-21),
+            // 1 mol/L = 1e-24 mol/nm^3
+            FillType::Molarity(concentration) => Some(concentration * AVOGADRO * 1e-24),
+        }
+    }
+
+    /// Assert that the fill type can be converted to a number of residues, ie. that
+    /// a density is positive and that the residue mass is known if filling by mass density.
+    ///
+    /// # Errors
+    /// Returns an `InvalidParameter` error if the fill type cannot be used.
+    pub fn check(self, residue: Option<&Residue>) -> error::Result<FillType> {
+        match self {
+            FillType::NumCoords(_) => Ok(self),
+            _ => self.checked_density(residue).map(|_| self),
+        }
+    }
+
+    /// Return the positive number density of a fill type which is not a number of coordinates.
+    fn checked_density(&self, residue: Option<&Residue>) -> error::Result<f64> {
+        match self.number_density(residue) {
+            Some(density) if density > 0.0 => Ok(density),
+            Some(_) => Err(GrafenError::invalid_parameter(
+                "density",
+                "it must be positive",
+            )),
+            None => Err(GrafenError::invalid_parameter(
+                "density",
+                "the mass of the residue is not known",
+            )),
+        }
+    }
+
+    /// Convert a mass density or molarity into a number density for the input residue.
+    ///
+    /// # Errors
+    /// Returns an `InvalidParameter` error if the density is not positive or the
+    /// residue mass is not known.
+    fn resolve(self, residue: Option<&Residue>) -> error::Result<FillType> {
+        match self {
+            FillType::NumCoords(_) => Ok(self),
+            _ => self.checked_density(residue).map(FillType::Density),
+        }
+    }
+
     /// Unwrap the number of coordinates by either calculating it using the density and volume
     /// of the input object, or return it.
-    fn to_num_coords<T: Volume>(&self, volume: &T) -> u64 {
+    ///
+    /// # Errors
+    /// Returns an `InvalidParameter` error if the density is not positive or the
+    /// residue mass is not known.
+    fn to_num_coords<T: Volume>(
+        &self,
+        volume: &T,
+        residue: Option<&Residue>,
+    ) -> error::Result<u64> {
         match *self {
-            FillType::Density(density) => (volume.volume() * density).round() as u64,
-            FillType::NumCoords(num) => num,
+            FillType::NumCoords(num) => Ok(num),
+            _ => {
+                let density = self.checked_density(residue)?;
+                Ok((volume.volume() * density).round() as u64)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FillType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FillType::Density(density) => write!(f, "{} 1/nm^3", density),
+            FillType::NumCoords(num) => write!(f, "{} residues", num),
+            FillType::MassDensity(density) => write!(f, "{} g/cm^3", density),
+            FillType::Molarity(concentration) => write!(f, "{} mol/L", concentration),
         }
     }
 }
@@ -78,6 +191,135 @@ fn cut_to_sphere(coords: &[Coord], center: Coord, radius: f64) -> Vec<Coord> {
         .collect()
 }
 
+/// Helper function to construct a size from a (radial) size in the plane perpendicular
+/// to an alignment axis and an (axial) size along it.
+fn align_size(radial: f64, axial: f64, alignment: Direction) -> Coord {
+    match alignment {
+        Direction::X => Coord::new(axial, radial, radial),
+        Direction::Y => Coord::new(radial, axial, radial),
+        Direction::Z => Coord::new(radial, radial, axial),
+    }
+}
+
+/// Assert that a dimension of a volume is positive.
+fn check_positive(parameter: &str, value: f64) -> error::Result<()> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(GrafenError::invalid_parameter(
+            parameter,
+            "it must be positive",
+        ))
+    }
+}
+
+/// Assert that a dimension of a volume is not negative.
+fn check_non_negative(parameter: &str, value: f64) -> error::Result<()> {
+    if value >= 0.0 {
+        Ok(())
+    } else {
+        Err(GrafenError::invalid_parameter(
+            parameter,
+            "it must not be negative",
+        ))
+    }
+}
+
+/// Assert that a relation between dimensions of a volume holds, eg. that one radius
+/// is not larger than another. The relation is described from the parameter.
+fn check_relation(parameter: &str, holds: bool, relation: &str) -> error::Result<()> {
+    if holds {
+        Ok(())
+    } else {
+        Err(GrafenError::invalid_parameter(
+            parameter,
+            &format!("it must {}", relation),
+        ))
+    }
+}
+
+/// Helper function to fill a volume by generating coordinates inside of a bounding box
+/// and keeping those which are contained by the volume.
+///
+/// The box is positioned with its lower corner at `lower`, relative to the volume `origin`.
+/// The returned coordinates are also relative to the origin.
+///
+/// # Errors
+/// Returns an `InvalidParameter` error if a number of coordinates could not be placed
+/// inside of the volume after many more attempts than expected from its size.
+fn fill_bounding_box<V: Volume>(
+    volume: &V,
+    origin: Coord,
+    lower: Coord,
+    size: Coord,
+    fill_type: FillType,
+) -> error::Result<Vec<Coord>> {
+    match fill_type {
+        FillType::Density(_) | FillType::MassDensity(_) | FillType::Molarity(_) => Ok(Cuboid {
+            size,
+            ..Cuboid::default()
+        }
+        .fill(fill_type)?
+        .coords
+        .into_iter()
+        .map(|coord| coord + lower)
+        .filter(|&coord| volume.contains(origin + coord))
+        .collect()),
+        FillType::NumCoords(num_coords) => {
+            // Generate coordinates until we have enough inside the volume. Volumes
+            // without any space can never be filled.
+            if volume.volume() <= 0.0 {
+                return Ok(Vec::new());
+            }
+
+            // The fraction of the box which is inside the volume gives the expected
+            // number of attempts. Stop well after that, in case the volume does not
+            // fit inside of the box.
+            let box_volume = size.x * size.y * size.z;
+            let max_attempts = (MAX_FILL_ATTEMPTS_FACTOR * num_coords as f64 * box_volume
+                / volume.volume())
+            .ceil() as u64
+                + 1000;
+
+            let range_x = Uniform::new_inclusive(0.0, size.x);
+            let range_y = Uniform::new_inclusive(0.0, size.y);
+            let range_z = Uniform::new_inclusive(0.0, size.z);
+
+            let mut rng = thread_rng();
+            let mut coords = Vec::with_capacity(num_coords as usize);
+            let mut attempts = 0;
+
+            while (coords.len() as u64) < num_coords {
+                if attempts >= max_attempts {
+                    return Err(GrafenError::invalid_parameter(
+                        "number of coordinates",
+                        &format!(
+                            "only {} of {} coordinates could be placed inside of the volume",
+                            coords.len(),
+                            num_coords
+                        ),
+                    ));
+                }
+
+                attempts += 1;
+
+                let coord = lower
+                    + Coord::new(
+                        range_x.sample(&mut rng),
+                        range_y.sample(&mut rng),
+                        range_z.sample(&mut rng),
+                    );
+
+                if volume.contains(origin + coord) {
+                    coords.push(coord);
+                }
+            }
+
+            Ok(coords)
+        }
+    }
+}
+
 /// Helper function to periodically replicate a set of coordinates for a volume object.
 pub fn pbc_multiply_volume(
     coords: &[Coord],
@@ -116,38 +358,161 @@ pub fn pbc_multiply_volume(
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+/// Variants for how to decide whether a residue is inside of a volume.
+pub enum InclusionPolicy {
+    /// Any atom of the residue is inside.
+    AnyAtom,
+    /// All atoms of the residue are inside.
+    AllAtoms,
+    /// The center of geometry of the residue is inside.
+    CenterOfGeometry,
+    /// The center of mass of the residue is inside. If the mass of any atom
+    /// is not known the center of geometry is used (see `num_residues_without_mass`).
+    CenterOfMass,
+    /// The first atom of the residue is inside.
+    FirstAtom,
+}
+
+impl Default for InclusionPolicy {
+    fn default() -> InclusionPolicy {
+        InclusionPolicy::AnyAtom
+    }
+}
+
+impl InclusionPolicy {
+    /// Whether a residue is inside of a volume, for a component with an origin.
+    pub fn contains<V: ?Sized + Contains>(
+        &self,
+        volume: &V,
+        residue: &ResidueIterOut,
+        origin: Coord,
+    ) -> bool {
+        let positions = residue
+            .get_atoms()
+            .into_iter()
+            .map(|(_, position)| position + origin)
+            .collect::<Vec<_>>();
+
+        match *self {
+            InclusionPolicy::AnyAtom => positions.iter().any(|&coord| volume.contains(coord)),
+            InclusionPolicy::AllAtoms => positions.iter().all(|&coord| volume.contains(coord)),
+            InclusionPolicy::CenterOfGeometry => get_center(&positions, None)
+                .map(|coord| volume.contains(coord))
+                .unwrap_or(false),
+            InclusionPolicy::CenterOfMass => {
+                let masses = residue
+                    .get_metadata()
+                    .iter()
+                    .map(|metadata| metadata.mass())
+                    .collect::<Option<Vec<f64>>>();
+
+                get_center(&positions, masses.as_ref().map(|m| m.as_slice()))
+                    .map(|coord| volume.contains(coord))
+                    .unwrap_or(false)
+            }
+            InclusionPolicy::FirstAtom => positions
+                .first()
+                .map(|&coord| volume.contains(coord))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Get the center of positions, weighted by masses if they are given and their sum is
+/// positive. Returns `None` if there are no positions.
+pub(crate) fn get_center(positions: &[Coord], masses: Option<&[f64]>) -> Option<Coord> {
+    if positions.is_empty() {
+        return None;
+    }
+
+    let weights = match masses {
+        Some(masses) if masses.len() == positions.len() && masses.iter().sum::<f64>() > 0.0 => {
+            masses.to_vec()
+        }
+        _ => vec![1.0; positions.len()],
+    };
+
+    let total: f64 = weights.iter().sum();
+    let sum = positions
+        .iter()
+        .zip(weights.iter())
+        .fold(Coord::ORIGO, |acc, (&position, &weight)| {
+            acc + position * weight
+        });
+
+    Some(sum * (1.0 / total))
+}
+
+impl fmt::Display for InclusionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InclusionPolicy::AnyAtom => write!(f, "any atom"),
+            InclusionPolicy::AllAtoms => write!(f, "all atoms"),
+            InclusionPolicy::CenterOfGeometry => write!(f, "center of geometry"),
+            InclusionPolicy::CenterOfMass => write!(f, "center of mass"),
+            InclusionPolicy::FirstAtom => write!(f, "first atom"),
+        }
+    }
+}
+
+impl FromStr for InclusionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<InclusionPolicy, String> {
+        match s.to_lowercase().as_str() {
+            "any" => Ok(InclusionPolicy::AnyAtom),
+            "all" => Ok(InclusionPolicy::AllAtoms),
+            "geometry" => Ok(InclusionPolicy::CenterOfGeometry),
+            "mass" => Ok(InclusionPolicy::CenterOfMass),
+            "first" => Ok(InclusionPolicy::FirstAtom),
+            _ => Err(format!(
+                "unknown inclusion policy '{}' (use any, all, geometry, mass or first)",
+                s
+            )),
+        }
+    }
+}
+
 /// Return residues of an input `Component` which are not contained by a pruning volume.
 ///
-/// Checks all atoms within residues to see if any are contained by the volume.
-/// If any are, the residue is filtered from the returned list.
+/// Residues are filtered from the returned list if they are inside of the volume
+/// by the inclusion policy.
 pub fn prune_residues_from_volume<'a, T, V>(
     component: &'a T,
     pruning_vol: &V,
+    policy: InclusionPolicy,
 ) -> Vec<ResidueIterOut>
 where
     T: Component<'a>,
     V: ?Sized + Contains,
 {
-    let origin = component.get_origin();
-
-    component
-        .iter_residues()
-        .filter(|res| {
-            res.get_atoms()
-                .iter()
-                .map(|atom| atom.1 + origin)
-                .all(|coord| !pruning_vol.contains(coord))
-        })
-        .collect()
+    filter_residues(component, pruning_vol, policy, false)
 }
 
 /// Return residues of an input `Component` which are contained within an input volume.
 ///
-/// Checks all atoms within residues to see if any are contained by it. If any are,
-/// the residue is kept in the returned list.
+/// Residues are kept in the returned list if they are inside of the volume by the
+/// inclusion policy.
 pub fn keep_residues_within_volume<'a, T, V>(
     component: &'a T,
     containing_vol: &V,
+    policy: InclusionPolicy,
+) -> Vec<ResidueIterOut>
+where
+    T: Component<'a>,
+    V: ?Sized + Contains,
+{
+    filter_residues(component, containing_vol, policy, true)
+}
+
+/// Return the residues of a component which are (if `keep_inside` is set) or are not
+/// inside of a volume by the inclusion policy.
+fn filter_residues<'a, T, V>(
+    component: &'a T,
+    volume: &V,
+    policy: InclusionPolicy,
+    keep_inside: bool,
 ) -> Vec<ResidueIterOut>
 where
     T: Component<'a>,
@@ -157,15 +522,22 @@ where
 
     component
         .iter_residues()
-        .filter(|res| {
-            res.get_atoms()
-                .iter()
-                .map(|atom| atom.1 + origin)
-                .any(|coord| containing_vol.contains(coord))
-        })
+        .filter(|res| policy.contains(volume, res, origin) == keep_inside)
         .collect()
 }
 
+/// Return the number of residues in a component for which the mass of any atom
+/// is not known.
+///
+/// These residues are tested by their center of geometry if the inclusion policy
+/// is `CenterOfMass`, which callers may want to warn about.
+pub fn num_residues_without_mass<'a, T: Component<'a>>(component: &'a T) -> usize {
+    component
+        .iter_residues()
+        .filter(|res| res.get_metadata().iter().any(|meta| meta.mass().is_none()))
+        .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,12 +555,128 @@ mod tests {
         let expected_num_coords = (cuboid.volume() * density).round() as u64;
 
         assert_eq!(
-            FillType::Density(density).to_num_coords(&cuboid),
+            FillType::Density(density)
+                .to_num_coords(&cuboid, None)
+                .unwrap(),
             expected_num_coords
         );
 
         let num = 11;
-        assert_eq!(FillType::NumCoords(num).to_num_coords(&cuboid), num);
+        assert_eq!(
+            FillType::NumCoords(num)
+                .to_num_coords(&cuboid, None)
+                .unwrap(),
+            num
+        );
+    }
+
+    #[test]
+    fn mass_density_and_molarity_are_converted_to_number_densities() {
+        let mut residue = resbase!["SOL", ("OW", 0.0, 0.0, 0.0)];
+
+        // Without a residue mass the mass density can not be converted
+        assert_eq!(
+            FillType::MassDensity(1.0).number_density(Some(&residue)),
+            None
+        );
+        assert_eq!(FillType::MassDensity(1.0).number_density(None), None);
+
+        // Water at 0.997 g/cm^3 has a number density of about 33.33 molecules per nm^3
+        residue.atoms[0].metadata.mass = Some(18.015);
+        let density = FillType::MassDensity(0.997)
+            .number_density(Some(&residue))
+            .unwrap();
+        assert!((density - 33.33).abs() < 0.01);
+
+        // 1 mol/L is about 0.6022 molecules per nm^3
+        let density = FillType::Molarity(1.0).number_density(None).unwrap();
+        assert!((density - 0.6022).abs() < 1e-4);
+
+        let cuboid = Cuboid {
+            size: Coord::new(10.0, 10.0, 10.0),
+            ..Cuboid::default()
+        };
+
+        assert_eq!(
+            FillType::Molarity(0.15)
+                .to_num_coords(&cuboid, Some(&residue))
+                .unwrap(),
+            90
+        );
+        match FillType::MassDensity(0.997).resolve(Some(&residue)) {
+            Ok(FillType::Density(density)) => assert!((density - 33.33).abs() < 0.01),
+            fill_type => panic!("expected a number density, got {:?}", fill_type),
+        }
+    }
+
+    #[test]
+    fn filling_by_mass_density_without_a_residue_mass_is_an_error() {
+        let residue = resbase!["SOL", ("OW", 0.0, 0.0, 0.0)];
+        let cuboid = Cuboid {
+            residue: Some(residue.clone()),
+            size: Coord::new(1.0, 1.0, 1.0),
+            ..Cuboid::default()
+        };
+
+        assert!(FillType::MassDensity(0.997)
+            .resolve(Some(&residue))
+            .is_err());
+        assert!(cuboid.fill(FillType::MassDensity(0.997)).is_err());
+
+        let ellipsoid = Ellipsoid {
+            name: None,
+            residue: Some(residue),
+            origin: Coord::ORIGO,
+            semi_axes: Coord::new(1.0, 1.0, 1.0),
+            fill_type: None,
+            coords: vec![],
+        };
+        assert!(ellipsoid.fill(FillType::MassDensity(0.997)).is_err());
+    }
+
+    #[test]
+    fn fill_type_is_deserialized_from_a_variant_or_missing() {
+        let cuboid: Cuboid =
+            serde_json::from_str(r#"{"fill_type": {"MassDensity": 0.997}}"#).unwrap();
+        assert_eq!(cuboid.fill_type, Some(FillType::MassDensity(0.997)));
+
+        let cuboid: Cuboid = serde_json::from_str(r#"{}"#).unwrap();
+        assert_eq!(cuboid.fill_type, None);
+    }
+
+    /// A volume which never contains any coordinates, although it has a size.
+    struct Hollow;
+
+    impl Contains for Hollow {
+        fn contains(&self, _: Coord) -> bool {
+            false
+        }
+    }
+
+    impl Volume for Hollow {
+        fn fill(self, _: FillType) -> error::Result<Hollow> {
+            Ok(self)
+        }
+
+        fn volume(&self) -> f64 {
+            1.0
+        }
+    }
+
+    #[test]
+    fn filling_a_volume_which_cannot_contain_coordinates_gives_up() {
+        let size = Coord::new(1.0, 1.0, 1.0);
+
+        match fill_bounding_box(
+            &Hollow,
+            Coord::ORIGO,
+            Coord::ORIGO,
+            size,
+            FillType::NumCoords(10),
+        ) {
+            Err(GrafenError::InvalidParameter { .. }) => (),
+            _ => panic!("filling the volume did not stop"),
+        }
     }
 
     #[test]
@@ -222,7 +710,7 @@ mod tests {
             ..Cuboid::default()
         };
 
-        let pruned = prune_residues_from_volume(&component, &pruning_vol);
+        let pruned = prune_residues_from_volume(&component, &pruning_vol, InclusionPolicy::AnyAtom);
         let atoms = pruned.iter().map(|res| res.get_atoms()).collect::<Vec<_>>();
         assert_eq!(atoms.len(), 2);
         assert_eq!(atoms[0][0].1, coord1_without);
@@ -269,7 +757,7 @@ mod tests {
             ..Cuboid::default()
         };
 
-        let pruned = prune_residues_from_volume(&component, &pruning_vol);
+        let pruned = prune_residues_from_volume(&component, &pruning_vol, InclusionPolicy::AnyAtom);
         let atoms = pruned.iter().map(|res| res.get_atoms()).collect::<Vec<_>>();
         assert_eq!(atoms.len(), 2);
 
@@ -319,7 +807,8 @@ mod tests {
             ..Cuboid::default()
         };
 
-        let contained = keep_residues_within_volume(&component, &containing_vol);
+        let contained =
+            keep_residues_within_volume(&component, &containing_vol, InclusionPolicy::AnyAtom);
         let atoms = contained
             .iter()
             .map(|res| res.get_atoms())
@@ -351,9 +840,50 @@ mod tests {
             ..Cuboid::default()
         };
 
-        let pruned = prune_residues_from_volume(&component, &pruning_vol);
+        let pruned = prune_residues_from_volume(&component, &pruning_vol, InclusionPolicy::AnyAtom);
         let atoms = pruned.iter().map(|res| res.get_atoms()).collect::<Vec<_>>();
         assert_eq!(atoms.len(), 1);
         assert_eq!(atoms[0][0].1, Coord::new(2.5, 0.5, 0.5));
     }
+
+    #[test]
+    fn inclusion_policies_decide_which_residues_are_inside() {
+        let containing_vol = Cuboid {
+            size: Coord::new(1.0, 1.0, 1.0),
+            ..Cuboid::default()
+        };
+
+        let mut residue = resbase![
+            "RES",
+            ("A", 0.0, 0.0, 0.0),
+            ("B", 1.0, 0.0, 0.0) // Shifted by 1
+        ];
+        residue.atoms[0].metadata.mass = Some(12.0);
+        residue.atoms[1].metadata.mass = Some(1.0);
+
+        // Atom A is outside and atom B inside, the center of geometry is inside
+        // and the center of mass (which is close to atom A) is outside
+        let component = Cuboid {
+            residue: Some(residue),
+            coords: vec![Coord::new(-0.3, 0.5, 0.5)],
+            ..Cuboid::default()
+        };
+
+        let is_kept =
+            |policy| keep_residues_within_volume(&component, &containing_vol, policy).len() == 1;
+        let is_pruned =
+            |policy| prune_residues_from_volume(&component, &containing_vol, policy).is_empty();
+
+        assert!(is_kept(InclusionPolicy::AnyAtom));
+        assert!(!is_kept(InclusionPolicy::AllAtoms));
+        assert!(is_kept(InclusionPolicy::CenterOfGeometry));
+        assert!(!is_kept(InclusionPolicy::CenterOfMass));
+        assert!(!is_kept(InclusionPolicy::FirstAtom));
+
+        assert!(is_pruned(InclusionPolicy::AnyAtom));
+        assert!(!is_pruned(InclusionPolicy::AllAtoms));
+        assert!(is_pruned(InclusionPolicy::CenterOfGeometry));
+        assert!(!is_pruned(InclusionPolicy::CenterOfMass));
+        assert!(!is_pruned(InclusionPolicy::FirstAtom));
+    }
 }
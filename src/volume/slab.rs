@@ -0,0 +1,182 @@
+//! Slab objects.
+
+use crate::{
+    coord::{Coord, Direction, Translate},
+    describe::{unwrap_name, Describe},
+    iterator::{ResidueIter, ResidueIterOut},
+    system::{Component, Residue},
+    volume::*,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A slab volume: the space between two parallel planes.
+///
+/// The slab extends infinitely along its planes when checking whether it contains
+/// coordinates. When it is filled the coordinates are placed in a region of the input
+/// `length` and `width` along the planes. The length is along the first and the width
+/// along the second of the two axes in the planes, ie. along y and z for a slab with
+/// its normal along x.
+pub struct Slab {
+    pub name: Option<String>,
+    pub residue: Option<Residue>,
+    /// Normal vector of the planes.
+    pub normal: Direction,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Position of the lower plane, and the corner of the filled region.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Distance between the planes (nm).
+    pub thickness: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Length of the filled region along the first axis in the planes (nm).
+    pub length: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Width of the filled region along the second axis in the planes (nm).
+    pub width: f64,
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub coords: Vec<Coord>,
+}
+
+impl_component![Slab];
+impl_translate![Slab];
+
+impl Slab {
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        match self.normal {
+            Direction::X => Coord::new(self.thickness, self.length, self.width),
+            Direction::Y => Coord::new(self.length, self.thickness, self.width),
+            Direction::Z => Coord::new(self.length, self.width, self.thickness),
+        }
+    }
+}
+
+impl Contains for Slab {
+    fn contains(&self, coord: Coord) -> bool {
+        let dh = match self.normal {
+            Direction::X => coord.x - self.origin.x,
+            Direction::Y => coord.y - self.origin.y,
+            Direction::Z => coord.z - self.origin.z,
+        };
+
+        dh >= 0.0 && dh <= self.thickness
+    }
+}
+
+impl Describe for Slab {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Slab volume of thickness {:.2} with normal {} at {})",
+            unwrap_name(&self.name),
+            self.thickness,
+            self.normal,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Slab volume)", unwrap_name(&self.name))
+    }
+}
+
+impl Volume for Slab {
+    fn fill(self, fill_type: FillType) -> error::Result<Slab> {
+        check_positive("thickness", self.thickness)?;
+        check_positive("length", self.length)?;
+        check_positive("width", self.width)?;
+
+        let coords = fill_bounding_box(
+            &self,
+            self.origin,
+            Coord::ORIGO,
+            self.calc_box_size(),
+            fill_type.resolve(self.residue.as_ref())?,
+        )?;
+
+        Ok(Slab { coords, ..self })
+    }
+
+    /// Return the volume of the filled region.
+    fn volume(&self) -> f64 {
+        self.length * self.width * self.thickness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_slab(normal: Direction) -> Slab {
+        Slab {
+            name: None,
+            residue: None,
+            normal,
+            origin: Coord::new(1.0, 1.0, 1.0),
+            thickness: 2.0,
+            length: 3.0,
+            width: 4.0,
+            fill_type: None,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn slab_contains_coordinates_between_its_planes_only() {
+        let slab = setup_slab(Direction::Y);
+        let err = 1e-9;
+
+        assert!(slab.contains(Coord::new(1.0, 1.0 + err, 1.0)));
+        assert!(slab.contains(Coord::new(1.0, 3.0 - err, 1.0)));
+        assert!(!slab.contains(Coord::new(1.0, 1.0 - err, 1.0)));
+        assert!(!slab.contains(Coord::new(1.0, 3.0 + err, 1.0)));
+
+        // The planes are infinite
+        assert!(slab.contains(Coord::new(-100.0, 2.0, 100.0)));
+    }
+
+    #[test]
+    fn calc_box_size_of_slab_depends_on_normal() {
+        assert_eq!(
+            setup_slab(Direction::X).calc_box_size(),
+            Coord::new(2.0, 3.0, 4.0)
+        );
+        assert_eq!(
+            setup_slab(Direction::Y).calc_box_size(),
+            Coord::new(3.0, 2.0, 4.0)
+        );
+        assert_eq!(
+            setup_slab(Direction::Z).calc_box_size(),
+            Coord::new(3.0, 4.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn fill_slab_places_coordinates_in_the_filled_region() {
+        let num_coords = 100;
+        let slab = setup_slab(Direction::Z)
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap();
+
+        assert_eq!(slab.coords.len(), num_coords as usize);
+
+        for coord in slab.coords {
+            assert!(coord.x >= 0.0 && coord.x <= 3.0);
+            assert!(coord.y >= 0.0 && coord.y <= 4.0);
+            assert!(coord.z >= 0.0 && coord.z <= 2.0);
+        }
+    }
+
+    #[test]
+    fn filling_a_slab_with_a_negative_size_is_an_error() {
+        let slab = Slab {
+            length: -3.0,
+            width: -4.0,
+            ..setup_slab(Direction::Z)
+        };
+
+        assert!(slab.fill(FillType::NumCoords(10)).is_err());
+    }
+}
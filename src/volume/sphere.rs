@@ -20,13 +20,14 @@ use std::f64::consts::PI;
 pub struct Spheroid {
     pub name: Option<String>,
     pub residue: Option<Residue>,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub origin: Coord,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub radius: f64,
-    /// A density may be set for the component.
-    pub density: Option<f64>,
-    #[serde(skip)]
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
     pub coords: Vec<Coord>,
 }
 
@@ -64,24 +65,26 @@ impl Describe for Spheroid {
 }
 
 impl Volume for Spheroid {
-    fn fill(self, fill_type: FillType) -> Spheroid {
+    fn fill(self, fill_type: FillType) -> error::Result<Spheroid> {
+        check_positive("radius", self.radius)?;
+
         match fill_type {
-            FillType::Density(_) => {
+            FillType::Density(_) | FillType::MassDensity(_) | FillType::Molarity(_) => {
                 // Use the filling function from `Cuboid` to generate coordinates to cut from.
                 // This is slightly inefficient, but for now it is easy to keep the generation
                 // in a single function.
                 let box_side = 2.1 * self.radius;
                 let size = Coord::new(box_side, box_side, box_side);
 
-                Cuboid {
+                Ok(Cuboid {
                     name: self.name,
                     residue: self.residue,
                     origin: self.origin,
                     size,
                     ..Cuboid::default()
                 }
-                .fill(fill_type)
-                .to_sphere(self.radius)
+                .fill(fill_type)?
+                .to_sphere(self.radius))
             }
             FillType::NumCoords(num_coords) => {
                 // To fill with an exact number of coordinates, generate them explictly.
@@ -108,10 +111,10 @@ impl Volume for Spheroid {
 
                 let coords = (0..num_coords).map(|_| gen_coord()).collect::<Vec<_>>();
 
-                Spheroid {
+                Ok(Spheroid {
                     coords,
                     ..self.clone()
-                }
+                })
             }
         }
     }
@@ -0,0 +1,181 @@
+//! Toroidal objects.
+
+use crate::{
+    coord::{Coord, Direction, Translate},
+    describe::{unwrap_name, Describe},
+    iterator::{ResidueIter, ResidueIterOut},
+    system::{Component, Residue},
+    volume::*,
+};
+
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A toroidal volume, ie. a ring with a circular cross section.
+pub struct Torus {
+    pub name: Option<String>,
+    pub residue: Option<Residue>,
+    /// Axis of rotational symmetry.
+    pub alignment: Direction,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Center of the torus.
+    pub origin: Coord,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Distance from the center to the center of the tube (nm).
+    pub major_radius: f64,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    /// Radius of the tube (nm).
+    pub minor_radius: f64,
+    #[serde(default)]
+    /// A default fill type may be set for the component.
+    pub fill_type: Option<FillType>,
+    #[serde(default, skip_serializing_if = "crate::system::is_default")]
+    pub coords: Vec<Coord>,
+}
+
+impl_component![Torus];
+impl_translate![Torus];
+
+impl Torus {
+    /// Calculate the box size.
+    fn calc_box_size(&self) -> Coord {
+        let outer_radius = self.major_radius + self.minor_radius;
+        align_size(2.0 * outer_radius, 2.0 * self.minor_radius, self.alignment)
+    }
+}
+
+impl Contains for Torus {
+    fn contains(&self, coord: Coord) -> bool {
+        let (dr, dh) = self.origin.distance_cylindrical(coord, self.alignment);
+
+        (dr - self.major_radius).powi(2) + dh.powi(2) <= self.minor_radius.powi(2)
+    }
+}
+
+impl Describe for Torus {
+    fn describe(&self) -> String {
+        format!(
+            "{} (Toroidal volume of radii {:.2} and {:.2} at {})",
+            unwrap_name(&self.name),
+            self.major_radius,
+            self.minor_radius,
+            self.origin
+        )
+    }
+
+    fn describe_short(&self) -> String {
+        format!("{} (Toroidal volume)", unwrap_name(&self.name))
+    }
+}
+
+impl Volume for Torus {
+    fn fill(self, fill_type: FillType) -> error::Result<Torus> {
+        check_positive("major radius", self.major_radius)?;
+        check_positive("minor radius", self.minor_radius)?;
+
+        // The volume of a spindle torus is not that of its tube, which would fill it too densely
+        check_relation(
+            "minor radius",
+            self.minor_radius <= self.major_radius,
+            "not be larger than the major radius",
+        )?;
+
+        let outer_radius = self.major_radius + self.minor_radius;
+        let lower = align_size(-outer_radius, -self.minor_radius, self.alignment);
+
+        let coords = fill_bounding_box(
+            &self,
+            self.origin,
+            lower,
+            self.calc_box_size(),
+            fill_type.resolve(self.residue.as_ref())?,
+        )?;
+
+        Ok(Torus { coords, ..self })
+    }
+
+    fn volume(&self) -> f64 {
+        2.0 * PI.powi(2) * self.major_radius * self.minor_radius.powi(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_torus(major_radius: f64, minor_radius: f64) -> Torus {
+        Torus {
+            name: None,
+            residue: None,
+            alignment: Direction::Z,
+            origin: Coord::ORIGO,
+            major_radius,
+            minor_radius,
+            fill_type: None,
+            coords: vec![],
+        }
+    }
+
+    #[test]
+    fn torus_contains_coordinates_within_its_tube_but_not_its_hole() {
+        let torus = setup_torus(2.0, 0.5);
+        let err = 1e-9;
+
+        assert!(torus.contains(Coord::new(2.0, 0.0, 0.0)));
+        assert!(torus.contains(Coord::new(0.0, -2.5 + err, 0.0)));
+        assert!(torus.contains(Coord::new(1.5 + err, 0.0, 0.0)));
+        assert!(torus.contains(Coord::new(2.0, 0.0, 0.5 - err)));
+
+        assert!(!torus.contains(Coord::new(0.0, 0.0, 0.0)));
+        assert!(!torus.contains(Coord::new(1.5 - err, 0.0, 0.0)));
+        assert!(!torus.contains(Coord::new(0.0, 2.5 + err, 0.0)));
+        assert!(!torus.contains(Coord::new(2.0, 0.0, 0.5 + err)));
+    }
+
+    #[test]
+    fn calc_box_size_of_torus_depends_on_alignment() {
+        let torus = Torus {
+            alignment: Direction::Y,
+            ..setup_torus(2.0, 0.5)
+        };
+
+        assert_eq!(torus.calc_box_size(), Coord::new(5.0, 1.0, 5.0));
+    }
+
+    #[test]
+    fn fill_torus_with_number_of_coordinates_places_them_inside() {
+        let num_coords = 100;
+        let torus = setup_torus(2.0, 0.5)
+            .fill(FillType::NumCoords(num_coords))
+            .unwrap();
+
+        assert_eq!(torus.coords.len(), num_coords as usize);
+
+        for &coord in &torus.coords {
+            assert!(torus.contains(torus.origin + coord));
+        }
+    }
+
+    #[test]
+    fn filling_a_torus_with_a_negative_radius_is_an_error() {
+        match setup_torus(2.0, -0.5).fill(FillType::NumCoords(10)) {
+            Err(GrafenError::InvalidParameter { parameter, .. }) => {
+                assert_eq!(parameter, "minor radius")
+            }
+            _ => panic!("a torus with a negative radius was filled"),
+        }
+    }
+
+    #[test]
+    fn filling_a_torus_with_a_minor_radius_larger_than_the_major_is_an_error() {
+        match setup_torus(0.5, 1.0).fill(FillType::Density(10.0)) {
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "invalid minor radius: it must not be larger than the major radius"
+            ),
+            _ => panic!("a spindle torus was filled"),
+        }
+
+        assert!(setup_torus(1.0, 1.0).fill(FillType::NumCoords(10)).is_ok());
+    }
+}